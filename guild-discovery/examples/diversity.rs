@@ -0,0 +1,214 @@
+// 주소 다양성 규칙 확인
+//
+// 한 /24 대역에서 많은 노드를 띄운 공격자를 흉내 내서 다음을 확인한다:
+//   1. DHT 버킷에는 같은 그룹 노드가 제한만큼만 들어가고 교체 후보에도 쌓이지 않음
+//   2. 교체 후보 승격 시 그룹 제한을 넘는 후보는 건너뜀
+//   3. 주소록은 그룹이 가득 차면 오래 유지된 주소를 지키고, 실패 중인 주소만 밀어냄
+//   4. 부트스트랩 피어 목록도 같은 제한을 따름
+//   5. 탐색 결과에서 연결 대상은 그룹마다 제한된 수만 고름
+//   6. 루프백/사설 주소는 기본적으로 제한하지 않음
+//
+// 실행: cargo run -p guild-discovery --example diversity
+use async_trait::async_trait;
+use guild_discovery::{
+    AddressBook, Bootstrap, DhtConfig, Discovery, DiscoveryConfig, DiscoveryMethod,
+    DiversityConfig, Kademlia, Node, NodeId, PeerInfo, PeerSource,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+fn addr(s: &str) -> SocketAddr {
+    s.parse().unwrap()
+}
+
+fn node(id: NodeId, addr: SocketAddr) -> Node {
+    Node {
+        id,
+        addr,
+        last_seen: 0,
+    }
+}
+
+fn peer(addr: SocketAddr) -> PeerInfo {
+    PeerInfo {
+        addr,
+        node_id: NodeId::from_addr(&addr).0,
+        last_seen: 0,
+    }
+}
+
+/// 정해진 주소 목록을 돌려주는 탐색 방법
+struct Fixed(Vec<SocketAddr>);
+
+#[async_trait]
+impl DiscoveryMethod for Fixed {
+    fn name(&self) -> &str {
+        "fixed"
+    }
+
+    async fn discover_peers(&self) -> Vec<SocketAddr> {
+        self.0.clone()
+    }
+
+    async fn announce(&self, _addr: SocketAddr) {}
+}
+
+#[tokio::main]
+async fn main() {
+    guild_logger::init_logger(true);
+    let diversity = DiversityConfig::default();
+
+    // 1. 버킷 제한 (전송 계층이 없으므로 PING 없이 바로 결정됨)
+    let own_id = NodeId::random();
+    let dht = Kademlia::with_config(
+        own_id,
+        DhtConfig {
+            k: 4,
+            ..DhtConfig::default()
+        },
+    );
+    for i in 1..=5 {
+        let id = own_id.random_in_bucket(0);
+        dht.add_node(node(id, addr(&format!("198.51.100.{}:42000", i))))
+            .await;
+    }
+    let bucket = dht.bucket_nodes(0).await;
+    println!(
+        "1) bucket holds {} nodes from 198.51.100.0/24",
+        bucket.len()
+    );
+    assert_eq!(bucket.len(), diversity.max_per_group_per_bucket);
+    assert!(dht.bucket_replacements(0).await.is_empty());
+
+    // 다른 그룹의 노드로 버킷을 채우면 나머지는 교체 후보로
+    for i in 1..=3 {
+        let id = own_id.random_in_bucket(0);
+        dht.add_node(node(id, addr(&format!("203.0.{}.1:42000", i))))
+            .await;
+    }
+    let bucket = dht.bucket_nodes(0).await;
+    let replacements = dht.bucket_replacements(0).await;
+    assert_eq!(bucket.len(), 4);
+    assert_eq!(replacements.len(), 1);
+
+    // 2. 같은 그룹 후보는 승격되지 않음 - 198.51.100.0/24 후보를 넣고 다른 그룹 노드를 제거
+    let mut fresh = DhtConfig {
+        k: 3,
+        ..DhtConfig::default()
+    };
+    fresh.diversity.max_per_group_per_bucket = 1;
+    let dht = Kademlia::with_config(own_id, fresh);
+    let a = own_id.random_in_bucket(0);
+    let b = own_id.random_in_bucket(0);
+    let c = own_id.random_in_bucket(0);
+    dht.add_node(node(a, addr("198.51.100.1:42000"))).await;
+    dht.add_node(node(b, addr("203.0.113.1:42000"))).await;
+    dht.add_node(node(c, addr("192.0.2.1:42000"))).await;
+    let sybil = own_id.random_in_bucket(0);
+    let honest = own_id.random_in_bucket(0);
+    dht.add_node(node(honest, addr("100.64.0.1:42000"))).await;
+    dht.add_node(node(sybil, addr("198.51.100.2:42000"))).await;
+    // 가득 찬 그룹의 노드는 교체 후보에도 들어가지 않음
+    let replacements: Vec<NodeId> = dht
+        .bucket_replacements(0)
+        .await
+        .iter()
+        .map(|n| n.id)
+        .collect();
+    assert_eq!(replacements, vec![honest]);
+    dht.mark_failed(&b).await;
+    let bucket: Vec<NodeId> = dht.bucket_nodes(0).await.iter().map(|n| n.id).collect();
+    println!("2) promoted {} after eviction", &honest.to_hex()[..8]);
+    assert!(bucket.contains(&honest) && !bucket.contains(&sybil));
+
+    // 3. 주소록
+    let book = AddressBook::in_memory();
+    book.set_diversity(DiversityConfig {
+        max_per_group_in_address_book: 2,
+        ..DiversityConfig::default()
+    });
+    book.record_success(addr("198.51.100.1:42000"), PeerSource::Bootstrap)
+        .await;
+    book.record_success(addr("198.51.100.2:42000"), PeerSource::Bootstrap)
+        .await;
+    book.record_success(addr("198.51.100.3:42000"), PeerSource::Pex)
+        .await;
+    assert_eq!(book.len().await, 2, "newcomer is refused");
+    assert!(book.get(&addr("198.51.100.3:42000")).await.is_none());
+
+    // 실패 중인 주소는 새 주소에 자리를 내줌
+    book.record_failure(addr("198.51.100.2:42000")).await;
+    book.record_success(addr("198.51.100.3:42000"), PeerSource::Pex)
+        .await;
+    println!(
+        "3) address book keeps {} entries per group",
+        book.len().await
+    );
+    assert!(book.get(&addr("198.51.100.2:42000")).await.is_none());
+    assert!(book.get(&addr("198.51.100.3:42000")).await.is_some());
+    assert!(book.get(&addr("198.51.100.1:42000")).await.is_some());
+
+    // 4. 부트스트랩 피어 목록
+    let bootstrap = Bootstrap::new(Vec::new()).with_diversity(DiversityConfig {
+        max_per_group_in_address_book: 3,
+        ..DiversityConfig::default()
+    });
+    let mut accepted = 0;
+    for i in 1..=10 {
+        if bootstrap
+            .add_peer(peer(addr(&format!("198.51.100.{}:42000", i))))
+            .await
+        {
+            accepted += 1;
+        }
+    }
+    assert!(bootstrap.add_peer(peer(addr("203.0.113.1:42000"))).await);
+    println!("4) bootstrap accepted {} of 10 same-group peers", accepted);
+    assert_eq!(accepted, 3);
+    assert_eq!(bootstrap.get_peers().await.len(), 4);
+
+    // 5. 연결 대상 선택 - 공격자 주소가 먼저 나와도 다른 그룹이 자리를 얻음
+    let mut found: Vec<SocketAddr> = (1..=20)
+        .map(|i| addr(&format!("198.51.100.{}:42000", i)))
+        .collect();
+    found.extend((1..=4).map(|i| addr(&format!("203.0.{}.1:42000", i))));
+    let config = DiscoveryConfig {
+        enable_dht: false,
+        enable_mdns: false,
+        enable_scan: false,
+        max_peers: 6,
+        ..DiscoveryConfig::default()
+    };
+    let discovery = Discovery::new(config);
+    discovery.add_method(Arc::new(Fixed(found)));
+    let picked: Vec<SocketAddr> = discovery
+        .discover()
+        .await
+        .into_iter()
+        .map(|p| p.addr)
+        .collect();
+    println!("5) picked {:?}", picked);
+    assert_eq!(picked.len(), 6);
+    let sybils = picked
+        .iter()
+        .filter(|a| a.ip().to_string().starts_with("198.51.100."))
+        .count();
+    assert_eq!(sybils, diversity.max_outbound_per_group);
+
+    // 6. 로컬 주소는 제한하지 않음 (설정으로 켤 수 있음)
+    let local: Vec<SocketAddr> = (1..=5)
+        .map(|i| addr(&format!("127.0.0.1:{}", 42000 + i)))
+        .collect();
+    assert_eq!(
+        diversity.select_outbound(local.clone(), |a| *a, 10).len(),
+        5
+    );
+    let strict = DiversityConfig {
+        limit_local_addresses: true,
+        ..DiversityConfig::default()
+    };
+    assert_eq!(strict.select_outbound(local, |a| *a, 10).len(), 2);
+    println!("6) local addresses are exempt unless limit_local_addresses is set");
+
+    println!("✅ all diversity rules hold");
+}
@@ -0,0 +1,428 @@
+// 주소록 - 연결에 성공한 적 있는 피어를 data_dir에 저장해서 재시작 후에도 재사용
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+
+use crate::diversity::DiversityConfig;
+use crate::log_discovery;
+
+/// 파일 포맷 버전 (구조가 바뀌면 증가)
+const FILE_VERSION: u32 = 1;
+
+/// 한 번도 연결되지 않은 상태로 이만큼 실패하면 압축 시 제거
+const MAX_FAILURES_WITHOUT_SUCCESS: u32 = 3;
+
+/// 피어를 처음 알게 된 경로
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PeerSource {
+    Bootstrap,
+    Scan,
+    /// 다른 피어와의 피어 목록 교환 (Peer Exchange)
+    Pex,
+    Dht,
+    /// 직접 추가됨
+    Manual,
+    /// LAN mDNS
+    Mdns,
+    /// 정적 피어 파일
+    Static,
+}
+
+/// 주소별 기록
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressEntry {
+    pub addr: SocketAddr,
+    pub source: PeerSource,
+    /// 처음으로 연결에 성공한 시각 (unix 초)
+    pub first_seen: u64,
+    /// 마지막으로 연결에 성공한 시각 (unix 초)
+    pub last_seen: u64,
+    /// 마지막으로 연결을 시도한 시각 (unix 초)
+    pub last_attempt: u64,
+    pub successes: u32,
+    pub failures: u32,
+    /// 마지막 성공 이후 연속 실패 횟수
+    pub consecutive_failures: u32,
+}
+
+impl AddressEntry {
+    /// 살아 있는 것을 확인한 기간 (first_seen부터 last_seen까지, 초)
+    pub fn tenure(&self) -> u64 {
+        self.last_seen.saturating_sub(self.first_seen)
+    }
+
+    /// 다이얼 우선순위 (클수록 먼저 시도)
+    ///
+    /// 정상인 주소 중에서는 오래 살아 있었고 연결에 여러 번 성공한 주소를 앞에 둔다. 공격자가
+    /// 새로 띄운 노드로 주소록 앞쪽을 빠르게 채우지 못하도록, 기간과 성공 횟수는 log2 단위로
+    /// 비교하고 그다음에 최근 확인 시각을 본다.
+    fn score(&self) -> (bool, u32, u32, u64, std::cmp::Reverse<u32>) {
+        let tenure_hours = self.tenure() / 3600;
+        (
+            self.consecutive_failures == 0,
+            (tenure_hours + 1).ilog2(),
+            self.successes.saturating_add(1).ilog2(),
+            self.last_seen,
+            std::cmp::Reverse(self.failures),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct AddressBookFile {
+    version: u32,
+    entries: Vec<AddressEntry>,
+}
+
+/// 영속 주소록
+pub struct AddressBook {
+    path: Option<PathBuf>,
+    entries: RwLock<HashMap<SocketAddr, AddressEntry>>,
+    // 같은 주소 그룹(/24, /48) 주소 수 제한
+    diversity: std::sync::RwLock<DiversityConfig>,
+}
+
+impl AddressBook {
+    /// 파일 없이 메모리에만 유지
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: RwLock::new(HashMap::new()),
+            diversity: std::sync::RwLock::new(DiversityConfig::default()),
+        }
+    }
+
+    /// 파일에서 불러오기 (없거나 읽을 수 없으면 빈 주소록으로 시작)
+    pub async fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = HashMap::new();
+
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => match bincode::deserialize::<AddressBookFile>(&bytes) {
+                Ok(file) if file.version == FILE_VERSION => {
+                    for entry in file.entries {
+                        entries.insert(entry.addr, entry);
+                    }
+                    let count = entries.len();
+                    let path_str = path.display().to_string();
+                    log_discovery!("📒 Loaded {} addresses from {}", count, path_str);
+                }
+                Ok(file) => {
+                    let version = file.version;
+                    log_discovery!("⚠️ Ignoring address book version {}", version);
+                }
+                Err(e) => {
+                    let error = e.to_string();
+                    log_discovery!("⚠️ Corrupted address book, starting empty: {}", error);
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                let error = e.to_string();
+                log_discovery!("⚠️ Failed to read address book: {}", error);
+            }
+        }
+
+        Self {
+            path: Some(path),
+            entries: RwLock::new(entries),
+            diversity: std::sync::RwLock::new(DiversityConfig::default()),
+        }
+    }
+
+    /// 주소 그룹 제한 변경 (이미 저장된 주소에는 적용하지 않음)
+    pub fn set_diversity(&self, diversity: DiversityConfig) {
+        *self.diversity.write().unwrap() = diversity;
+    }
+
+    /// 파일에 저장 (임시 파일에 쓴 뒤 교체)
+    pub async fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let file = AddressBookFile {
+            version: FILE_VERSION,
+            entries: self.entries.read().await.values().cloned().collect(),
+        };
+        let bytes = bincode::serialize(&file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp = path.with_extension("tmp");
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, path).await
+    }
+
+    /// 연결 성공 기록 (처음이면 새로 추가)
+    ///
+    /// 같은 주소 그룹의 주소가 이미 제한만큼 있으면 그중 계속 실패하고 있는 주소를 밀어내고,
+    /// 모두 정상이면 오래 유지된 주소를 지키기 위해 새 주소는 저장하지 않는다.
+    pub async fn record_success(&self, addr: SocketAddr, source: PeerSource) {
+        let now = now_secs();
+        let mut entries = self.entries.write().await;
+        if !entries.contains_key(&addr) && !self.make_room(&mut entries, &addr) {
+            log_discovery!("🛡️ Address group of {} is full, not recording it", addr);
+            return;
+        }
+        let entry = entries.entry(addr).or_insert_with(|| AddressEntry {
+            addr,
+            source,
+            first_seen: now,
+            last_seen: 0,
+            last_attempt: 0,
+            successes: 0,
+            failures: 0,
+            consecutive_failures: 0,
+        });
+        entry.last_seen = now;
+        entry.last_attempt = now;
+        entry.successes = entry.successes.saturating_add(1);
+        entry.consecutive_failures = 0;
+    }
+
+    /// 연결된 피어와 정상적으로 주고받았을 때 마지막 확인 시각만 갱신 (주소록에 없으면 false)
+    ///
+    /// 연결 성공 횟수는 늘리지 않는다 (Pong마다 호출되므로).
+    pub async fn touch(&self, addr: SocketAddr) -> bool {
+        match self.entries.write().await.get_mut(&addr) {
+            Some(entry) => {
+                entry.last_seen = now_secs();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 새 주소가 들어갈 자리가 있는지 확인하고, 필요하면 같은 그룹에서 실패 중인 주소 제거
+    fn make_room(
+        &self,
+        entries: &mut HashMap<SocketAddr, AddressEntry>,
+        addr: &SocketAddr,
+    ) -> bool {
+        let diversity = self.diversity.read().unwrap().clone();
+        let limit = diversity.max_per_group_in_address_book;
+        if !diversity.group_full(addr, entries.keys(), limit) {
+            return true;
+        }
+        let group = diversity.group(addr);
+        let worst = entries
+            .values()
+            .filter(|entry| entry.consecutive_failures > 0 && diversity.group(&entry.addr) == group)
+            .min_by_key(|entry| (entry.score(), entry.successes))
+            .map(|entry| entry.addr);
+        match worst {
+            Some(worst) => {
+                entries.remove(&worst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 연결 실패 기록 (주소록에 있는 주소만 - 추측한 주소로 주소록이 오염되지 않도록)
+    pub async fn record_failure(&self, addr: SocketAddr) {
+        if let Some(entry) = self.entries.write().await.get_mut(&addr) {
+            entry.last_attempt = now_secs();
+            entry.failures = entry.failures.saturating_add(1);
+            entry.consecutive_failures = entry.consecutive_failures.saturating_add(1);
+        }
+    }
+
+    /// 주소 삭제 (삭제한 항목 반환)
+    pub async fn remove(&self, addr: &SocketAddr) -> Option<AddressEntry> {
+        self.entries.write().await.remove(addr)
+    }
+
+    pub async fn get(&self, addr: &SocketAddr) -> Option<AddressEntry> {
+        self.entries.read().await.get(addr).cloned()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.entries.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.entries.read().await.is_empty()
+    }
+
+    /// 다이얼할 주소 목록 (오래 살아 있었고 자주 성공한 주소 우선)
+    pub async fn candidates(&self, limit: usize) -> Vec<SocketAddr> {
+        let mut entries: Vec<AddressEntry> = self.entries.read().await.values().cloned().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score()));
+        entries
+            .into_iter()
+            .take(limit)
+            .map(|entry| entry.addr)
+            .collect()
+    }
+
+    /// 오래되었거나 계속 실패하는 주소를 제거하고 최대 개수 유지
+    ///
+    /// 제거된 주소 수를 반환
+    pub async fn compact(&self, max_age_secs: u64, max_entries: usize) -> usize {
+        let now = now_secs();
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+
+        entries.retain(|_, entry| {
+            let stale = now.saturating_sub(entry.last_seen) > max_age_secs;
+            let unreachable = entry.consecutive_failures >= MAX_FAILURES_WITHOUT_SUCCESS
+                && entry.successes <= entry.failures;
+            !stale && !unreachable
+        });
+
+        if entries.len() > max_entries {
+            let mut ranked: Vec<(SocketAddr, _)> = entries
+                .iter()
+                .map(|(addr, entry)| (*addr, entry.score()))
+                .collect();
+            ranked.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+            for (addr, _) in ranked.into_iter().skip(max_entries) {
+                entries.remove(&addr);
+            }
+        }
+
+        before - entries.len()
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn full_group_keeps_healthy_entries() {
+        let book = AddressBook::in_memory();
+        for i in 1..=8 {
+            book.record_success(addr(&format!("203.0.113.{}:42000", i)), PeerSource::Dht)
+                .await;
+        }
+        book.record_success(addr("203.0.113.9:42000"), PeerSource::Dht)
+            .await;
+        assert_eq!(book.len().await, 8);
+        assert!(book.get(&addr("203.0.113.9:42000")).await.is_none());
+
+        // 다른 /24는 영향 없음
+        book.record_success(addr("203.0.114.1:42000"), PeerSource::Dht)
+            .await;
+        assert_eq!(book.len().await, 9);
+    }
+
+    #[tokio::test]
+    async fn full_group_evicts_failing_entry() {
+        let book = AddressBook::in_memory();
+        for i in 1..=8 {
+            book.record_success(
+                addr(&format!("[2001:db8:1:{:x}::1]:42000", i)),
+                PeerSource::Dht,
+            )
+            .await;
+        }
+        book.record_failure(addr("[2001:db8:1:3::1]:42000")).await;
+
+        // 같은 /48의 새 주소가 실패 중인 주소를 밀어냄
+        book.record_success(addr("[2001:db8:1:ffff::1]:42000"), PeerSource::Dht)
+            .await;
+        assert_eq!(book.len().await, 8);
+        assert!(book.get(&addr("[2001:db8:1:3::1]:42000")).await.is_none());
+        assert!(book
+            .get(&addr("[2001:db8:1:ffff::1]:42000"))
+            .await
+            .is_some());
+
+        // 이제 모두 정상이므로 더 들어가지 않음
+        book.record_success(addr("[2001:db8:1:fffe::1]:42000"), PeerSource::Dht)
+            .await;
+        assert_eq!(book.len().await, 8);
+    }
+
+    fn entry(addr_str: &str, first_seen: u64, last_seen: u64, successes: u32) -> AddressEntry {
+        AddressEntry {
+            addr: addr(addr_str),
+            source: PeerSource::Dht,
+            first_seen,
+            last_seen,
+            last_attempt: last_seen,
+            successes,
+            failures: 0,
+            consecutive_failures: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn candidates_prefer_long_lived_peers() {
+        let now = now_secs();
+        let book = AddressBook::in_memory();
+        {
+            let mut entries = book.entries.write().await;
+            for entry in [
+                // 방금 나타난 주소 (가장 최근에 확인)
+                entry("198.51.100.1:42000", now, now, 1),
+                // 한 달 동안 살아 있던 주소
+                entry("203.0.113.1:42000", now - 30 * 86400, now - 60, 40),
+                // 하루 동안 살아 있던 주소
+                entry("192.0.2.1:42000", now - 86400, now - 30, 3),
+            ] {
+                entries.insert(entry.addr, entry);
+            }
+        }
+        assert_eq!(
+            book.candidates(3).await,
+            vec![
+                addr("203.0.113.1:42000"),
+                addr("192.0.2.1:42000"),
+                addr("198.51.100.1:42000"),
+            ]
+        );
+
+        // 실패 중인 주소는 오래 살아 있었어도 뒤로
+        book.record_failure(addr("203.0.113.1:42000")).await;
+        assert_eq!(book.candidates(1).await, vec![addr("192.0.2.1:42000")]);
+    }
+
+    #[tokio::test]
+    async fn first_seen_is_kept_across_successes() {
+        let book = AddressBook::in_memory();
+        let peer = addr("203.0.113.1:42000");
+        book.record_success(peer, PeerSource::Dht).await;
+        let first_seen = book.get(&peer).await.unwrap().first_seen;
+        book.entries
+            .write()
+            .await
+            .get_mut(&peer)
+            .unwrap()
+            .first_seen -= 3600;
+        book.record_success(peer, PeerSource::Dht).await;
+
+        let entry = book.get(&peer).await.unwrap();
+        assert_eq!(entry.first_seen, first_seen - 3600);
+        assert_eq!(entry.successes, 2);
+        assert!(entry.tenure() >= 3600);
+    }
+
+    #[tokio::test]
+    async fn local_addresses_are_not_limited() {
+        let book = AddressBook::in_memory();
+        for i in 1..=20 {
+            book.record_success(addr(&format!("192.168.1.{}:42000", i)), PeerSource::Mdns)
+                .await;
+        }
+        assert_eq!(book.len().await, 20);
+    }
+}
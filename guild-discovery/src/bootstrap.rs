@@ -1,29 +1,183 @@
 // Bootstrap 기반 피어 발견
-use std::net::SocketAddr;
+//
+// 부트스트랩 항목은 IP 주소(`1.2.3.4:9000`, `[::1]:9000`) 또는 호스트 이름(`guild-home:8000`,
+// `seed.guild.network`)이다. 호스트 이름은 A/AAAA 레코드를 모두 조회하므로, 여러 노드 주소를
+// 등록해 둔 DNS 시드 하나로 많은 피어를 얻을 수 있다. 조회 결과는 캐시해 두고
+// resolve_interval이 지나면 다시 조회하며, 실패하면 마지막으로 성공한 주소를 계속 쓴다.
+// 실패한 호스트는 RESOLVE_RETRY_BASE부터 두 배씩 늘어나는 간격(최대 resolve_interval)으로 재시도한다.
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 
+use crate::address_book::PeerSource;
+use crate::discovery::DiscoveryMethod;
+use crate::diversity::DiversityConfig;
+use crate::local_scan::DEFAULT_PORT;
+use crate::log_discovery;
+
+/// 호스트 이름 재조회 주기
+pub const DEFAULT_RESOLVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// 호스트 이름 하나의 조회 제한 시간
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 조회에 실패한 호스트의 첫 재시도 간격 (연속 실패마다 두 배)
+pub const RESOLVE_RETRY_BASE: Duration = Duration::from_secs(30);
+/// DNS 시드 하나에서 받아들이는 최대 주소 수
+pub const MAX_ADDRS_PER_SEED: usize = 64;
+/// 기본 최대 알려진 피어 수
+pub const MAX_KNOWN_PEERS: usize = 1000;
+
+/// 부트스트랩 항목
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BootstrapEntry {
+    Addr(SocketAddr),
+    /// DNS로 조회할 호스트 이름과 포트
+    Host { host: String, port: u16 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBootstrapEntry(pub String);
+
+impl fmt::Display for InvalidBootstrapEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid bootstrap entry {:?}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidBootstrapEntry {}
+
+impl BootstrapEntry {
+    /// `addr:port`, `[v6]:port`, `host:port` 또는 `host` (포트가 없으면 DEFAULT_PORT)
+    pub fn parse(entry: &str) -> Result<Self, InvalidBootstrapEntry> {
+        let entry = entry.trim();
+        let invalid = || InvalidBootstrapEntry(entry.to_string());
+
+        if let Ok(addr) = entry.parse::<SocketAddr>() {
+            return Ok(BootstrapEntry::Addr(addr));
+        }
+        if let Ok(ip) = entry.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            return Ok(BootstrapEntry::Addr(SocketAddr::new(ip, DEFAULT_PORT)));
+        }
+
+        let (host, port) = match entry.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse::<u16>().map_err(|_| invalid())?),
+            None => (entry, DEFAULT_PORT),
+        };
+        let valid_host = !host.is_empty()
+            && host.len() <= 253
+            && host
+                .split('.')
+                .all(|label| !label.is_empty() && label.len() <= 63 && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+        if !valid_host || port == 0 {
+            return Err(invalid());
+        }
+        Ok(BootstrapEntry::Host { host: host.to_string(), port })
+    }
+}
+
+impl fmt::Display for BootstrapEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BootstrapEntry::Addr(addr) => write!(f, "{}", addr),
+            BootstrapEntry::Host { host, port } => write!(f, "{}:{}", host, port),
+        }
+    }
+}
+
+/// 호스트 이름 조회 결과
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub entry: String,
+    /// 마지막으로 성공한 조회 결과 (실패해도 유지)
+    pub addrs: Vec<SocketAddr>,
+    /// 마지막 조회가 실패했으면 에러 메시지
+    pub error: Option<String>,
+    /// 연속 실패 횟수 (성공하면 0)
+    pub failures: u32,
+    resolved_at: Instant,
+}
+
+impl Resolution {
+    // 다시 조회할 때가 되었는지 (실패했으면 지수 백오프)
+    fn is_due(&self, resolve_interval: Duration) -> bool {
+        self.resolved_at.elapsed() >= retry_delay(self.failures, resolve_interval)
+    }
+}
+
+/// 연속 실패 횟수에 따른 재조회 간격 (실패가 없으면 resolve_interval)
+pub(crate) fn retry_delay(failures: u32, resolve_interval: Duration) -> Duration {
+    if failures == 0 {
+        return resolve_interval;
+    }
+    let factor = 1u32.checked_shl(failures - 1).unwrap_or(u32::MAX);
+    RESOLVE_RETRY_BASE.saturating_mul(factor).min(resolve_interval)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PeerInfo {
     pub addr: SocketAddr,
     pub node_id: [u8; 32],
+    /// 마지막으로 연결하거나 응답을 받은 시각 (unix 초)
     pub last_seen: u64,
 }
 
+/// 최근에 확인한 피어가 앞에 오도록 정렬하고 max개까지만 유지 (가장 오래 소식이 없는 피어부터 제거)
+pub(crate) fn keep_freshest(peers: &mut Vec<PeerInfo>, max: usize) {
+    peers.sort_by_key(|p| std::cmp::Reverse(p.last_seen));
+    peers.truncate(max);
+}
+
+/// last_seen이 max_age_secs보다 오래된 피어 제거 (제거한 수 반환)
+pub(crate) fn remove_stale(peers: &mut Vec<PeerInfo>, max_age_secs: u64) -> usize {
+    let now = now_secs();
+    let before = peers.len();
+    peers.retain(|p| now.saturating_sub(p.last_seen) < max_age_secs);
+    before - peers.len()
+}
+
 pub struct Bootstrap {
     bootstrap_nodes: Vec<String>,
     known_peers: Arc<RwLock<Vec<PeerInfo>>>,
+    resolve_interval: Duration,
+    // 호스트 이름 항목별 조회 결과
+    resolutions: RwLock<HashMap<String, Resolution>>,
+    // 같은 주소 그룹(/24, /48) 피어 수 제한
+    diversity: DiversityConfig,
+    max_known_peers: usize,
 }
 
 impl Bootstrap {
     pub fn new(bootstrap_nodes: Vec<String>) -> Self {
+        Self::with_resolve_interval(bootstrap_nodes, DEFAULT_RESOLVE_INTERVAL)
+    }
+    
+    pub fn with_resolve_interval(bootstrap_nodes: Vec<String>, resolve_interval: Duration) -> Self {
         Self {
             bootstrap_nodes,
             known_peers: Arc::new(RwLock::new(Vec::new())),
+            resolve_interval,
+            resolutions: RwLock::new(HashMap::new()),
+            diversity: DiversityConfig::default(),
+            max_known_peers: MAX_KNOWN_PEERS,
         }
     }
     
+    /// 알려진 피어 목록에 적용할 주소 그룹 제한
+    pub fn with_diversity(mut self, diversity: DiversityConfig) -> Self {
+        self.diversity = diversity;
+        self
+    }
+    
+    /// 보관할 최대 알려진 피어 수
+    pub fn with_max_known_peers(mut self, max_known_peers: usize) -> Self {
+        self.max_known_peers = max_known_peers;
+        self
+    }
+    
     pub fn with_defaults() -> Self {
         // 기본 부트스트랩 노드들 (실제 서비스에서는 여러 지역의 안정적인 노드들)
         let defaults = vec![
@@ -34,20 +188,116 @@ impl Bootstrap {
         Self::new(defaults)
     }
     
+    /// 부트스트랩 주소 목록 (호스트 이름은 조회해서 모든 주소로 펼침)
+    ///
+    /// 조회 결과가 resolve_interval보다 오래되었으면 다시 조회한다. 잘못된 항목과
+    /// 조회 실패는 로그로 남기고 resolution_report()로 확인할 수 있다.
     pub async fn connect_bootstrap(&self) -> Vec<SocketAddr> {
         let mut connected = Vec::new();
+        let mut hosts = Vec::new();
         
         for node in &self.bootstrap_nodes {
-            if let Ok(addr) = node.parse::<SocketAddr>() {
-                // 실제로는 여기서 연결 시도
+            match BootstrapEntry::parse(node) {
+                Ok(BootstrapEntry::Addr(addr)) => {
+                    if !connected.contains(&addr) {
+                        connected.push(addr);
+                    }
+                }
+                Ok(entry @ BootstrapEntry::Host { .. }) => hosts.push(entry),
+                Err(e) => {
+                    let error = e.to_string();
+                    log_discovery!("⚠️ Skipping {}", error);
+                }
+            }
+        }
+        
+        for addr in self.resolve_hosts(hosts).await {
+            if !connected.contains(&addr) {
                 connected.push(addr);
-                println!("🔗 Bootstrap node: {}", addr);
             }
         }
         
+        for addr in &connected {
+            let addr = *addr;
+            log_discovery!("🔗 Bootstrap node: {}", addr);
+        }
         connected
     }
     
+    // 캐시가 오래된 호스트만 동시에 다시 조회
+    async fn resolve_hosts(&self, hosts: Vec<BootstrapEntry>) -> Vec<SocketAddr> {
+        let stale: Vec<BootstrapEntry> = {
+            let resolutions = self.resolutions.read().await;
+            hosts
+                .iter()
+                .filter(|entry| {
+                    resolutions
+                        .get(&entry.to_string())
+                        .is_none_or(|r| r.is_due(self.resolve_interval))
+                })
+                .cloned()
+                .collect()
+        };
+        
+        let mut lookups = tokio::task::JoinSet::new();
+        for entry in stale {
+            lookups.spawn(async move {
+                let result = resolve(&entry).await;
+                (entry, result)
+            });
+        }
+        while let Some(joined) = lookups.join_next().await {
+            let Ok((entry, result)) = joined else { continue };
+            let name = entry.to_string();
+            let mut resolutions = self.resolutions.write().await;
+            let (previous, failures) = resolutions.remove(&name).map(|r| (r.addrs, r.failures)).unwrap_or_default();
+            let resolution = match result {
+                Ok(addrs) => {
+                    let count = addrs.len();
+                    let host = name.clone();
+                    log_discovery!("🌐 Resolved {} to {} addresses", host, count);
+                    Resolution {
+                        entry: name.clone(),
+                        addrs,
+                        error: None,
+                        failures: 0,
+                        resolved_at: Instant::now(),
+                    }
+                }
+                Err(error) => {
+                    let host = name.clone();
+                    let message = error.clone();
+                    let failures = failures.saturating_add(1);
+                    let retry_secs = retry_delay(failures, self.resolve_interval).as_secs();
+                    log_discovery!("⚠️ Failed to resolve bootstrap host {}: {} (retry in {}s)", host, message, retry_secs);
+                    // 일시적인 DNS 장애일 수 있으므로 마지막 결과는 유지
+                    Resolution {
+                        entry: name.clone(),
+                        addrs: previous,
+                        error: Some(error),
+                        failures,
+                        resolved_at: Instant::now(),
+                    }
+                }
+            };
+            resolutions.insert(name, resolution);
+        }
+        
+        let resolutions = self.resolutions.read().await;
+        hosts
+            .iter()
+            .filter_map(|entry| resolutions.get(&entry.to_string()))
+            .flat_map(|r| r.addrs.iter().copied())
+            .collect()
+    }
+    
+    /// 호스트 이름 항목별 최근 조회 결과 (실패 항목 포함)
+    pub async fn resolution_report(&self) -> Vec<Resolution> {
+        let mut report: Vec<Resolution> = self.resolutions.read().await.values().cloned().collect();
+        report.sort_by(|a, b| a.entry.cmp(&b.entry));
+        report
+    }
+    
     pub async fn exchange_peers(&self, _remote_addr: SocketAddr) -> Vec<PeerInfo> {
         // 부트스트랩 노드와 피어 목록 교환
         // 실제로는 네트워크 통신으로 구현
@@ -55,31 +305,185 @@ impl Bootstrap {
         peers.clone()
     }
     
-    pub async fn add_peer(&self, peer: PeerInfo) {
+    /// 알려진 피어 추가 (같은 주소 그룹이 가득 차 있으면 기존 피어를 유지하고 false)
+    ///
+    /// 목록은 최근에 확인한 순서로 유지하고, 가득 차면 가장 오래 소식이 없는 피어를 뺀다.
+    pub async fn add_peer(&self, peer: PeerInfo) -> bool {
         let mut peers = self.known_peers.write().await;
         
-        // 중복 체크
-        if !peers.iter().any(|p| p.addr == peer.addr) {
-            peers.push(peer);
-            
-            // 최대 1000개 피어 유지
-            if peers.len() > 1000 {
-                peers.remove(0);
-            }
+        // 이미 있으면 마지막 확인 시각만 갱신
+        if let Some(existing) = peers.iter_mut().find(|p| p.addr == peer.addr) {
+            existing.last_seen = existing.last_seen.max(peer.last_seen);
+            existing.node_id = peer.node_id;
+            keep_freshest(&mut peers, self.max_known_peers);
+            return true;
+        }
+        
+        let limit = self.diversity.max_per_group_in_address_book;
+        if self.diversity.group_full(&peer.addr, peers.iter().map(|p| &p.addr), limit) {
+            let addr = peer.addr;
+            log_discovery!("🛡️ Address group of {} is full, keeping existing peers", addr);
+            return false;
         }
+        
+        peers.push(peer);
+        keep_freshest(&mut peers, self.max_known_peers);
+        true
+    }
+    
+    /// 피어와 정상적으로 주고받았을 때 마지막 확인 시각 갱신 (알려진 피어가 아니면 false)
+    pub async fn touch_peer(&self, addr: SocketAddr) -> bool {
+        let mut peers = self.known_peers.write().await;
+        let Some(peer) = peers.iter_mut().find(|p| p.addr == addr) else {
+            return false;
+        };
+        peer.last_seen = now_secs();
+        keep_freshest(&mut peers, self.max_known_peers);
+        true
     }
     
     pub async fn get_peers(&self) -> Vec<PeerInfo> {
         self.known_peers.read().await.clone()
     }
     
-    pub async fn cleanup_stale_peers(&self, max_age_secs: u64) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+    /// max_age_secs 동안 확인되지 않은 피어 제거 (제거한 수 반환)
+    pub async fn cleanup_stale_peers(&self, max_age_secs: u64) -> usize {
+        remove_stale(&mut *self.known_peers.write().await, max_age_secs)
+    }
+}
+
+#[async_trait]
+impl DiscoveryMethod for Bootstrap {
+    fn name(&self) -> &str {
+        "bootstrap"
+    }
+    
+    fn source(&self) -> PeerSource {
+        PeerSource::Bootstrap
+    }
+    
+    /// 부트스트랩 노드와, 그 노드들과 교환한 피어 목록
+    async fn discover_peers(&self) -> Vec<SocketAddr> {
+        let mut peers = self.connect_bootstrap().await;
+        for addr in peers.clone() {
+            for peer in self.exchange_peers(addr).await {
+                if !peers.contains(&peer.addr) {
+                    peers.push(peer.addr);
+                }
+            }
+        }
+        peers
+    }
+    
+    async fn announce(&self, _addr: SocketAddr) {}
+}
+
+/// 호스트 이름의 A/AAAA 레코드 조회 (결과가 없어도 실패로 봄)
+pub(crate) async fn resolve(entry: &BootstrapEntry) -> Result<Vec<SocketAddr>, String> {
+    let target = match entry {
+        BootstrapEntry::Addr(addr) => return Ok(vec![*addr]),
+        BootstrapEntry::Host { host, port } => format!("{}:{}", host, port),
+    };
+    
+    let addrs = tokio::time::timeout(RESOLVE_TIMEOUT, tokio::net::lookup_host(target))
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+    
+    let mut resolved: Vec<SocketAddr> = Vec::new();
+    for addr in addrs {
+        if !resolved.contains(&addr) {
+            resolved.push(addr);
+        }
+    }
+    if resolved.is_empty() {
+        return Err("no addresses".to_string());
+    }
+    resolved.truncate(MAX_ADDRS_PER_SEED);
+    Ok(resolved)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn peer(addr: &str) -> PeerInfo {
+        PeerInfo { addr: addr.parse().unwrap(), node_id: [0; 32], last_seen: now_secs() }
+    }
+    
+    #[tokio::test]
+    async fn known_peers_are_limited_per_address_group() {
+        let bootstrap = Bootstrap::new(vec![]);
+        for i in 1..=8 {
+            assert!(bootstrap.add_peer(peer(&format!("198.51.100.{}:42000", i))).await);
+        }
+        // 가득 찬 /24에는 새 피어를 넣지 않지만, 이미 있는 피어의 갱신과 다른 그룹은 허용
+        assert!(!bootstrap.add_peer(peer("198.51.100.9:42000")).await);
+        assert!(bootstrap.add_peer(peer("198.51.100.1:42000")).await);
+        assert!(bootstrap.add_peer(peer("198.51.101.1:42000")).await);
+        
+        for i in 1..=8 {
+            assert!(bootstrap.add_peer(peer(&format!("[2001:db8:1:{:x}::1]:42000", i))).await);
+        }
+        assert!(!bootstrap.add_peer(peer("[2001:db8:1:ffff::1]:42000")).await);
+        assert!(bootstrap.add_peer(peer("[2001:db8:2::1]:42000")).await);
+        
+        assert_eq!(bootstrap.get_peers().await.len(), 18);
+    }
+    
+    #[tokio::test]
+    async fn local_peers_are_not_limited() {
+        let bootstrap = Bootstrap::new(vec![]);
+        for i in 1..=20 {
+            assert!(bootstrap.add_peer(peer(&format!("192.168.1.{}:42000", i))).await);
+        }
+    }
+    
+    #[test]
+    fn failed_resolutions_back_off_exponentially() {
+        let interval = DEFAULT_RESOLVE_INTERVAL;
+        assert_eq!(retry_delay(0, interval), interval);
+        assert_eq!(retry_delay(1, interval), RESOLVE_RETRY_BASE);
+        assert_eq!(retry_delay(2, interval), RESOLVE_RETRY_BASE * 2);
+        assert_eq!(retry_delay(3, interval), RESOLVE_RETRY_BASE * 4);
+        // resolve_interval보다 길어지지 않음
+        assert_eq!(retry_delay(10, interval), interval);
+        assert_eq!(retry_delay(u32::MAX, interval), interval);
+    }
+    
+    #[tokio::test]
+    async fn failed_host_is_reported_and_keeps_previous_addresses() {
+        let cached: SocketAddr = "198.51.100.7:9000".parse().unwrap();
+        let direct: SocketAddr = "203.0.113.1:9000".parse().unwrap();
+        let bootstrap = Bootstrap::with_resolve_interval(
+            vec!["seed.invalid:9000".to_string(), direct.to_string(), "bad entry".to_string()],
+            Duration::ZERO,
+        );
+        // 이전 조회에서 얻은 주소 (resolve_interval이 0이라 바로 다시 조회함)
+        bootstrap.resolutions.write().await.insert(
+            "seed.invalid:9000".to_string(),
+            Resolution {
+                entry: "seed.invalid:9000".to_string(),
+                addrs: vec![cached],
+                error: None,
+                failures: 0,
+                resolved_at: Instant::now(),
+            },
+        );
         
-        let mut peers = self.known_peers.write().await;
-        peers.retain(|p| now - p.last_seen < max_age_secs);
+        assert_eq!(bootstrap.connect_bootstrap().await, vec![direct, cached]);
+        let report = bootstrap.resolution_report().await;
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].entry, "seed.invalid:9000");
+        assert_eq!(report[0].addrs, vec![cached]);
+        assert!(report[0].error.is_some());
+        assert_eq!(report[0].failures, 1);
     }
-}
\ No newline at end of file
+}
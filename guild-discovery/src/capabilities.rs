@@ -0,0 +1,105 @@
+// 역할/기능 태그 - 노드가 맡은 역할(validator, relay, archive 등)을 광고하고 검색
+//
+// 태그마다 DHT 키 `record_key("guild/cap/<tag>")`에 레코드를 발행한다. 레코드 값에는
+// 발행 노드의 전체 태그 목록이 들어가고, 주소는 담지 않는다 (노드는 자신의 외부 주소를
+// 모를 수 있음). 검색하는 쪽은 레코드 발행자 id로 FIND_NODE를 해서 실제 주소를 얻는다.
+// LAN에서는 mDNS TXT 레코드의 `caps` 값으로도 광고한다.
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::address_book::PeerSource;
+use crate::dht::NodeId;
+use crate::dht_store::record_key;
+
+/// 블록 생성/투표에 참여하는 노드
+pub const CAPABILITY_VALIDATOR: &str = "validator";
+/// 다른 노드의 트래픽을 중계하는 노드
+pub const CAPABILITY_RELAY: &str = "relay";
+/// 전체 기록을 보관하는 노드
+pub const CAPABILITY_ARCHIVE: &str = "archive";
+/// 일반 참여 노드
+pub const CAPABILITY_MEMBER: &str = "member";
+
+/// 노드 하나가 광고할 수 있는 최대 태그 수 (mDNS TXT 값 255바이트 안에 들어가도록)
+pub const MAX_CAPABILITIES: usize = 8;
+/// 태그 하나의 최대 길이
+pub const MAX_CAPABILITY_LEN: usize = 24;
+/// DHT 광고 레코드의 유효 기간
+pub const CAPABILITY_TTL: Duration = Duration::from_secs(60 * 60);
+/// 만료 전에 다시 발행하는 주기
+pub const CAPABILITY_REPUBLISH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// 태그 형식 검사 (소문자, 숫자, '-'만 허용)
+pub fn validate_capability(tag: &str) -> Result<(), String> {
+    if tag.is_empty() || tag.len() > MAX_CAPABILITY_LEN {
+        return Err(format!(
+            "capability must be 1-{} characters: {:?}",
+            MAX_CAPABILITY_LEN, tag
+        ));
+    }
+    if !tag
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    {
+        return Err(format!(
+            "capability may only contain a-z, 0-9 and '-': {:?}",
+            tag
+        ));
+    }
+    Ok(())
+}
+
+/// 태그 목록 검사 (개수 제한과 각 태그 형식)
+pub fn validate_capabilities(tags: &[String]) -> Result<(), String> {
+    if tags.len() > MAX_CAPABILITIES {
+        return Err(format!(
+            "at most {} capabilities can be advertised",
+            MAX_CAPABILITIES
+        ));
+    }
+    tags.iter().try_for_each(|tag| validate_capability(tag))
+}
+
+/// 태그를 광고하는 DHT 레코드 키
+pub fn capability_key(tag: &str) -> NodeId {
+    record_key(format!("guild/cap/{}", tag).as_bytes())
+}
+
+/// DHT 광고 레코드 값
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityAdvert {
+    pub capabilities: Vec<String>,
+}
+
+impl CapabilityAdvert {
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("capability advert serialization")
+    }
+
+    /// 디코딩 (형식이 잘못되었거나 태그 규칙에 맞지 않으면 None)
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let advert: Self = bincode::deserialize(data).ok()?;
+        validate_capabilities(&advert.capabilities).ok()?;
+        Some(advert)
+    }
+}
+
+/// 태그를 알고 있는 피어
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapablePeer {
+    pub addr: SocketAddr,
+    /// 노드 id (직접 등록한 피어는 모를 수 있음)
+    pub node_id: Option<NodeId>,
+    pub capabilities: Vec<String>,
+    /// 태그를 알게 된 경로
+    pub source: PeerSource,
+    /// 마지막으로 태그를 확인한 시각 (unix 초)
+    pub last_seen: u64,
+}
+
+impl CapablePeer {
+    pub fn has(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
@@ -1,12 +1,63 @@
 // Kademlia DHT 구현
+use async_trait::async_trait;
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
+// 일시정지된 tokio 시계에서도 버킷 경과 시간이 흐르도록 tokio의 Instant 사용
+use tokio::time::Instant;
 use serde::{Deserialize, Serialize};
 
+use crate::address_book::PeerSource;
+use crate::discovery::{DiscoveryMethod, DEFAULT_NETWORK_ID};
+use crate::dht_rpc::{DhtError, DhtRequest, DhtResponse, DhtTransport, MAX_NODES_PER_RESPONSE, MAX_RECORDS_PER_RESPONSE};
+use crate::dht_store::{self, DhtRecord, RecordError, RecordStore};
+use crate::diversity::DiversityConfig;
+use crate::identity::NodeIdentity;
+use crate::log_discovery;
+
 pub const K_BUCKET_SIZE: usize = 20;  // 각 버킷의 최대 노드 수
 pub const ALPHA: usize = 3;           // 동시 조회 수
 pub const NODE_ID_LENGTH: usize = 32; // 256 bits
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(2); // FIND_NODE 한 번의 응답 대기 시간
+pub const BUCKET_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60); // 이 시간 동안 조용한 버킷은 무작위 조회로 갱신
+pub const REPUBLISH_INTERVAL: Duration = Duration::from_secs(60 * 60); // 보관 중인 레코드를 가장 가까운 노드들에 다시 복제하는 주기
+const MAX_LOOKUP_ROUNDS: usize = 32;  // 수렴하지 않는 조회를 끊기 위한 상한
+const MAX_REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct DhtConfig {
+    /// 버킷 크기이자 조회 결과 수
+    pub k: usize,
+    /// 조회 한 라운드에서 동시에 보내는 요청 수
+    pub alpha: usize,
+    /// 요청 하나의 응답 대기 시간 (넘기면 실패로 보고 다른 노드로 진행)
+    pub query_timeout: Duration,
+    /// 이 시간 동안 갱신되지 않은 버킷은 범위 안의 무작위 id를 조회해서 갱신
+    pub refresh_interval: Duration,
+    /// 보관 중인 레코드를 현재 가장 가까운 k개 노드에 다시 보내는 주기 (노드 이탈 대비)
+    pub republish_interval: Duration,
+    /// 발행하는 레코드에 서명해서 넣고, 다른 network의 레코드는 거부
+    pub network_id: String,
+    /// 버킷 하나에 들어갈 수 있는 같은 주소 그룹(/24, /48) 노드 수와
+    /// 키 하나에 같은 그룹이 STORE로 넣을 수 있는 발행자 수 제한
+    pub diversity: DiversityConfig,
+}
+
+impl Default for DhtConfig {
+    fn default() -> Self {
+        Self {
+            k: K_BUCKET_SIZE,
+            alpha: ALPHA,
+            query_timeout: QUERY_TIMEOUT,
+            refresh_interval: BUCKET_REFRESH_INTERVAL,
+            republish_interval: REPUBLISH_INTERVAL,
+            network_id: DEFAULT_NETWORK_ID.to_string(),
+            diversity: DiversityConfig::default(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NodeId(pub [u8; NODE_ID_LENGTH]);
@@ -27,6 +78,22 @@ impl NodeId {
         NodeId(*hash.as_bytes())
     }
     
+    /// 16진수 문자열 (64자)
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+    
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != NODE_ID_LENGTH * 2 || !hex.is_ascii() {
+            return None;
+        }
+        let mut id = [0u8; NODE_ID_LENGTH];
+        for (i, byte) in id.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(NodeId(id))
+    }
+    
     pub fn distance(&self, other: &NodeId) -> Distance {
         let mut dist = [0u8; NODE_ID_LENGTH];
         for (i, byte) in dist.iter_mut().enumerate().take(NODE_ID_LENGTH) {
@@ -39,9 +106,22 @@ impl NodeId {
         let dist = self.distance(other);
         dist.leading_zeros()
     }
+    
+    /// 이 id 기준으로 index번 버킷에 들어가는 무작위 id
+    /// (앞 index비트는 같고, index번째 비트는 다르고, 나머지는 무작위)
+    pub fn random_in_bucket(&self, index: usize) -> NodeId {
+        let mut id = NodeId::random().0;
+        for bit in 0..=index.min(NODE_ID_LENGTH * 8 - 1) {
+            let (byte, mask) = (bit / 8, 0x80u8 >> (bit % 8));
+            let own = self.0[byte] & mask;
+            let value = if bit == index { own ^ mask } else { own };
+            id[byte] = (id[byte] & !mask) | value;
+        }
+        NodeId(id)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Distance([u8; NODE_ID_LENGTH]);
 
 impl Distance {
@@ -63,8 +143,18 @@ pub struct Node {
 }
 
 pub struct KBucket {
+    // 앞쪽일수록 오래전에 본 노드 (마지막이 가장 최근)
     nodes: Vec<Node>,
     max_size: usize,
+    // 버킷이 가득 찬 동안 들어온 후보 (마지막이 가장 최근)
+    replacements: Vec<Node>,
+    max_replacements: usize,
+    // 마지막으로 노드가 추가/갱신되었거나 이 범위를 조회한 시각
+    last_updated: Instant,
+    // 가장 오래된 노드의 생존 확인 PING이 진행 중인지
+    pinging: bool,
+    // 같은 주소 그룹 노드 수 제한
+    diversity: DiversityConfig,
 }
 
 impl Default for KBucket {
@@ -75,68 +165,345 @@ impl Default for KBucket {
 
 impl KBucket {
     pub fn new() -> Self {
+        Self::with_capacity(K_BUCKET_SIZE)
+    }
+    
+    pub fn with_capacity(max_size: usize) -> Self {
+        Self::with_diversity(max_size, DiversityConfig::default())
+    }
+    
+    pub fn with_diversity(max_size: usize, diversity: DiversityConfig) -> Self {
         Self {
             nodes: Vec::new(),
-            max_size: K_BUCKET_SIZE,
+            max_size,
+            replacements: Vec::new(),
+            max_replacements: max_size,
+            last_updated: Instant::now(),
+            pinging: false,
+            diversity,
         }
     }
     
+    /// 노드 추가 또는 갱신 (가득 차서 넣지 못하면 false, 노드는 교체 후보에 보관)
+    ///
+    /// 같은 주소 그룹의 노드가 이미 제한만큼 있으면 새 노드는 교체 후보에도 넣지 않고 false.
     pub fn add_node(&mut self, node: Node) -> bool {
         // 이미 있으면 업데이트
         if let Some(pos) = self.nodes.iter().position(|n| n.id == node.id) {
             self.nodes.remove(pos);
             self.nodes.push(node);
+            self.last_updated = Instant::now();
             return true;
         }
         
+        if !self.admits(&node) {
+            return false;
+        }
+        
         // 공간이 있으면 추가
         if self.nodes.len() < self.max_size {
+            self.replacements.retain(|n| n.id != node.id);
             self.nodes.push(node);
+            self.last_updated = Instant::now();
             return true;
         }
         
-        // 버킷이 가득 차면 가장 오래된 노드 교체 고려
-        // 실제로는 핑을 보내서 응답 없으면 교체
+        // 버킷이 가득 차면 교체 후보로 보관 (가장 오래된 노드가 응답하지 않으면 승격)
+        self.replacements.retain(|n| n.id != node.id);
+        let limit = self.diversity.max_per_group_per_bucket;
+        if self.diversity.group_full(&node.addr, self.replacements.iter().map(|n| &n.addr), limit) {
+            return false;
+        }
+        self.replacements.push(node);
+        if self.replacements.len() > self.max_replacements {
+            self.replacements.remove(0);
+        }
         false
     }
     
+    /// 주소 그룹 제한 안에서 받을 수 있는 노드인지 (이미 있는 노드는 항상 true)
+    pub fn admits(&self, node: &Node) -> bool {
+        if self.nodes.iter().any(|n| n.id == node.id) {
+            return true;
+        }
+        let limit = self.diversity.max_per_group_per_bucket;
+        !self.diversity.group_full(&node.addr, self.nodes.iter().map(|n| &n.addr), limit)
+    }
+    
     pub fn get_nodes(&self) -> Vec<Node> {
         self.nodes.clone()
     }
     
+    pub fn get_replacements(&self) -> Vec<Node> {
+        self.replacements.clone()
+    }
+    
+    /// 가장 오래전에 본 노드 (교체 전에 생존 확인할 대상)
+    pub fn least_recent(&self) -> Option<&Node> {
+        self.nodes.first()
+    }
+    
     pub fn remove_node(&mut self, id: &NodeId) {
         self.nodes.retain(|n| n.id != *id);
     }
+    
+    /// 노드를 빼고 가장 최근 교체 후보를 승격 (후보가 없으면 노드를 유지하고 false)
+    ///
+    /// 승격하면 주소 그룹 제한을 넘는 후보는 건너뛴다.
+    pub fn replace_node(&mut self, id: &NodeId) -> bool {
+        if !self.nodes.iter().any(|n| n.id == *id) {
+            return false;
+        }
+        let limit = self.diversity.max_per_group_per_bucket;
+        let remaining: Vec<SocketAddr> = self.nodes.iter().filter(|n| n.id != *id).map(|n| n.addr).collect();
+        let Some(pos) = self
+            .replacements
+            .iter()
+            .rposition(|r| !self.diversity.group_full(&r.addr, &remaining, limit))
+        else {
+            return false;
+        };
+        self.remove_node(id);
+        let replacement = self.replacements.remove(pos);
+        self.nodes.push(replacement);
+        self.last_updated = Instant::now();
+        true
+    }
+    
+    pub fn is_full(&self) -> bool {
+        self.nodes.len() >= self.max_size
+    }
+    
+    /// 마지막 갱신 이후 지난 시간
+    pub fn idle_time(&self) -> Duration {
+        self.last_updated.elapsed()
+    }
+    
+    fn touch(&mut self) {
+        self.last_updated = Instant::now();
+    }
 }
 
 pub struct Kademlia {
     node_id: NodeId,
+    config: DhtConfig,
     k_buckets: Arc<RwLock<Vec<KBucket>>>,
+    // 없으면 로컬 라우팅 테이블만으로 조회
+    transport: std::sync::RwLock<Option<Arc<dyn DhtTransport>>>,
+    // 레코드 발행용 키 (없으면 다른 노드의 레코드 보관만 가능)
+    identity: Option<NodeIdentity>,
+    records: RwLock<RecordStore>,
 }
 
 impl Kademlia {
     pub fn new(node_id: NodeId) -> Self {
+        Self::with_config(node_id, DhtConfig::default())
+    }
+    
+    pub fn with_config(node_id: NodeId, config: DhtConfig) -> Self {
         let mut buckets = Vec::with_capacity(NODE_ID_LENGTH * 8);
         for _ in 0..NODE_ID_LENGTH * 8 {
-            buckets.push(KBucket::new());
+            buckets.push(KBucket::with_diversity(config.k, config.diversity.clone()));
         }
         
+        let records = RecordStore::new(&config.network_id)
+            .with_group_limit(config.diversity.max_stores_per_group_per_key);
         Self {
             node_id,
+            config,
             k_buckets: Arc::new(RwLock::new(buckets)),
+            transport: std::sync::RwLock::new(None),
+            identity: None,
+            records: RwLock::new(records),
+        }
+    }
+    
+    /// 노드 키와 함께 생성 (노드 id는 공개키에서 유도, 레코드 발행 가능)
+    pub fn with_identity(identity: NodeIdentity, config: DhtConfig) -> Self {
+        Self {
+            identity: Some(identity.clone()),
+            ..Self::with_config(identity.node_id(), config)
         }
     }
     
+    /// 네트워크 전송 계층 연결 (이후 lookup은 다른 노드에게 FIND_NODE를 보냄)
+    pub fn set_transport(&self, transport: Arc<dyn DhtTransport>) {
+        *self.transport.write().unwrap() = Some(transport);
+    }
+    
+    pub fn has_transport(&self) -> bool {
+        self.transport.read().unwrap().is_some()
+    }
+    
+    fn transport(&self) -> Option<Arc<dyn DhtTransport>> {
+        self.transport.read().unwrap().clone()
+    }
+    
+    pub fn config(&self) -> &DhtConfig {
+        &self.config
+    }
+    
+    /// 버킷 번호 (자기 자신이면 None)
+    pub fn bucket_index(&self, id: &NodeId) -> Option<usize> {
+        let index = self.node_id.distance(id).leading_zeros();
+        (index < NODE_ID_LENGTH * 8).then_some(index)
+    }
+    
+    /// 노드 추가 또는 갱신
+    ///
+    /// 버킷이 가득 차 있으면 새 노드는 교체 후보로 보관하고, 가장 오래전에 본 노드에게
+    /// PING을 보낸다. 응답하면 그 노드를 유지하고(최근으로 이동), 응답하지 않으면 빼고
+    /// 가장 최근 교체 후보를 승격한다. 오래 살아 있는 노드를 우선하는 Kademlia 기본 정책.
+    /// 같은 주소 그룹(/24, /48)의 노드가 버킷에 이미 제한만큼 있으면 새 노드는 버린다.
     pub async fn add_node(&self, node: Node) {
-        let distance = self.node_id.distance(&node.id);
-        let bucket_idx = distance.leading_zeros();
+        let Some(bucket_idx) = self.bucket_index(&node.id) else {
+            return;
+        };
         
-        if bucket_idx < NODE_ID_LENGTH * 8 {
+        let least_recent = {
             let mut buckets = self.k_buckets.write().await;
-            buckets[bucket_idx].add_node(node);
+            let bucket = &mut buckets[bucket_idx];
+            // 같은 주소 그룹이 이미 가득 차 있으면 오래된 노드를 확인할 필요도 없음
+            if !bucket.admits(&node) {
+                return;
+            }
+            if bucket.add_node(node) || bucket.pinging || !self.has_transport() {
+                return;
+            }
+            bucket.pinging = true;
+            bucket.least_recent().cloned()
+        };
+        
+        let (Some(least_recent), Some(transport)) = (least_recent, self.transport()) else {
+            return;
+        };
+        let buckets = self.k_buckets.clone();
+        let timeout = self.config.query_timeout;
+        tokio::spawn(async move {
+            let reply = tokio::time::timeout(timeout, transport.request(least_recent.addr, DhtRequest::Ping)).await;
+            let alive = matches!(reply, Ok(Ok((id, DhtResponse::Pong))) if id == least_recent.id);
+            
+            let mut buckets = buckets.write().await;
+            let bucket = &mut buckets[bucket_idx];
+            bucket.pinging = false;
+            if alive {
+                bucket.add_node(Node { last_seen: now_secs(), ..least_recent });
+            } else if bucket.replace_node(&least_recent.id) {
+                let addr = least_recent.addr;
+                log_discovery!("🔁 Evicted unresponsive DHT node {}", addr);
+            }
+        });
+    }
+    
+    /// 요청에 응답하지 않은 노드 처리 (교체 후보가 있을 때만 교체)
+    ///
+    /// 후보가 없으면 일시적인 장애일 수 있으므로 노드를 유지한다.
+    pub async fn mark_failed(&self, id: &NodeId) {
+        if let Some(bucket_idx) = self.bucket_index(id) {
+            self.k_buckets.write().await[bucket_idx].replace_node(id);
         }
     }
     
+    pub async fn bucket_nodes(&self, index: usize) -> Vec<Node> {
+        let buckets = self.k_buckets.read().await;
+        buckets.get(index).map(KBucket::get_nodes).unwrap_or_default()
+    }
+    
+    pub async fn bucket_replacements(&self, index: usize) -> Vec<Node> {
+        let buckets = self.k_buckets.read().await;
+        buckets.get(index).map(KBucket::get_replacements).unwrap_or_default()
+    }
+    
+    /// refresh_interval 동안 갱신되지 않은 버킷마다 범위 안의 무작위 id를 조회
+    ///
+    /// 가장 먼 버킷부터 노드가 있는 가장 가까운 버킷까지만 대상으로 한다 (그보다 가까운
+    /// 버킷은 대부분 비어 있을 수밖에 없음). 갱신한 버킷 수를 반환.
+    pub async fn refresh_stale_buckets(&self) -> usize {
+        let stale: Vec<usize> = {
+            let buckets = self.k_buckets.read().await;
+            let Some(deepest) = buckets.iter().rposition(|b| !b.nodes.is_empty()) else {
+                return 0;
+            };
+            (0..=deepest)
+                .filter(|&i| buckets[i].idle_time() >= self.config.refresh_interval)
+                .collect()
+        };
+        
+        for &index in &stale {
+            self.lookup(self.node_id.random_in_bucket(index)).await;
+            // 찾은 노드가 없어도 다음 주기까지는 다시 조회하지 않음
+            self.k_buckets.write().await[index].touch();
+        }
+        stale.len()
+    }
+    
+    /// 버킷 갱신, 레코드 만료/재발행을 주기적으로 처리하는 백그라운드 태스크 시작
+    pub fn spawn_maintenance(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let dht = Arc::downgrade(self);
+        let check_interval = (self.config.refresh_interval / 4)
+            .min(self.config.republish_interval / 4)
+            .min(MAX_REFRESH_CHECK_INTERVAL);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            interval.tick().await;
+            let mut last_republish = Instant::now();
+            loop {
+                interval.tick().await;
+                let Some(dht) = dht.upgrade() else {
+                    break;
+                };
+                
+                let expired = dht.expire_records().await;
+                if expired > 0 {
+                    log_discovery!("🗑️ Expired {} DHT records", expired);
+                }
+                if !dht.has_transport() {
+                    continue;
+                }
+                
+                let refreshed = dht.refresh_stale_buckets().await;
+                if refreshed > 0 {
+                    log_discovery!("🔄 Refreshed {} stale DHT buckets", refreshed);
+                }
+                if last_republish.elapsed() >= dht.config.republish_interval {
+                    last_republish = Instant::now();
+                    let republished = dht.republish_records().await;
+                    if republished > 0 {
+                        log_discovery!("📤 Republished {} DHT records", republished);
+                    }
+                }
+            }
+        })
+    }
+    
+    pub async fn remove_node(&self, id: &NodeId) {
+        let bucket_idx = self.node_id.distance(id).leading_zeros();
+        if bucket_idx < NODE_ID_LENGTH * 8 {
+            self.k_buckets.write().await[bucket_idx].remove_node(id);
+        }
+    }
+    
+    /// 라우팅 테이블의 전체 노드 수
+    pub async fn node_count(&self) -> usize {
+        self.k_buckets.read().await.iter().map(|b| b.nodes.len()).sum()
+    }
+    
+    /// 라우팅 테이블에 있는 노드 (id가 정확히 같은 노드만)
+    pub async fn node(&self, id: &NodeId) -> Option<Node> {
+        let index = self.bucket_index(id)?;
+        let buckets = self.k_buckets.read().await;
+        buckets[index].nodes.iter().find(|n| n.id == *id).cloned()
+    }
+    
+    /// id의 주소 찾기 (라우팅 테이블에 없으면 네트워크에서 FIND_NODE로 조회)
+    pub async fn resolve(&self, id: NodeId) -> Option<Node> {
+        if let Some(node) = self.node(&id).await {
+            return Some(node);
+        }
+        self.lookup(id).await.into_iter().find(|n| n.id == id)
+    }
+    
     pub async fn find_closest_nodes(&self, target: &NodeId, count: usize) -> Vec<Node> {
         let mut all_nodes = Vec::new();
         let buckets = self.k_buckets.read().await;
@@ -146,25 +513,531 @@ impl Kademlia {
         }
         
         // 타겟과의 거리로 정렬
-        all_nodes.sort_by_key(|node| {
-            let dist = node.id.distance(target);
-            dist.0
-        });
+        all_nodes.sort_by_key(|node| node.id.distance(target));
         
         all_nodes.truncate(count);
         all_nodes
     }
     
+    /// 다른 노드가 보낸 요청 처리 (보낸 노드는 라우팅 테이블에 추가)
+    ///
+    /// sender는 요청에 적힌 값이 아니라 전송 계층이 확인한 id여야 한다 (id를 공짜로
+    /// 만들 수 있으면 아무 키 근처에나 노드를 심을 수 있음).
+    pub async fn handle_request(&self, from: SocketAddr, sender: NodeId, request: DhtRequest) -> DhtResponse {
+        if sender != self.node_id {
+            self.add_node(Node { id: sender, addr: from, last_seen: now_secs() }).await;
+        }
+        
+        match request {
+            DhtRequest::Ping => DhtResponse::Pong,
+            DhtRequest::FindNode { target } => {
+                DhtResponse::Nodes(self.closest_for(&target, &sender).await)
+            }
+            DhtRequest::Store { record } => {
+                // 이미 같은 레코드가 있으면(재발행) 보관된 것으로 응답
+                let source = self.config.diversity.group(&from);
+                let accepted = match self.records.write().await.insert_from(record, source) {
+                    Ok(()) | Err(RecordError::Stale) => true,
+                    Err(_) => false,
+                };
+                DhtResponse::Stored { accepted }
+            }
+            DhtRequest::FindValue { key } => {
+                let mut records = self.records.read().await.get(&key);
+                records.truncate(MAX_RECORDS_PER_RESPONSE);
+                DhtResponse::Value {
+                    records,
+                    nodes: self.closest_for(&key, &sender).await,
+                }
+            }
+        }
+    }
+    
+    // 요청한 노드 자신은 돌려줄 필요 없음
+    async fn closest_for(&self, target: &NodeId, requester: &NodeId) -> Vec<Node> {
+        let mut nodes = self.find_closest_nodes(target, self.config.k + 1).await;
+        nodes.retain(|n| n.id != *requester);
+        nodes.truncate(self.config.k);
+        nodes
+    }
+    
+    /// 주소만 아는 노드에게 PING을 보내 id를 확인하고 라우팅 테이블에 추가
+    pub async fn ping(&self, addr: SocketAddr) -> Result<NodeId, DhtError> {
+        let transport = self.transport().ok_or_else(|| DhtError::Unreachable("no DHT transport".to_string()))?;
+        let reply = tokio::time::timeout(self.config.query_timeout, transport.request(addr, DhtRequest::Ping))
+            .await
+            .map_err(|_| DhtError::Timeout)??;
+        
+        match reply {
+            (id, DhtResponse::Pong) => {
+                if id != self.node_id {
+                    self.add_node(Node { id, addr, last_seen: now_secs() }).await;
+                }
+                Ok(id)
+            }
+            _ => Err(DhtError::UnexpectedResponse),
+        }
+    }
+    
+    /// 반복 FIND_NODE 조회 - target에 가장 가까운 k개 노드를 찾음
+    ///
+    /// 후보 목록에서 아직 묻지 않은 가장 가까운 α개 노드에 동시에 요청하고, 응답으로 받은
+    /// 노드를 후보에 합친다. 한 라운드에서 더 가까운 노드를 찾지 못하면 남은 상위 k개 후보
+    /// 모두에게 묻고, 상위 k개가 전부 응답했거나 실패하면 끝낸다. 응답한 노드는 라우팅
+    /// 테이블에 추가하고, 응답하지 않은 노드는 후보에서 뺀다.
+    ///
+    /// 전송 계층이 없으면 로컬 라우팅 테이블에서만 찾는다.
     pub async fn lookup(&self, target: NodeId) -> Vec<Node> {
-        // FIND_NODE 연산 구현
-        // 1. 자신의 k-bucket에서 가장 가까운 α개 노드 선택
-        // 2. 이 노드들에게 FIND_NODE 요청
-        // 3. 응답으로 받은 노드들 중 더 가까운 노드가 있으면 반복
+        self.iterative_lookup(target, false).await.0
+    }
+    
+    // find_value면 FIND_VALUE를 보내고 응답에 담긴 (검증된) 레코드도 모음
+    async fn iterative_lookup(&self, target: NodeId, find_value: bool) -> (Vec<Node>, Vec<DhtRecord>) {
+        let k = self.config.k;
+        let Some(transport) = self.transport() else {
+            return (self.find_closest_nodes(&target, k).await, Vec::new());
+        };
+        
+        // 이 범위를 조회했으므로 버킷 갱신 주기를 다시 시작
+        if let Some(bucket_idx) = self.bucket_index(&target) {
+            self.k_buckets.write().await[bucket_idx].touch();
+        }
+        
+        let mut shortlist = self.find_closest_nodes(&target, k).await;
+        let mut queried: HashSet<NodeId> = HashSet::new();
+        let mut responded: HashSet<NodeId> = HashSet::new();
+        let mut closest = shortlist.first().map(|n| n.id.distance(&target));
+        let mut parallelism = self.config.alpha;
+        let mut records = Vec::new();
+        
+        for _ in 0..MAX_LOOKUP_ROUNDS {
+            let batch: Vec<Node> = shortlist
+                .iter()
+                .take(k)
+                .filter(|n| !queried.contains(&n.id))
+                .take(parallelism)
+                .cloned()
+                .collect();
+            if batch.is_empty() {
+                break;
+            }
+            
+            let mut queries = tokio::task::JoinSet::new();
+            for node in batch {
+                queried.insert(node.id);
+                let transport = transport.clone();
+                let timeout = self.config.query_timeout;
+                let request = if find_value {
+                    DhtRequest::FindValue { key: target }
+                } else {
+                    DhtRequest::FindNode { target }
+                };
+                queries.spawn(async move {
+                    let result = tokio::time::timeout(timeout, transport.request(node.addr, request))
+                        .await
+                        .map_err(|_| DhtError::Timeout)
+                        .and_then(|r| r);
+                    (node, result)
+                });
+            }
+            
+            while let Some(joined) = queries.join_next().await {
+                let Ok((node, result)) = joined else { continue };
+                let answer = match result {
+                    Ok((id, DhtResponse::Nodes(nodes))) => Some((id, nodes)),
+                    Ok((id, DhtResponse::Value { records: found, nodes })) if find_value => {
+                        records.extend(
+                            found
+                                .into_iter()
+                                .take(MAX_RECORDS_PER_RESPONSE)
+                                .filter(|r| {
+                                    r.key == target
+                                        && r.network_id == self.config.network_id
+                                        && r.verify().is_ok()
+                                }),
+                        );
+                        Some((id, nodes))
+                    }
+                    _ => None,
+                };
+                match answer {
+                    Some((id, nodes)) => {
+                        // 주소가 가리키는 노드의 id가 바뀌었을 수 있으므로 응답한 id를 기준으로 기록
+                        responded.insert(id);
+                        queried.insert(id);
+                        if id != node.id {
+                            shortlist.retain(|n| n.id != node.id);
+                        }
+                        self.add_node(Node { id, addr: node.addr, last_seen: now_secs() }).await;
+                        
+                        for learned in nodes.into_iter().take(MAX_NODES_PER_RESPONSE) {
+                            if learned.id != self.node_id && !shortlist.iter().any(|n| n.id == learned.id) {
+                                shortlist.push(learned);
+                            }
+                        }
+                        if !shortlist.iter().any(|n| n.id == id) {
+                            shortlist.push(Node { id, addr: node.addr, last_seen: now_secs() });
+                        }
+                    }
+                    None => {
+                        shortlist.retain(|n| n.id != node.id);
+                        self.mark_failed(&node.id).await;
+                    }
+                }
+            }
+            
+            shortlist.sort_by_key(|n| n.id.distance(&target));
+            shortlist.truncate(k * 3);
+            
+            // 더 가까운 노드를 찾았으면 α개씩 계속, 아니면 남은 상위 k개 모두에게 질의
+            let best = shortlist.first().map(|n| n.id.distance(&target));
+            if best.is_some() && (closest.is_none() || best < closest) {
+                closest = best;
+                parallelism = self.config.alpha;
+            } else {
+                parallelism = k;
+            }
+        }
+        
+        let mut result: Vec<Node> = shortlist.into_iter().filter(|n| responded.contains(&n.id)).collect();
+        result.truncate(k);
         
-        self.find_closest_nodes(&target, ALPHA).await
+        let found = result.len();
+        let contacted = queried.len();
+        log_discovery!("🧭 DHT lookup finished: {} nodes found, {} contacted", found, contacted);
+        (result, records)
+    }
+    
+    /// 레코드 발행 - 서명해서 로컬에 보관하고 key에 가장 가까운 k개 노드에 복제
+    ///
+    /// 복제에 성공한 노드 수를 반환. 같은 key에 다시 발행하면 이전 레코드를 대체한다.
+    pub async fn put(&self, key: NodeId, value: Vec<u8>, ttl: Duration) -> Result<usize, DhtError> {
+        let identity = self.identity.as_ref().ok_or(DhtError::NoIdentity)?;
+        // 같은 초에 여러 번 발행해도 순서가 유지되도록 마이크로초 사용
+        let sequence = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64;
+        let record = DhtRecord::sign(identity, &self.config.network_id, key, value, sequence, ttl);
+        self.records.write().await.insert(record.clone()).map_err(DhtError::Record)?;
+        
+        Ok(self.replicate(&record).await)
+    }
+    
+    /// key의 레코드 조회 (로컬 보관분 + 네트워크 FIND_VALUE, 발행자별 최신 레코드)
+    pub async fn get(&self, key: NodeId) -> Vec<DhtRecord> {
+        let local = self.records.read().await.get(&key);
+        let (_, found) = self.iterative_lookup(key, true).await;
+        dht_store::merge_records(local.into_iter().chain(found))
+    }
+    
+    // key에 가장 가까운 노드들에게 STORE (보관했다고 응답한 노드 수 반환)
+    async fn replicate(&self, record: &DhtRecord) -> usize {
+        let Some(transport) = self.transport() else {
+            return 0;
+        };
+        
+        let mut stores = tokio::task::JoinSet::new();
+        for node in self.lookup(record.key).await {
+            let transport = transport.clone();
+            let request = DhtRequest::Store { record: record.clone() };
+            let timeout = self.config.query_timeout;
+            stores.spawn(async move { tokio::time::timeout(timeout, transport.request(node.addr, request)).await });
+        }
+        
+        let mut stored = 0;
+        while let Some(joined) = stores.join_next().await {
+            if let Ok(Ok(Ok((_, DhtResponse::Stored { accepted: true })))) = joined {
+                stored += 1;
+            }
+        }
+        stored
+    }
+    
+    /// 보관 중인 유효 레코드를 모두 현재 가장 가까운 노드들에 다시 복제
+    pub async fn republish_records(&self) -> usize {
+        let records = self.records.read().await.all();
+        for record in &records {
+            self.replicate(record).await;
+        }
+        records.len()
+    }
+    
+    /// 만료된 레코드 제거
+    pub async fn expire_records(&self) -> usize {
+        self.records.write().await.expire()
+    }
+    
+    /// 로컬에 보관 중인 레코드 수
+    pub async fn record_count(&self) -> usize {
+        self.records.read().await.len()
     }
     
     pub fn get_node_id(&self) -> NodeId {
         self.node_id
     }
-}
\ No newline at end of file
+}
+
+#[async_trait]
+impl DiscoveryMethod for Kademlia {
+    fn name(&self) -> &str {
+        "dht"
+    }
+    
+    fn source(&self) -> PeerSource {
+        PeerSource::Dht
+    }
+    
+    async fn discover_peers(&self) -> Vec<SocketAddr> {
+        // 자기 id로 조회하면 주변 노드를 네트워크에서 찾아 라우팅 테이블도 채워짐
+        let nodes = if self.has_transport() {
+            self.lookup(self.node_id).await
+        } else {
+            self.find_closest_nodes(&self.node_id, self.config.k).await
+        };
+        nodes.into_iter().map(|n| n.addr).collect()
+    }
+    
+    async fn announce(&self, _addr: SocketAddr) {}
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dht_sim::SimulatedDhtNetwork;
+    
+    fn config() -> DhtConfig {
+        DhtConfig {
+            k: 2,
+            alpha: 3,
+            query_timeout: Duration::from_millis(100),
+            refresh_interval: Duration::from_millis(200),
+            ..DhtConfig::default()
+        }
+    }
+    
+    fn ids(nodes: &[Node]) -> Vec<NodeId> {
+        nodes.iter().map(|n| n.id).collect()
+    }
+    
+    fn node_at(addr: &str) -> Node {
+        Node { id: NodeId::random(), addr: addr.parse().unwrap(), last_seen: 0 }
+    }
+    
+    #[test]
+    fn bucket_limits_nodes_per_address_group() {
+        let mut bucket = KBucket::with_capacity(20);
+        // 같은 /24는 2개까지
+        assert!(bucket.add_node(node_at("203.0.113.1:42000")));
+        assert!(bucket.add_node(node_at("203.0.113.2:42000")));
+        assert!(!bucket.add_node(node_at("203.0.113.3:42000")));
+        assert!(bucket.add_node(node_at("203.0.114.1:42000")));
+        // 같은 /48도 2개까지
+        assert!(bucket.add_node(node_at("[2001:db8:1:1::1]:42000")));
+        assert!(bucket.add_node(node_at("[2001:db8:1:2::1]:42000")));
+        assert!(!bucket.add_node(node_at("[2001:db8:1:ffff::1]:42000")));
+        assert!(bucket.add_node(node_at("[2001:db8:2::1]:42000")));
+        // 사설 주소는 제한 없음
+        for i in 1..=5 {
+            assert!(bucket.add_node(node_at(&format!("192.168.1.{}:42000", i))));
+        }
+        assert_eq!(bucket.get_nodes().len(), 11);
+        // 거절된 노드는 교체 후보에도 들어가지 않음
+        assert!(bucket.get_replacements().is_empty());
+    }
+    
+    #[test]
+    fn bucket_limits_replacements_per_address_group() {
+        let mut bucket = KBucket::with_capacity(3);
+        for addr in ["198.51.100.1:42000", "203.0.113.1:42000", "192.0.2.1:42000"] {
+            assert!(bucket.add_node(node_at(addr)));
+        }
+        // 가득 찬 버킷의 교체 후보도 같은 그룹은 2개까지
+        for i in 1..=3 {
+            assert!(!bucket.add_node(node_at(&format!("100.64.0.{}:42000", i))));
+        }
+        let replacements: Vec<SocketAddr> = bucket.get_replacements().iter().map(|n| n.addr).collect();
+        assert_eq!(replacements, vec!["100.64.0.1:42000".parse().unwrap(), "100.64.0.2:42000".parse().unwrap()]);
+    }
+    
+    /// 0번 버킷(첫 비트가 다른 id)에 노드 3개를 ping한 상태
+    async fn full_bucket(network: &SimulatedDhtNetwork) -> (Arc<Kademlia>, Vec<(SocketAddr, Arc<Kademlia>)>) {
+        let (_, node) = network.add_node(config());
+        let own_id = node.get_node_id();
+        let mut members = Vec::new();
+        for _ in 0..3 {
+            members.push(network.add_node_with_id(own_id.random_in_bucket(0), config()));
+        }
+        for (addr, _) in &members {
+            node.ping(*addr).await.expect("member should answer");
+        }
+        // ping 응답 처리와 가장 오래된 노드 확인이 끝날 때까지 대기
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        (node, members)
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn live_least_recent_node_is_kept() {
+        guild_logger::init_logger(true);
+        let network = SimulatedDhtNetwork::new();
+        network.set_latency(Duration::from_millis(1));
+        let (node, members) = full_bucket(&network).await;
+        let member_ids: Vec<NodeId> = members.iter().map(|(_, dht)| dht.get_node_id()).collect();
+        
+        // 살아 있는 가장 오래된 노드는 뒤로 옮겨지고 새 노드는 교체 후보에서 대기
+        assert_eq!(ids(&node.bucket_nodes(0).await), vec![member_ids[1], member_ids[0]]);
+        assert_eq!(ids(&node.bucket_replacements(0).await), vec![member_ids[2]]);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn unresponsive_least_recent_node_is_replaced() {
+        guild_logger::init_logger(true);
+        let network = SimulatedDhtNetwork::new();
+        network.set_latency(Duration::from_millis(1));
+        let (node, members) = full_bucket(&network).await;
+        let member_ids: Vec<NodeId> = members.iter().map(|(_, dht)| dht.get_node_id()).collect();
+        
+        // 가장 오래된 노드(members[1])가 응답하지 않으면 빼고 가장 최근 후보를 승격
+        network.set_offline(members[1].0, true);
+        let newcomer = network.add_node_with_id(node.get_node_id().random_in_bucket(0), config());
+        node.ping(newcomer.0).await.expect("newcomer should answer");
+        tokio::time::sleep(config().query_timeout * 2).await;
+        
+        let bucket = ids(&node.bucket_nodes(0).await);
+        assert!(!bucket.contains(&member_ids[1]), "offline node is evicted");
+        assert!(bucket.contains(&member_ids[0]));
+        assert!(bucket.contains(&newcomer.1.get_node_id()), "newest candidate is promoted");
+        assert_eq!(ids(&node.bucket_replacements(0).await), vec![member_ids[2]]);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn stale_buckets_are_refreshed() {
+        guild_logger::init_logger(true);
+        let network = SimulatedDhtNetwork::new();
+        network.set_latency(Duration::from_millis(1));
+        
+        // 모든 노드가 같은 부트스트랩 노드로 참여하고, 마지막 노드는 PING만 보낸 상태
+        let wide = DhtConfig { k: 8, ..config() };
+        let (bootstrap_addr, _bootstrap) = network.add_node(wide.clone());
+        let mut joined = Vec::new();
+        for _ in 0..40 {
+            let (addr, dht) = network.add_node(wide.clone());
+            dht.ping(bootstrap_addr).await.expect("bootstrap should answer");
+            dht.lookup(dht.get_node_id()).await;
+            joined.push((addr, dht));
+        }
+        let (_, last) = network.add_node(wide.clone());
+        last.ping(bootstrap_addr).await.expect("bootstrap should answer");
+        let before = last.node_count().await;
+        
+        tokio::time::sleep(wide.refresh_interval).await;
+        assert!(last.refresh_stale_buckets().await > 0);
+        assert!(last.node_count().await > before, "random lookups discover more nodes");
+        
+        // 새로 찾은 노드 때문에 범위에 들어온 더 가까운 버킷만 남고, 곧 더 갱신할 버킷이 없어짐
+        // (방금 갱신한 버킷은 다음 주기 전까지 다시 조회하지 않음)
+        let mut rounds = 0;
+        while last.refresh_stale_buckets().await > 0 {
+            rounds += 1;
+            assert!(rounds < NODE_ID_LENGTH * 8, "refresh never settles");
+        }
+        
+        // 마지막 갱신의 PING 응답 처리가 끝난 뒤에도 refresh_interval이 지나도록 넉넉히 대기
+        tokio::time::sleep(wide.refresh_interval * 2).await;
+        assert!(last.refresh_stale_buckets().await > 0, "buckets go stale again after refresh_interval");
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn lookup_reaches_a_target_several_hops_away() {
+        guild_logger::init_logger(true);
+        let network = SimulatedDhtNetwork::new();
+        network.set_latency(Duration::from_millis(1));
+        
+        // 각 노드가 바로 앞 노드만 아는 사슬 - 첫 노드는 두 번째 노드만 앎
+        let wide = DhtConfig { k: 8, ..config() };
+        let chain: Vec<(SocketAddr, Arc<Kademlia>)> = (0..8).map(|_| network.add_node(wide.clone())).collect();
+        for pair in chain.windows(2) {
+            pair[1].1.ping(pair[0].0).await.expect("neighbour should answer");
+        }
+        let (first, target) = (&chain[0].1, chain[7].1.get_node_id());
+        assert!(first.node(&target).await.is_none());
+        
+        let started = tokio::time::Instant::now();
+        let found = first.lookup(target).await;
+        assert_eq!(found.first().map(|n| n.id), Some(target));
+        assert!(first.node(&target).await.is_some(), "target joins the routing table");
+        // 한 번에 한 홉씩만 알게 되므로 왕복 7번이 순서대로 필요
+        assert!(started.elapsed() >= Duration::from_millis(14));
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn lookup_skips_offline_nodes() {
+        guild_logger::init_logger(true);
+        let network = SimulatedDhtNetwork::new();
+        network.set_latency(Duration::from_millis(1));
+        
+        let wide = DhtConfig { k: 8, ..config() };
+        let (_, node) = network.add_node(wide.clone());
+        let mut known = Vec::new();
+        for _ in 0..5 {
+            let (addr, dht) = network.add_node(wide.clone());
+            node.ping(addr).await.expect("node should answer");
+            known.push((addr, dht));
+        }
+        for (addr, _) in &known[..2] {
+            network.set_offline(*addr, true);
+        }
+        
+        let requests = network.request_count();
+        let mut found = ids(&node.lookup(NodeId::random()).await);
+        let mut online: Vec<NodeId> = known[2..].iter().map(|(_, dht)| dht.get_node_id()).collect();
+        found.sort_by_key(|id| id.0);
+        online.sort_by_key(|id| id.0);
+        assert_eq!(found, online, "only nodes that answered are returned");
+        // 응답 없는 노드는 후보에서 빠지므로 다음 라운드에 다시 묻지 않음
+        assert_eq!(network.request_count() - requests, known.len() as u64);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn records_are_replicated_to_k_nodes() {
+        guild_logger::init_logger(true);
+        let network = SimulatedDhtNetwork::new();
+        network.set_latency(Duration::from_millis(1));
+        
+        let wide = DhtConfig { k: 4, ..config() };
+        let (bootstrap_addr, bootstrap) = network.add_node(wide.clone());
+        let mut joined = Vec::new();
+        for _ in 0..12 {
+            let (addr, dht) = network.add_node(wide.clone());
+            dht.ping(bootstrap_addr).await.expect("bootstrap should answer");
+            dht.lookup(dht.get_node_id()).await;
+            joined.push((addr, dht));
+        }
+        
+        let (publisher_addr, publisher) = joined.remove(0);
+        let key = NodeId::random();
+        let stored = publisher.put(key, b"hello".to_vec(), Duration::from_secs(60)).await.unwrap();
+        assert_eq!(stored, wide.k);
+        let mut holders = bootstrap.record_count().await;
+        for (_, dht) in &joined {
+            holders += dht.record_count().await;
+        }
+        assert_eq!(holders, wide.k, "each accepted STORE is kept");
+        
+        // 발행자가 빠져도 부트스트랩만 아는 새 노드가 FIND_VALUE로 찾음
+        network.set_offline(publisher_addr, true);
+        let (_, reader) = network.add_node(wide.clone());
+        reader.ping(bootstrap_addr).await.expect("bootstrap should answer");
+        let records = reader.get(key).await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].value, b"hello");
+        assert_eq!(records[0].publisher, publisher.get_node_id());
+    }
+}
@@ -0,0 +1,121 @@
+// Kademlia RPC - DHT 노드 사이에 주고받는 요청/응답과 전송 계층 추상화
+//
+// guild-discovery는 연결을 직접 관리하지 않는다. 실제 전송은 `DhtTransport`를 구현한
+// 쪽(guild-home의 Network 프로토콜 핸들러 등)이 맡고, Kademlia는 이 트레이트로만 요청을 보낸다.
+//
+// 모든 메시지는 `DhtEnvelope`로 감싸서 bincode로 인코딩한다. 요청과 응답은 request_id로
+// 짝을 맞추고, sender에는 보낸 노드의 id를 담는다. sender는 보낸 쪽이 주장하는 값일 뿐이므로
+// 전송 계층은 연결에서 확인한 id(핸드셰이크 서명 등)와 맞는지 검사한 뒤에만 Kademlia에 넘긴다.
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::SocketAddr;
+
+use crate::dht::{Node, NodeId};
+use crate::dht_store::{DhtRecord, RecordError, MAX_PUBLISHERS_PER_KEY};
+
+/// Network 프로토콜 레지스트리에 등록할 프로토콜 id
+pub const DHT_PROTOCOL: &str = "guild/kad/1";
+
+/// 한 응답에 담을 수 있는 최대 노드 수 (악의적인 응답으로 메모리를 낭비하지 않도록)
+pub const MAX_NODES_PER_RESPONSE: usize = 64;
+/// 한 응답에 담을 수 있는 최대 레코드 수
+pub const MAX_RECORDS_PER_RESPONSE: usize = MAX_PUBLISHERS_PER_KEY;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DhtRequest {
+    Ping,
+    /// target에 가장 가까운 노드들을 요청
+    FindNode {
+        target: NodeId,
+    },
+    /// 레코드 보관 요청 (받는 쪽이 서명을 검증)
+    Store {
+        record: DhtRecord,
+    },
+    /// key의 레코드와, 없거나 더 찾아볼 수 있도록 key에 가까운 노드들을 요청
+    FindValue {
+        key: NodeId,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DhtResponse {
+    Pong,
+    Nodes(Vec<Node>),
+    Stored {
+        accepted: bool,
+    },
+    Value {
+        records: Vec<DhtRecord>,
+        nodes: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DhtBody {
+    Request(DhtRequest),
+    Response(DhtResponse),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DhtEnvelope {
+    pub request_id: u64,
+    pub sender: NodeId,
+    pub body: DhtBody,
+}
+
+impl DhtEnvelope {
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("DHT envelope serialization")
+    }
+
+    /// 디코딩 (잘못된 데이터면 None)
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        bincode::deserialize(data).ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DhtError {
+    /// 응답 대기 시간 초과
+    Timeout,
+    /// 연결 또는 전송 실패
+    Unreachable(String),
+    /// 요청과 맞지 않는 응답
+    UnexpectedResponse,
+    /// 레코드 발행에 필요한 노드 키가 없음
+    NoIdentity,
+    /// DHT가 꺼져 있음
+    Disabled,
+    Record(RecordError),
+}
+
+impl fmt::Display for DhtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DhtError::Timeout => write!(f, "DHT request timed out"),
+            DhtError::Unreachable(reason) => write!(f, "DHT peer unreachable: {}", reason),
+            DhtError::UnexpectedResponse => write!(f, "unexpected DHT response"),
+            DhtError::NoIdentity => write!(f, "no node key to sign DHT records"),
+            DhtError::Disabled => write!(f, "DHT is disabled"),
+            DhtError::Record(e) => write!(f, "invalid DHT record: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DhtError {}
+
+/// DHT 요청을 실제 네트워크로 보내는 전송 계층
+#[async_trait]
+pub trait DhtTransport: Send + Sync {
+    /// `to`에게 요청을 보내고 응답을 기다림
+    ///
+    /// 응답한 노드의 id와 응답을 반환한다. 이 id는 라우팅 테이블에 추가되므로 전송 계층이
+    /// 확인한 id여야 한다. 시간 제한은 호출하는 쪽(Kademlia)이 건다.
+    async fn request(
+        &self,
+        to: SocketAddr,
+        request: DhtRequest,
+    ) -> Result<(NodeId, DhtResponse), DhtError>;
+}
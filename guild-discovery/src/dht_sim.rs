@@ -0,0 +1,130 @@
+// DHT 시뮬레이션 네트워크 - 실제 소켓 없이 여러 Kademlia 노드를 한 프로세스에서 연결
+//
+// 각 노드는 가상의 127.0.0.1 포트를 받고, 요청은 상대 노드의 `handle_request`를 직접 호출해서
+// 처리한다. 오프라인으로 표시한 노드로 가는 요청(또는 오프라인 노드가 보내는 요청)은 응답이
+// 오지 않으므로 Kademlia의 query_timeout에 걸린다. 버킷 유지 정책이나 조회 수렴을 재현 가능하게
+// 확인할 때 사용 (dht.rs의 테스트 참고). 테스트 빌드나 `test-util` 기능에서만 포함된다.
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::Duration;
+
+use crate::dht::{DhtConfig, Kademlia, NodeId};
+use crate::dht_rpc::{DhtError, DhtRequest, DhtResponse, DhtTransport};
+use crate::identity::NodeIdentity;
+
+const FIRST_PORT: u16 = 20000;
+
+#[derive(Default)]
+struct SimState {
+    nodes: HashMap<SocketAddr, Weak<Kademlia>>,
+    offline: HashSet<SocketAddr>,
+    next_port: u16,
+    latency: Duration,
+    requests: u64,
+}
+
+/// 시뮬레이션 네트워크 (복제해도 같은 네트워크를 가리킴)
+#[derive(Clone, Default)]
+pub struct SimulatedDhtNetwork {
+    state: Arc<RwLock<SimState>>,
+}
+
+impl SimulatedDhtNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 모든 요청에 적용할 편도 지연
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.write().unwrap().latency = latency;
+    }
+
+    /// 새 노드 키로 노드 추가 (레코드 발행 가능)
+    pub fn add_node(&self, config: DhtConfig) -> (SocketAddr, Arc<Kademlia>) {
+        self.attach(Arc::new(Kademlia::with_identity(
+            NodeIdentity::generate(),
+            config,
+        )))
+    }
+
+    /// 지정한 id로 새 노드 추가 (특정 버킷을 채우는 시나리오용, 레코드 발행 불가)
+    pub fn add_node_with_id(&self, id: NodeId, config: DhtConfig) -> (SocketAddr, Arc<Kademlia>) {
+        self.attach(Arc::new(Kademlia::with_config(id, config)))
+    }
+
+    /// 이미 만든 노드(Discovery 안의 DHT 등)를 네트워크에 연결
+    pub fn attach(&self, dht: Arc<Kademlia>) -> (SocketAddr, Arc<Kademlia>) {
+        let id = dht.get_node_id();
+        let addr = {
+            let mut state = self.state.write().unwrap();
+            let port = FIRST_PORT + state.next_port;
+            state.next_port += 1;
+            let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
+            state.nodes.insert(addr, Arc::downgrade(&dht));
+            addr
+        };
+
+        dht.set_transport(Arc::new(SimulatedTransport {
+            network: self.clone(),
+            local_addr: addr,
+            local_id: id,
+        }));
+        (addr, dht)
+    }
+
+    /// 오프라인 상태 설정 (오프라인 노드는 요청을 보내지도 받지도 못함)
+    pub fn set_offline(&self, addr: SocketAddr, offline: bool) {
+        let mut state = self.state.write().unwrap();
+        if offline {
+            state.offline.insert(addr);
+        } else {
+            state.offline.remove(&addr);
+        }
+    }
+
+    /// 지금까지 처리한 요청 수 (응답하지 못한 요청 포함)
+    pub fn request_count(&self) -> u64 {
+        self.state.read().unwrap().requests
+    }
+}
+
+struct SimulatedTransport {
+    network: SimulatedDhtNetwork,
+    local_addr: SocketAddr,
+    local_id: NodeId,
+}
+
+#[async_trait]
+impl DhtTransport for SimulatedTransport {
+    async fn request(
+        &self,
+        to: SocketAddr,
+        request: DhtRequest,
+    ) -> Result<(NodeId, DhtResponse), DhtError> {
+        let (target, reachable, latency) = {
+            let mut state = self.network.state.write().unwrap();
+            state.requests += 1;
+            let reachable =
+                !state.offline.contains(&to) && !state.offline.contains(&self.local_addr);
+            let target = state.nodes.get(&to).and_then(Weak::upgrade);
+            (target, reachable, state.latency)
+        };
+
+        let Some(target) = target else {
+            return Err(DhtError::Unreachable(format!("no node at {}", to)));
+        };
+        if !reachable {
+            // 응답이 오지 않는 상황 - 호출한 쪽의 시간 제한으로 끝남
+            std::future::pending::<()>().await;
+        }
+
+        tokio::time::sleep(latency).await;
+        let response = target
+            .handle_request(self.local_addr, self.local_id, request)
+            .await;
+        tokio::time::sleep(latency).await;
+        Ok((target.get_node_id(), response))
+    }
+}
@@ -0,0 +1,443 @@
+// DHT 레코드 저장소 - STORE/FIND_VALUE로 주고받는 서명된 레코드
+//
+// 레코드는 키(256비트, 노드 id와 같은 공간)마다 발행자별로 하나씩 보관한다. 같은 키에
+// 여러 노드가 발행할 수 있고(서비스 제공자 목록 등), 같은 발행자의 레코드는 sequence가
+// 더 큰 것으로만 교체된다. 모든 레코드는 발행자의 ed25519 키로 서명되어 있고, 발행자
+// id는 공개키의 해시여야 한다. network id도 서명에 포함되므로 다른 network의 레코드는
+// 옮겨 와도 받아들여지지 않는다.
+//
+// 발행자 id는 키만 만들면 얼마든지 생기므로, STORE를 보낸 주소 그룹(/24, /48)마다 키 하나에
+// 넣을 수 있는 새 발행자 수를 제한한다. 한 대역에서 키를 가득 채워 정직한 발행자를 밀어내지
+// 못하게 하기 위함.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::dht::NodeId;
+use crate::diversity::{AddressGroup, DEFAULT_MAX_STORES_PER_GROUP_PER_KEY};
+use crate::identity::{self, NodeIdentity, PUBLIC_KEY_LENGTH};
+
+/// 레코드 값 최대 크기
+pub const MAX_RECORD_VALUE_SIZE: usize = 4096;
+/// 허용하는 최대 유효 기간 (이보다 먼 만료 시각은 거부)
+pub const MAX_RECORD_TTL: Duration = Duration::from_secs(48 * 60 * 60);
+/// 키 하나에 보관하는 최대 발행자 수 (가득 차면 가장 오래 갱신되지 않은 발행자를 밀어냄)
+pub const MAX_PUBLISHERS_PER_KEY: usize = 32;
+/// 노드 하나가 보관하는 최대 레코드 수
+pub const MAX_STORED_RECORDS: usize = 10_000;
+
+const SIGNATURE_DOMAIN: &[u8] = b"guild-dht-record-v1";
+
+/// 문자열/바이트 이름을 레코드 키로 변환
+pub fn record_key(name: &[u8]) -> NodeId {
+    NodeId(*blake3::hash(name).as_bytes())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DhtRecord {
+    pub key: NodeId,
+    /// 발행한 노드의 network id
+    pub network_id: String,
+    pub value: Vec<u8>,
+    pub publisher: NodeId,
+    pub public_key: [u8; PUBLIC_KEY_LENGTH],
+    /// 같은 발행자의 레코드 중 큰 값이 최신
+    pub sequence: u64,
+    /// 만료 시각 (unix 초)
+    pub expires_at: u64,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    ValueTooLarge(usize),
+    Expired,
+    TtlTooLong,
+    /// 발행자 id가 공개키와 맞지 않음
+    PublisherMismatch,
+    BadSignature,
+    /// 이미 같거나 더 새로운 레코드가 있음
+    Stale,
+    StoreFull,
+    /// 다른 network의 레코드
+    WrongNetwork,
+    /// STORE를 보낸 주소 그룹이 이 키에 이미 제한만큼 발행자를 넣음
+    GroupFull,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::ValueTooLarge(size) => write!(
+                f,
+                "record value too large ({} > {} bytes)",
+                size, MAX_RECORD_VALUE_SIZE
+            ),
+            RecordError::Expired => write!(f, "record expired"),
+            RecordError::TtlTooLong => write!(f, "record TTL too long"),
+            RecordError::PublisherMismatch => write!(f, "publisher id does not match public key"),
+            RecordError::BadSignature => write!(f, "invalid record signature"),
+            RecordError::Stale => write!(f, "newer record already stored"),
+            RecordError::StoreFull => write!(f, "record store full"),
+            RecordError::WrongNetwork => write!(f, "record from another network"),
+            RecordError::GroupFull => write!(f, "too many publishers from this address group"),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+impl DhtRecord {
+    /// 새 레코드를 만들고 서명
+    pub fn sign(
+        identity: &NodeIdentity,
+        network_id: &str,
+        key: NodeId,
+        value: Vec<u8>,
+        sequence: u64,
+        ttl: Duration,
+    ) -> Self {
+        let mut record = DhtRecord {
+            key,
+            network_id: network_id.to_string(),
+            value,
+            publisher: identity.node_id(),
+            public_key: identity.public_key(),
+            sequence,
+            expires_at: now_secs().saturating_add(ttl.as_secs()),
+            signature: Vec::new(),
+        };
+        record.signature = identity.sign(&record.signing_bytes());
+        record
+    }
+
+    fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            SIGNATURE_DOMAIN.len() + 97 + self.network_id.len() + self.value.len(),
+        );
+        bytes.extend_from_slice(SIGNATURE_DOMAIN);
+        bytes.extend_from_slice(&self.key.0);
+        // 길이를 앞에 붙여서 network id와 뒤 필드의 경계가 모호하지 않도록 함
+        bytes.push(self.network_id.len().min(u8::MAX as usize) as u8);
+        bytes.extend_from_slice(self.network_id.as_bytes());
+        bytes.extend_from_slice(&self.publisher.0);
+        bytes.extend_from_slice(&self.sequence.to_be_bytes());
+        bytes.extend_from_slice(&self.expires_at.to_be_bytes());
+        bytes.extend_from_slice(&self.value);
+        bytes
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= now_secs()
+    }
+
+    /// 크기, 유효 기간, 발행자 id, 서명 검사
+    pub fn verify(&self) -> Result<(), RecordError> {
+        if self.value.len() > MAX_RECORD_VALUE_SIZE {
+            return Err(RecordError::ValueTooLarge(self.value.len()));
+        }
+        if self.is_expired() {
+            return Err(RecordError::Expired);
+        }
+        if self.expires_at > now_secs().saturating_add(MAX_RECORD_TTL.as_secs()) {
+            return Err(RecordError::TtlTooLong);
+        }
+        if identity::node_id_for_key(&self.public_key) != self.publisher {
+            return Err(RecordError::PublisherMismatch);
+        }
+        if !identity::verify(&self.public_key, &self.signing_bytes(), &self.signature) {
+            return Err(RecordError::BadSignature);
+        }
+        Ok(())
+    }
+}
+
+struct StoredRecord {
+    record: DhtRecord,
+    // 마지막으로 저장/갱신한 순서 (클수록 최근)
+    stored: u64,
+    // 레코드를 보낸 주소 그룹 (직접 발행했거나 제한하지 않는 주소면 None)
+    source: Option<AddressGroup>,
+}
+
+/// 키 -> 발행자 -> 레코드 (한 network의 레코드만 보관)
+pub struct RecordStore {
+    network_id: String,
+    records: HashMap<NodeId, HashMap<NodeId, StoredRecord>>,
+    count: usize,
+    next_stored: u64,
+    max_per_group: usize,
+}
+
+impl RecordStore {
+    pub fn new(network_id: &str) -> Self {
+        Self {
+            network_id: network_id.to_string(),
+            records: HashMap::new(),
+            count: 0,
+            next_stored: 0,
+            max_per_group: DEFAULT_MAX_STORES_PER_GROUP_PER_KEY,
+        }
+    }
+
+    /// 키 하나에 같은 주소 그룹이 넣을 수 있는 발행자 수 설정
+    pub fn with_group_limit(mut self, max_per_group: usize) -> Self {
+        self.max_per_group = max_per_group;
+        self
+    }
+
+    /// 검증 후 저장 (같은 발행자의 더 새로운 레코드면 교체)
+    ///
+    /// 키의 발행자가 가득 차 있으면 만료된 레코드부터 빼고, 그래도 자리가 없으면 가장 오래
+    /// 갱신되지 않은 발행자를 밀어낸다. 먼저 들어온 발행자들이 키를 영구히 차지하지 못하고,
+    /// 주기적으로 재발행하는 발행자가 남는다.
+    pub fn insert(&mut self, record: DhtRecord) -> Result<(), RecordError> {
+        self.insert_from(record, None)
+    }
+
+    /// 다른 노드가 STORE로 보낸 레코드 저장 (`source`는 보낸 주소의 그룹)
+    ///
+    /// 같은 그룹에서 이 키에 넣은 발행자가 이미 제한만큼 있으면 새 발행자는 거부한다.
+    /// 이미 있는 발행자의 갱신은 그룹과 상관없이 받아들이고, 처음 넣은 그룹의 몫으로 센다.
+    pub fn insert_from(
+        &mut self,
+        record: DhtRecord,
+        source: Option<AddressGroup>,
+    ) -> Result<(), RecordError> {
+        if record.network_id != self.network_id {
+            return Err(RecordError::WrongNetwork);
+        }
+        record.verify()?;
+
+        let key = record.key;
+        let publishers = self.records.entry(key).or_default();
+        let (is_new, source) = match publishers.get(&record.publisher) {
+            Some(existing) if existing.record.sequence >= record.sequence => {
+                return Err(RecordError::Stale)
+            }
+            // 갱신은 처음 넣은 그룹의 몫으로 남김
+            Some(existing) => (false, existing.source),
+            None => (true, source),
+        };
+        if is_new && source.is_some() {
+            let from_group = publishers
+                .values()
+                .filter(|stored| stored.source == source && !stored.record.is_expired())
+                .count();
+            if from_group >= self.max_per_group {
+                if publishers.is_empty() {
+                    self.records.remove(&key);
+                }
+                return Err(RecordError::GroupFull);
+            }
+        }
+        if is_new && self.count >= MAX_STORED_RECORDS {
+            if publishers.is_empty() {
+                self.records.remove(&key);
+            }
+            return Err(RecordError::StoreFull);
+        }
+        if is_new && publishers.len() >= MAX_PUBLISHERS_PER_KEY {
+            let before = publishers.len();
+            publishers.retain(|_, stored| !stored.record.is_expired());
+            if publishers.len() >= MAX_PUBLISHERS_PER_KEY {
+                let oldest = publishers
+                    .iter()
+                    .min_by_key(|(_, stored)| stored.stored)
+                    .map(|(publisher, _)| *publisher);
+                if let Some(oldest) = oldest {
+                    publishers.remove(&oldest);
+                }
+            }
+            self.count -= before - publishers.len();
+        }
+
+        let stored = self.next_stored;
+        self.next_stored += 1;
+        publishers.insert(
+            record.publisher,
+            StoredRecord {
+                record,
+                stored,
+                source,
+            },
+        );
+        if is_new {
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    /// 키에 대한 유효한 레코드 (만료된 것 제외)
+    pub fn get(&self, key: &NodeId) -> Vec<DhtRecord> {
+        self.records
+            .get(key)
+            .map(|publishers| {
+                publishers
+                    .values()
+                    .map(|stored| &stored.record)
+                    .filter(|record| !record.is_expired())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 만료된 레코드 제거 (제거한 수 반환)
+    pub fn expire(&mut self) -> usize {
+        let before = self.count;
+        self.records.retain(|_, publishers| {
+            publishers.retain(|_, stored| !stored.record.is_expired());
+            !publishers.is_empty()
+        });
+        self.count = self.records.values().map(HashMap::len).sum();
+        before - self.count
+    }
+
+    /// 보관 중인 모든 유효 레코드 (재발행용)
+    pub fn all(&self) -> Vec<DhtRecord> {
+        self.records
+            .values()
+            .flat_map(|publishers| publishers.values())
+            .map(|stored| &stored.record)
+            .filter(|record| !record.is_expired())
+            .cloned()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+/// 발행자별로 가장 새로운 레코드만 남김
+pub fn merge_records(records: impl IntoIterator<Item = DhtRecord>) -> Vec<DhtRecord> {
+    let mut latest: HashMap<NodeId, DhtRecord> = HashMap::new();
+    for record in records {
+        match latest.get(&record.publisher) {
+            Some(existing) if existing.sequence >= record.sequence => {}
+            _ => {
+                latest.insert(record.publisher, record);
+            }
+        }
+    }
+    let mut merged: Vec<DhtRecord> = latest.into_values().collect();
+    merged.sort_by_key(|record| std::cmp::Reverse(record.sequence));
+    merged
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(identity: &NodeIdentity, sequence: u64) -> DhtRecord {
+        DhtRecord::sign(
+            identity,
+            "test",
+            record_key(b"crowded"),
+            b"value".to_vec(),
+            sequence,
+            Duration::from_secs(60),
+        )
+    }
+
+    fn publishers(store: &RecordStore) -> Vec<NodeId> {
+        store
+            .get(&record_key(b"crowded"))
+            .iter()
+            .map(|record| record.publisher)
+            .collect()
+    }
+
+    #[test]
+    fn full_key_evicts_least_recently_refreshed_publisher() {
+        let mut store = RecordStore::new("test");
+        let early: Vec<NodeIdentity> = (0..MAX_PUBLISHERS_PER_KEY)
+            .map(|_| NodeIdentity::generate())
+            .collect();
+        for identity in &early {
+            store.insert(record(identity, 1)).unwrap();
+        }
+
+        // 첫 발행자는 재발행해서 최근으로 갱신, 새 발행자는 두 번째 발행자를 밀어냄
+        store.insert(record(&early[0], 2)).unwrap();
+        let newcomer = NodeIdentity::generate();
+        store.insert(record(&newcomer, 1)).unwrap();
+
+        let stored = publishers(&store);
+        assert_eq!(store.len(), MAX_PUBLISHERS_PER_KEY);
+        assert_eq!(stored.len(), MAX_PUBLISHERS_PER_KEY);
+        assert!(stored.contains(&newcomer.node_id()));
+        assert!(stored.contains(&early[0].node_id()));
+        assert!(!stored.contains(&early[1].node_id()));
+    }
+
+    #[test]
+    fn records_from_other_networks_are_rejected() {
+        let mut store = RecordStore::new("other-net");
+        let identity = NodeIdentity::generate();
+        assert_eq!(
+            store.insert(record(&identity, 1)),
+            Err(RecordError::WrongNetwork)
+        );
+        assert!(store.is_empty());
+        assert!(publishers(&store).is_empty());
+    }
+
+    #[test]
+    fn stale_sequence_is_rejected() {
+        let mut store = RecordStore::new("test");
+        let identity = NodeIdentity::generate();
+        store.insert(record(&identity, 2)).unwrap();
+        assert_eq!(store.insert(record(&identity, 2)), Err(RecordError::Stale));
+        assert_eq!(store.insert(record(&identity, 1)), Err(RecordError::Stale));
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn one_address_group_cannot_crowd_out_other_publishers() {
+        let diversity = crate::diversity::DiversityConfig::default();
+        let group = |addr: &str| diversity.group(&addr.parse().unwrap());
+        let mut store = RecordStore::new("test");
+        let honest: Vec<NodeIdentity> = (0..4).map(|_| NodeIdentity::generate()).collect();
+        for (i, identity) in honest.iter().enumerate() {
+            let source = group(&format!("198.51.{}.1:42000", i));
+            store.insert_from(record(identity, 1), source).unwrap();
+        }
+
+        // 한 /24에서 새 키쌍을 계속 만들어 보내도 제한만큼만 들어감
+        let sybil = group("203.0.113.7:42000");
+        let mut accepted = 0;
+        for _ in 0..MAX_PUBLISHERS_PER_KEY {
+            match store.insert_from(record(&NodeIdentity::generate(), 1), sybil) {
+                Ok(()) => accepted += 1,
+                Err(err) => assert_eq!(err, RecordError::GroupFull),
+            }
+        }
+        assert_eq!(accepted, DEFAULT_MAX_STORES_PER_GROUP_PER_KEY);
+
+        let stored = publishers(&store);
+        assert!(honest
+            .iter()
+            .all(|identity| stored.contains(&identity.node_id())));
+        // 이미 있는 발행자의 갱신과 직접 발행은 그룹 제한과 상관없음
+        store.insert_from(record(&honest[0], 2), sybil).unwrap();
+        store.insert(record(&NodeIdentity::generate(), 1)).unwrap();
+        assert_eq!(
+            store.insert_from(record(&NodeIdentity::generate(), 1), sybil),
+            Err(RecordError::GroupFull)
+        );
+    }
+}
@@ -1,14 +1,60 @@
 // Discovery - 통합 피어 발견 시스템
+//
+// 피어를 찾는 방법(LAN 스캔, 부트스트랩, mDNS, DHT, 정적 피어 파일, 직접 추가한 방법)은
+// 모두 `DiscoveryMethod`로 등록된다. 탐색 한 번에 등록된 방법을 동시에 실행하고, 방법마다
+// 시간 제한을 두어 느린 방법이 전체를 붙잡지 않도록 한다. 결과는 주소별로 합쳐서, 주소록에서
+// 연결에 성공한 적 있는 피어 → 더 많은 방법이 찾은 피어 → 먼저 등록된 방법이 찾은 피어 순으로
+// 정렬한 뒤, 한 주소 그룹(/24, /48)이 연결 대상을 독차지하지 않도록 그룹마다 개수를 제한해서
+// max_peers개를 고른다 (eclipse 공격 완화, diversity.rs 참고).
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
-use crate::bootstrap::{Bootstrap, PeerInfo};
-use crate::dht::{Kademlia, Node, NodeId};
-use crate::local_scan::LocalScanner;
+use crate::address_book::{AddressBook, PeerSource};
+use crate::bootstrap::{
+    self, Bootstrap, PeerInfo, Resolution, DEFAULT_RESOLVE_INTERVAL, MAX_KNOWN_PEERS,
+};
+use crate::capabilities::{
+    capability_key, CapabilityAdvert, CapablePeer, CAPABILITY_REPUBLISH_INTERVAL, CAPABILITY_TTL,
+};
+use crate::dht::{DhtConfig, Kademlia, Node, NodeId};
+use crate::dht_rpc::{DhtError, DhtTransport};
+use crate::dht_store::{record_key, DhtRecord};
+use crate::diversity::DiversityConfig;
+use crate::identity::NodeIdentity;
+use crate::local_scan::{LocalScanner, ScanConfig};
 use crate::log_network;
+use crate::mdns::MdnsDiscovery;
+use crate::static_peers::StaticPeers;
+
+pub const DEFAULT_NETWORK_ID: &str = "guild";
+/// 이 시간 동안 연결/응답이 없던 피어는 정리 시 제거
+pub const DEFAULT_PEER_MAX_AGE: Duration = Duration::from_secs(6 * 60 * 60);
+/// 피어 목록 정리 주기
+pub const DEFAULT_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// 연결 전까지 기억하는 주소별 발견 경로의 최대 개수 (넘으면 가장 오래된 기록부터 제거)
+pub const MAX_TRACKED_SOURCES: usize = 4096;
+/// network id 최대 길이 (mDNS TXT 값과 비컨 패킷에 들어감)
+pub const MAX_NETWORK_ID_LEN: usize = 64;
+
+/// network id 형식 검사 (1~64자의 출력 가능한 ASCII, 예: 길드 이름이나 제네시스 해시)
+pub fn validate_network_id(network_id: &str) -> Result<(), String> {
+    if network_id.is_empty() || network_id.len() > MAX_NETWORK_ID_LEN {
+        return Err(format!(
+            "network id must be 1-{} characters",
+            MAX_NETWORK_ID_LEN
+        ));
+    }
+    if !network_id.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err("network id must be printable ASCII without spaces".to_string());
+    }
+    Ok(())
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscoveryConfig {
@@ -16,6 +62,31 @@ pub struct DiscoveryConfig {
     pub enable_dht: bool,
     pub max_peers: usize,
     pub port: u16,
+    /// LAN에서 mDNS로 피어 광고 및 검색
+    pub enable_mdns: bool,
+    /// 같은 network id를 가진 피어만 발견
+    pub network_id: String,
+    /// 부트스트랩 호스트 이름(DNS 시드) 재조회 주기 (초)
+    pub dns_refresh_secs: u64,
+    /// LAN 비컨 응답기 실행 및 브로드캐스트/멀티캐스트 스캔
+    pub enable_scan: bool,
+    pub scan: ScanConfig,
+    /// 정적 피어 파일 경로 (None이면 사용 안 함)
+    pub static_peers_file: Option<PathBuf>,
+    /// 탐색 방법 하나의 기본 시간 제한 (초)
+    pub method_timeout_secs: u64,
+    /// 방법 이름별 시간 제한 (초, 예: "dht" -> 20, 지정하지 않은 방법은 예상 시간만큼 늘어남)
+    pub method_timeouts: HashMap<String, u64>,
+    /// 광고할 역할 태그 (예: "validator", "relay")
+    pub capabilities: Vec<String>,
+    /// 주소 그룹별 제한 (DHT 버킷, 주소록, 연결 대상 선택)
+    pub diversity: DiversityConfig,
+    /// 연결한 피어 목록의 최대 크기 (넘으면 가장 오래 소식이 없는 피어부터 제거)
+    pub max_known_peers: usize,
+    /// 이 시간(초) 동안 확인되지 않은 피어는 정리 시 제거
+    pub peer_max_age_secs: u64,
+    /// 백그라운드 정리 주기 (초)
+    pub maintenance_interval_secs: u64,
 }
 
 impl Default for DiscoveryConfig {
@@ -25,115 +96,359 @@ impl Default for DiscoveryConfig {
             enable_dht: true,
             max_peers: 100,
             port: 42000,
+            enable_mdns: true,
+            network_id: DEFAULT_NETWORK_ID.to_string(),
+            dns_refresh_secs: DEFAULT_RESOLVE_INTERVAL.as_secs(),
+            enable_scan: true,
+            scan: ScanConfig::default(),
+            static_peers_file: None,
+            method_timeout_secs: 10,
+            method_timeouts: HashMap::new(),
+            capabilities: Vec::new(),
+            diversity: DiversityConfig::default(),
+            max_known_peers: MAX_KNOWN_PEERS,
+            peer_max_age_secs: DEFAULT_PEER_MAX_AGE.as_secs(),
+            maintenance_interval_secs: DEFAULT_MAINTENANCE_INTERVAL.as_secs(),
         }
     }
 }
 
 #[async_trait]
 pub trait DiscoveryMethod: Send + Sync {
+    /// 결과 보고와 method_timeouts 설정에 쓰는 이름
+    fn name(&self) -> &str;
+
+    /// 이 방법으로 찾은 피어에 연결하면 주소록에 기록할 경로
+    fn source(&self) -> PeerSource {
+        PeerSource::Manual
+    }
+
+    /// 한 번 탐색하는 데 걸리는 예상 시간 (기본 시간 제한보다 길면 이 값을 제한으로 사용)
+    ///
+    /// 전송 속도가 제한된 스캔처럼 설정에 따라 오래 걸리는 방법이, 이미 확인한 피어를
+    /// 시간 제한에 걸려 잃지 않도록 한다.
+    fn expected_duration(&self) -> Option<Duration> {
+        None
+    }
+
     async fn discover_peers(&self) -> Vec<SocketAddr>;
     async fn announce(&self, addr: SocketAddr);
 }
 
+/// 탐색 결과의 피어 하나
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredPeer {
+    pub addr: SocketAddr,
+    /// 이 피어를 찾은 방법 이름 (등록 순서)
+    pub found_by: Vec<String>,
+    /// 주소록에 연결 성공 기록이 있음
+    pub known: bool,
+}
+
+/// 탐색 방법별 실행 결과
+#[derive(Debug, Clone)]
+pub struct MethodReport {
+    pub name: String,
+    pub found: usize,
+    pub elapsed: Duration,
+    /// 시간 제한에 걸려 결과 없이 끝남
+    pub timed_out: bool,
+}
+
 pub struct Discovery {
     config: DiscoveryConfig,
     bootstrap: Arc<Bootstrap>,
     dht: Option<Arc<Kademlia>>,
+    mdns: Option<Arc<MdnsDiscovery>>,
+    static_peers: Option<Arc<StaticPeers>>,
+    // 등록 순서가 곧 우선순위
+    methods: std::sync::RwLock<Vec<Arc<dyn DiscoveryMethod>>>,
+    reports: RwLock<Vec<MethodReport>>,
     discovered_peers: Arc<RwLock<Vec<PeerInfo>>>,
     node_id: NodeId,
+    address_book: Arc<AddressBook>,
+    // 각 주소를 찾은 경로와 기록한 시각 (연결 성공 시 주소록에 기록하고 제거)
+    sources: RwLock<HashMap<SocketAddr, (PeerSource, u64)>>,
+    // 주소별로 알고 있는 역할 태그 (mDNS, DHT 광고, 직접 등록)
+    peer_capabilities: RwLock<HashMap<SocketAddr, CapablePeer>>,
 }
 
 impl Discovery {
     pub fn new(config: DiscoveryConfig) -> Self {
-        let node_id = NodeId::random();
-        let bootstrap = Arc::new(Bootstrap::new(config.bootstrap_nodes.clone()));
+        Self::with_address_book(config, Arc::new(AddressBook::in_memory()))
+    }
+
+    /// 영속 주소록과 함께 생성 (저장된 주소로 다이얼을 시작)
+    pub fn with_address_book(config: DiscoveryConfig, address_book: Arc<AddressBook>) -> Self {
+        Self::with_identity(config, address_book, NodeIdentity::generate())
+    }
+
+    /// 노드 키를 지정해서 생성 (노드 id와 DHT 레코드 서명에 사용)
+    pub fn with_identity(
+        config: DiscoveryConfig,
+        address_book: Arc<AddressBook>,
+        identity: NodeIdentity,
+    ) -> Self {
+        let node_id = identity.node_id();
+        let bootstrap = Arc::new(
+            Bootstrap::with_resolve_interval(
+                config.bootstrap_nodes.clone(),
+                Duration::from_secs(config.dns_refresh_secs),
+            )
+            .with_diversity(config.diversity.clone())
+            .with_max_known_peers(config.max_known_peers),
+        );
+        address_book.set_diversity(config.diversity.clone());
 
         let dht = if config.enable_dht {
-            Some(Arc::new(Kademlia::new(node_id)))
+            let dht_config = DhtConfig {
+                network_id: config.network_id.clone(),
+                diversity: config.diversity.clone(),
+                ..DhtConfig::default()
+            };
+            Some(Arc::new(Kademlia::with_identity(identity, dht_config)))
         } else {
             None
         };
 
+        let mdns = if config.enable_mdns {
+            match MdnsDiscovery::with_capabilities(
+                node_id,
+                &config.network_id,
+                config.capabilities.clone(),
+            ) {
+                Ok(mdns) => {
+                    if let Err(e) = mdns.register(config.port) {
+                        let error = e.to_string();
+                        log_network!("⚠️ mDNS registration failed: {}", error);
+                    }
+                    Some(Arc::new(mdns))
+                }
+                Err(e) => {
+                    // 멀티캐스트를 쓸 수 없는 환경에서는 mDNS 없이 동작
+                    let error = e.to_string();
+                    log_network!("⚠️ mDNS unavailable: {}", error);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let scanner = if config.enable_scan {
+            let scanner = LocalScanner::new(
+                node_id,
+                &config.network_id,
+                config.port,
+                config.scan.clone(),
+            );
+            if let Err(e) = scanner.start_responder() {
+                // 비컨 포트를 열 수 없어도 스캔(probe 전송)은 가능
+                let error = e.to_string();
+                log_network!("⚠️ LAN beacon responder unavailable: {}", error);
+            }
+            Some(Arc::new(scanner))
+        } else {
+            None
+        };
+
+        let static_peers = config
+            .static_peers_file
+            .as_ref()
+            .map(|path| Arc::new(StaticPeers::new(path)));
+
+        // 기본 탐색 방법 (등록 순서 = 우선순위)
+        let mut methods: Vec<Arc<dyn DiscoveryMethod>> = Vec::new();
+        if let Some(static_peers) = &static_peers {
+            methods.push(static_peers.clone());
+        }
+        if !config.bootstrap_nodes.is_empty() {
+            methods.push(bootstrap.clone());
+        }
+        if let Some(mdns) = &mdns {
+            methods.push(mdns.clone());
+        }
+        if let Some(scanner) = scanner {
+            methods.push(scanner);
+        }
+        if let Some(dht) = &dht {
+            methods.push(dht.clone());
+        }
+
         Self {
             config,
             bootstrap,
             dht,
+            mdns,
+            static_peers,
+            methods: std::sync::RwLock::new(methods),
+            reports: RwLock::new(Vec::new()),
             discovered_peers: Arc::new(RwLock::new(Vec::new())),
             node_id,
+            address_book,
+            sources: RwLock::new(HashMap::new()),
+            peer_capabilities: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 탐색 방법 추가 (기본 방법들 뒤에 붙으므로 순위가 가장 낮음)
+    pub fn add_method(&self, method: Arc<dyn DiscoveryMethod>) {
+        let name = method.name().to_string();
+        log_network!("🧩 Discovery method registered: {}", name);
+        self.methods.write().unwrap().push(method);
+    }
+
+    /// 이름으로 탐색 방법 제거 (제거했으면 true)
+    pub fn remove_method(&self, name: &str) -> bool {
+        let mut methods = self.methods.write().unwrap();
+        let before = methods.len();
+        methods.retain(|method| method.name() != name);
+        methods.len() != before
+    }
+
+    /// 등록된 탐색 방법 이름 (우선순위 순)
+    pub fn method_names(&self) -> Vec<String> {
+        self.methods
+            .read()
+            .unwrap()
+            .iter()
+            .map(|method| method.name().to_string())
+            .collect()
+    }
+
+    // method_timeouts에 지정한 값, 없으면 기본값과 방법의 예상 시간 중 긴 쪽
+    fn method_timeout(&self, method: &dyn DiscoveryMethod) -> Duration {
+        if let Some(secs) = self.config.method_timeouts.get(method.name()) {
+            return Duration::from_secs(*secs);
+        }
+        let default = Duration::from_secs(self.config.method_timeout_secs);
+        method
+            .expected_duration()
+            .map_or(default, |expected| expected.max(default))
+    }
+
+    /// 모든 탐색 방법에 자기 주소 알림 (mDNS 재등록, 비컨 응답 포트 갱신 등)
+    pub async fn announce(&self, addr: SocketAddr) {
+        let methods = self.methods.read().unwrap().clone();
+        for method in methods {
+            method.announce(addr).await;
         }
     }
 
+    /// 마지막 탐색에서 방법별로 찾은 피어 수와 걸린 시간
+    pub async fn method_reports(&self) -> Vec<MethodReport> {
+        self.reports.read().await.clone()
+    }
+
+    /// 다이얼할 피어 주소 (순위 순, 최대 max_peers개)
     pub async fn start(&self) -> Vec<SocketAddr> {
-        let mut peers = Vec::new();
-
-        // 1. 로컬 네트워크 스캔 (Bootstrap 없이도 동작)
-        log_network!("🔍 Scanning local network for peers...");
-        let scanner = LocalScanner::new(self.config.port);
-        let local_peers = scanner.scan_local_peers().await;
-
-        for addr in local_peers {
-            if addr.port() != self.config.port {
-                // 자기 자신 제외
-                peers.push(addr);
-
-                // DHT에 추가
-                if let Some(dht) = &self.dht {
-                    let node = Node {
-                        id: NodeId::from_addr(&addr),
-                        addr,
-                        last_seen: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                    };
-                    dht.add_node(node).await;
-                }
+        self.discover()
+            .await
+            .into_iter()
+            .map(|peer| peer.addr)
+            .collect()
+    }
+
+    /// 등록된 방법을 모두 동시에 실행하고 결과를 합쳐서 순위 순으로 반환
+    pub async fn discover(&self) -> Vec<DiscoveredPeer> {
+        let methods = self.methods.read().unwrap().clone();
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, method) in methods.iter().enumerate() {
+            let method = method.clone();
+            let timeout = self.method_timeout(method.as_ref());
+            tasks.spawn(async move {
+                let started = Instant::now();
+                let result = tokio::time::timeout(timeout, method.discover_peers()).await;
+                (index, result.ok(), started.elapsed())
+            });
+        }
+
+        let mut results: Vec<Vec<SocketAddr>> = vec![Vec::new(); methods.len()];
+        let mut reports: Vec<Option<MethodReport>> = vec![None; methods.len()];
+        while let Some(joined) = tasks.join_next().await {
+            let Ok((index, found, elapsed)) = joined else {
+                continue;
+            };
+            let name = methods[index].name().to_string();
+            let timed_out = found.is_none();
+            if timed_out {
+                let method_name = name.clone();
+                log_network!("⏱️ Discovery method {} timed out", method_name);
             }
+            let found = found.unwrap_or_default();
+            reports[index] = Some(MethodReport {
+                name,
+                found: found.len(),
+                elapsed,
+                timed_out,
+            });
+            results[index] = found;
         }
+        *self.reports.write().await = reports.into_iter().flatten().collect();
 
-        // 2. Bootstrap 노드 연결 (있는 경우에만)
-        if !self.config.bootstrap_nodes.is_empty() {
-            log_network!("🚀 Connecting to bootstrap nodes");
-            let bootstrap_peers = self.bootstrap.connect_bootstrap().await;
-            peers.extend(bootstrap_peers.clone());
-
-            // Bootstrap 노드로부터 피어 목록 받기
-            for addr in &bootstrap_peers {
-                let more_peers = self.bootstrap.exchange_peers(*addr).await;
-                for peer in more_peers {
-                    if !peers.contains(&peer.addr) {
-                        peers.push(peer.addr);
-
-                        // DHT에 추가
-                        if let Some(dht) = &self.dht {
-                            let node = Node {
-                                id: NodeId::from_addr(&peer.addr),
-                                addr: peer.addr,
-                                last_seen: std::time::SystemTime::now()
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_secs(),
-                            };
-                            dht.add_node(node).await;
+        // 주소별로 합치기 (첫 번째 방법 = 가장 우선순위가 높은 방법)
+        let mut merged: Vec<(DiscoveredPeer, usize)> = Vec::new();
+        for (index, addrs) in results.iter().enumerate() {
+            let name = methods[index].name().to_string();
+            for addr in addrs {
+                match merged.iter_mut().find(|(peer, _)| peer.addr == *addr) {
+                    Some((peer, _)) => {
+                        if !peer.found_by.contains(&name) {
+                            peer.found_by.push(name.clone());
                         }
                     }
+                    None => {
+                        self.note_source(*addr, methods[index].source()).await;
+                        if methods[index].source() != PeerSource::Dht {
+                            // 아직 DHT에 없는 피어를 라우팅 테이블에 추가
+                            self.add_unverified_dht_node(*addr).await;
+                        }
+                        merged.push((
+                            DiscoveredPeer {
+                                addr: *addr,
+                                found_by: vec![name.clone()],
+                                known: false,
+                            },
+                            index,
+                        ));
+                    }
                 }
             }
         }
 
-        // 3. DHT를 통한 추가 발견
-        if let Some(dht) = &self.dht {
-            log_network!("🔍 Using DHT for peer discovery");
-            let closest = dht.find_closest_nodes(&self.node_id, 10).await;
-            for node in closest {
-                if !peers.contains(&node.addr) {
-                    peers.push(node.addr);
-                }
+        // 주소록에 저장된 피어 (이전 실행에서 연결에 성공한 주소, 다이얼 우선순위 순)
+        let known = self.address_book.candidates(self.config.max_peers).await;
+        for (rank, addr) in known.iter().enumerate() {
+            match merged.iter_mut().find(|(peer, _)| peer.addr == *addr) {
+                Some((peer, _)) => peer.known = true,
+                None => merged.push((
+                    DiscoveredPeer {
+                        addr: *addr,
+                        found_by: Vec::new(),
+                        known: true,
+                    },
+                    methods.len() + rank,
+                )),
             }
         }
 
-        // 최대 피어 수 제한
-        peers.truncate(self.config.max_peers);
+        // 안정 정렬이므로 같은 순위에서는 먼저 찾은 순서 유지
+        let known_rank = |addr: &SocketAddr| known.iter().position(|k| k == addr);
+        merged.sort_by_key(|(peer, first_method)| {
+            (
+                known_rank(&peer.addr).unwrap_or(usize::MAX),
+                std::cmp::Reverse(peer.found_by.len()),
+                *first_method,
+            )
+        });
+
+        // 순위를 유지하면서 주소 그룹마다 제한된 수만 선택
+        let ranked = merged.into_iter().map(|(peer, _)| peer);
+        let peers =
+            self.config
+                .diversity
+                .select_outbound(ranked, |peer| peer.addr, self.config.max_peers);
 
         let peer_count = peers.len();
         log_network!("✅ Discovered {} peers", peer_count);
@@ -153,27 +468,213 @@ impl Discovery {
         // Bootstrap에 추가
         self.bootstrap.add_peer(peer.clone()).await;
 
-        // DHT에 추가
+        // 주소록에 연결 성공 기록 (이후 재연결은 주소록에 남은 경로를 따름)
+        let source = self
+            .sources
+            .write()
+            .await
+            .remove(&addr)
+            .map_or(PeerSource::Manual, |(source, _)| source);
+        self.address_book.record_success(addr, source).await;
+
+        // DHT에 추가 (전송 계층이 있으면 PING으로 실제 노드 id를 확인)
         if let Some(dht) = &self.dht {
+            if dht.has_transport() {
+                let dht = dht.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = dht.ping(addr).await {
+                        let error = e.to_string();
+                        log_network!("⚠️ DHT ping to {} failed: {}", addr, error);
+                    }
+                });
+            } else {
+                let node = Node {
+                    id: NodeId::from_addr(&addr),
+                    addr,
+                    last_seen: peer.last_seen,
+                };
+                dht.add_node(node).await;
+            }
+        }
+
+        // 로컬 목록에 추가 (최근에 확인한 순서 유지)
+        let mut peers = self.discovered_peers.write().await;
+        match peers.iter_mut().find(|p| p.addr == addr) {
+            Some(existing) => existing.last_seen = peer.last_seen,
+            None => peers.push(peer),
+        }
+        bootstrap::keep_freshest(&mut peers, self.config.max_known_peers);
+    }
+
+    /// 연결된 피어와 정상적으로 주고받음 (Network의 핸드셰이크 완료, Pong 수신)
+    ///
+    /// 연결한 피어 목록, 부트스트랩 피어 목록, 주소록의 마지막 확인 시각을 갱신해서
+    /// 살아 있는 피어가 정리 대상이 되지 않도록 한다.
+    pub async fn record_contact(&self, addr: SocketAddr) {
+        let now = now_secs();
+        {
+            let mut peers = self.discovered_peers.write().await;
+            if let Some(peer) = peers.iter_mut().find(|p| p.addr == addr) {
+                peer.last_seen = now;
+                bootstrap::keep_freshest(&mut peers, self.config.max_known_peers);
+            }
+        }
+        self.bootstrap.touch_peer(addr).await;
+        self.address_book.touch(addr).await;
+    }
+
+    /// 오래 확인되지 않은 피어 정리 (제거한 수 반환)
+    ///
+    /// 연결한 피어와 부트스트랩 피어 목록, 연결하지 않은 주소의 발견 경로는 peer_max_age_secs,
+    /// 역할 태그 정보는 광고 유효 기간이 기준이다. 주소록은 자체 압축 주기를 따른다.
+    pub async fn cleanup_stale_peers(&self) -> usize {
+        let max_age = self.config.peer_max_age_secs;
+        let mut removed =
+            bootstrap::remove_stale(&mut *self.discovered_peers.write().await, max_age);
+        removed += self.bootstrap.cleanup_stale_peers(max_age).await;
+
+        // 발견 경로는 연결하지 않은 주소의 기록이므로 제거한 피어 수에 넣지 않음
+        let noted_after = now_secs().saturating_sub(max_age);
+        self.sources
+            .write()
+            .await
+            .retain(|_, (_, noted_at)| *noted_at >= noted_after);
+
+        let oldest = now_secs().saturating_sub(CAPABILITY_TTL.as_secs());
+        let mut capabilities = self.peer_capabilities.write().await;
+        let before = capabilities.len();
+        capabilities.retain(|_, peer| peer.last_seen >= oldest);
+        removed + (before - capabilities.len())
+    }
+
+    /// 백그라운드 정리 시작 (maintenance_interval_secs마다 cleanup_stale_peers)
+    pub fn spawn_maintenance(self: &Arc<Self>) {
+        let discovery = Arc::downgrade(self);
+        let interval = Duration::from_secs(self.config.maintenance_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(discovery) = discovery.upgrade() else {
+                    break;
+                };
+                let removed = discovery.cleanup_stale_peers().await;
+                if removed > 0 {
+                    log_network!("🧹 Removed {} stale peers", removed);
+                }
+            }
+        });
+    }
+
+    /// 아직 id를 모르는 주소를 DHT에 추가 (주소에서 유도한 임시 id 사용)
+    ///
+    /// 전송 계층이 있으면 연결 후 PING으로 실제 id를 받아 추가하므로 여기서는 건너뜀
+    async fn add_unverified_dht_node(&self, addr: SocketAddr) {
+        if let Some(dht) = &self.dht {
+            if dht.has_transport() {
+                return;
+            }
             let node = Node {
                 id: NodeId::from_addr(&addr),
                 addr,
-                last_seen: peer.last_seen,
+                last_seen: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
             };
             dht.add_node(node).await;
         }
+    }
 
-        // 로컬 목록에 추가
-        let mut peers = self.discovered_peers.write().await;
-        if !peers.iter().any(|p| p.addr == addr) {
-            peers.push(peer);
+    /// DHT 요청을 보낼 전송 계층 연결 (DHT가 꺼져 있으면 무시)
+    pub fn set_dht_transport(&self, transport: Arc<dyn DhtTransport>) {
+        if let Some(dht) = &self.dht {
+            dht.set_transport(transport);
         }
     }
 
+    /// Kademlia 인스턴스 (다른 노드의 DHT 요청에 응답할 때 사용)
+    pub fn dht(&self) -> Option<Arc<Kademlia>> {
+        self.dht.clone()
+    }
+
+    /// 정적 피어 파일 (static_peers_file을 지정했을 때)
+    pub fn static_peers(&self) -> Option<Arc<StaticPeers>> {
+        self.static_peers.clone()
+    }
+
+    /// 정적 피어 파일에서 이 주소에 고정한 node id
+    ///
+    /// 연결할 때 핸드셰이크에서 상대가 이 id의 키로 서명했는지 확인하는 데 쓴다.
+    pub async fn pinned_id(&self, addr: SocketAddr) -> Option<NodeId> {
+        self.static_peers.as_ref()?.pinned_id(&addr).await
+    }
+
+    /// 정적 피어 파일에서 빠진 주소를 잊음 (다음 탐색에서 다시 다이얼하지 않도록)
+    ///
+    /// 정적 피어로 알게 된 경우에만 주소록 항목과 발견 경로를 지운다. 다른 경로로
+    /// 알게 된 주소는 그대로 둔다. 고정 id는 StaticPeers가 다시 읽을 때 이미 빠진다.
+    pub async fn forget_static(&self, addr: SocketAddr) {
+        {
+            let mut sources = self.sources.write().await;
+            if matches!(sources.get(&addr), Some((PeerSource::Static, _))) {
+                sources.remove(&addr);
+            }
+        }
+        let book_entry = self.address_book.get(&addr).await;
+        if book_entry.is_some_and(|entry| entry.source == PeerSource::Static) {
+            self.address_book.remove(&addr).await;
+            self.discovered_peers
+                .write()
+                .await
+                .retain(|p| p.addr != addr);
+        }
+    }
+
+    /// 연결 실패 기록 (주소록에 있는 주소만 반영됨)
+    pub async fn record_failure(&self, addr: SocketAddr) {
+        self.address_book.record_failure(addr).await;
+    }
+
+    /// 부트스트랩 호스트 이름별 최근 DNS 조회 결과 (실패 포함)
+    pub async fn bootstrap_resolutions(&self) -> Vec<Resolution> {
+        self.bootstrap.resolution_report().await
+    }
+
+    pub fn address_book(&self) -> Arc<AddressBook> {
+        self.address_book.clone()
+    }
+
+    /// 주소를 알게 된 경로 기록 (처음 찾은 경로만 유지, 연결 성공 시 주소록에 남음)
+    ///
+    /// 연결하지 않은 주소의 기록은 정리 주기에 peer_max_age_secs가 지나면 지우고, 최대
+    /// MAX_TRACKED_SOURCES개까지만 유지한다.
+    pub async fn note_source(&self, addr: SocketAddr, source: PeerSource) {
+        let mut sources = self.sources.write().await;
+        if let Some((_, noted_at)) = sources.get_mut(&addr) {
+            *noted_at = now_secs();
+            return;
+        }
+        if sources.len() >= MAX_TRACKED_SOURCES {
+            let oldest = sources
+                .iter()
+                .min_by_key(|(_, (_, noted_at))| *noted_at)
+                .map(|(addr, _)| *addr);
+            if let Some(oldest) = oldest {
+                sources.remove(&oldest);
+            }
+        }
+        sources.insert(addr, (source, now_secs()));
+    }
+
+    /// 연결한 피어 (최근에 확인한 순서)
     pub async fn get_peers(&self) -> Vec<PeerInfo> {
         self.discovered_peers.read().await.clone()
     }
 
+    /// target에 가장 가까운 노드 주소 (DHT 전송 계층이 있으면 네트워크 전체에서 반복 조회)
     pub async fn find_node(&self, target: NodeId) -> Vec<SocketAddr> {
         if let Some(dht) = &self.dht {
             let nodes = dht.lookup(target).await;
@@ -189,7 +690,374 @@ impl Discovery {
         }
     }
 
+    /// DHT에 레코드 발행 (name은 레코드 키로 해시됨)
+    ///
+    /// key에 가장 가까운 노드들에 복제되고, ttl이 지나면 만료된다. 계속 유지하려면
+    /// ttl이 지나기 전에 다시 발행해야 한다. 복제에 성공한 노드 수를 반환.
+    pub async fn put_record(
+        &self,
+        name: &[u8],
+        value: Vec<u8>,
+        ttl: Duration,
+    ) -> Result<usize, DhtError> {
+        let dht = self.dht.as_ref().ok_or(DhtError::Disabled)?;
+        dht.put(record_key(name), value, ttl).await
+    }
+
+    /// DHT에서 레코드 조회 (발행자별 최신 레코드, 서명 검증됨)
+    pub async fn get_records(&self, name: &[u8]) -> Result<Vec<DhtRecord>, DhtError> {
+        let dht = self.dht.as_ref().ok_or(DhtError::Disabled)?;
+        Ok(dht.get(record_key(name)).await)
+    }
+
+    /// 이 노드가 광고하는 역할 태그
+    pub fn capabilities(&self) -> &[String] {
+        &self.config.capabilities
+    }
+
+    /// 역할 태그마다 DHT에 광고 레코드 발행 (복제에 성공한 노드 수의 합 반환)
+    pub async fn advertise_capabilities(&self) -> Result<usize, DhtError> {
+        if self.config.capabilities.is_empty() {
+            return Ok(0);
+        }
+        let dht = self.dht.as_ref().ok_or(DhtError::Disabled)?;
+        let advert = CapabilityAdvert {
+            capabilities: self.config.capabilities.clone(),
+        }
+        .encode();
+
+        let mut replicated = 0;
+        for tag in &self.config.capabilities {
+            replicated += dht
+                .put(capability_key(tag), advert.clone(), CAPABILITY_TTL)
+                .await?;
+        }
+        Ok(replicated)
+    }
+
+    /// 역할 태그를 주기적으로 다시 광고 (태그가 없거나 DHT가 꺼져 있으면 아무것도 하지 않음)
+    ///
+    /// 아직 복제할 노드가 없으면 30초 뒤 다시 시도하고, 성공하면 레코드가 만료되기 전에
+    /// CAPABILITY_REPUBLISH_INTERVAL마다 다시 발행한다.
+    pub fn spawn_capability_advertiser(self: &Arc<Self>) {
+        if self.config.capabilities.is_empty() || self.dht.is_none() {
+            return;
+        }
+        let discovery = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                let Some(discovery) = discovery.upgrade() else {
+                    break;
+                };
+                let delay = match discovery.advertise_capabilities().await {
+                    Ok(0) => Duration::from_secs(30),
+                    Ok(replicated) => {
+                        let tags = discovery.config.capabilities.join(",");
+                        log_network!(
+                            "🏷️ Advertised capabilities [{}] to {} nodes",
+                            tags,
+                            replicated
+                        );
+                        CAPABILITY_REPUBLISH_INTERVAL
+                    }
+                    Err(e) => {
+                        let error = e.to_string();
+                        log_network!("⚠️ Failed to advertise capabilities: {}", error);
+                        Duration::from_secs(30)
+                    }
+                };
+                drop(discovery);
+                tokio::time::sleep(delay).await;
+            }
+        });
+    }
+
+    /// 다른 경로(앱 프로토콜 등)로 알게 된 피어의 역할 태그 등록
+    pub async fn set_peer_capabilities(
+        &self,
+        addr: SocketAddr,
+        node_id: Option<NodeId>,
+        capabilities: Vec<String>,
+    ) {
+        self.remember_capabilities(addr, node_id, capabilities, PeerSource::Manual)
+            .await;
+    }
+
+    /// 주소에 대해 알고 있는 역할 태그
+    pub async fn peer_capabilities(&self, addr: SocketAddr) -> Option<CapablePeer> {
+        self.peer_capabilities.read().await.get(&addr).cloned()
+    }
+
+    /// capability 태그를 가진 피어를 최대 count개 찾기 (최근에 확인한 피어 우선)
+    ///
+    /// mDNS와 직접 등록한 정보로 먼저 찾고, 모자라면 DHT에서 태그 광고 레코드를 조회한 뒤
+    /// 발행자 id로 주소를 찾는다. 찾은 정보는 다음 조회를 위해 보관한다.
+    pub async fn find_peers_with_capability(
+        &self,
+        capability: &str,
+        count: usize,
+    ) -> Vec<CapablePeer> {
+        if let Some(mdns) = &self.mdns {
+            for peer in mdns.peers().await {
+                if let Some(addr) = peer.preferred_addr() {
+                    self.remember_capabilities(
+                        addr,
+                        Some(peer.node_id),
+                        peer.capabilities,
+                        PeerSource::Mdns,
+                    )
+                    .await;
+                }
+            }
+        }
+
+        let mut found = self.known_with_capability(capability).await;
+        if found.len() < count {
+            if let Some(dht) = &self.dht {
+                self.query_capability_adverts(dht, capability, &found).await;
+                found = self.known_with_capability(capability).await;
+            }
+        }
+
+        found.sort_by_key(|peer| std::cmp::Reverse(peer.last_seen));
+        found.truncate(count);
+        found
+    }
+
+    // DHT 광고 레코드의 발행자 주소를 찾아서 보관 (이미 아는 노드는 건너뜀)
+    async fn query_capability_adverts(
+        &self,
+        dht: &Arc<Kademlia>,
+        capability: &str,
+        known: &[CapablePeer],
+    ) {
+        let records = dht.get(capability_key(capability)).await;
+
+        let mut resolves = tokio::task::JoinSet::new();
+        for record in records {
+            if record.publisher == self.node_id
+                || known.iter().any(|p| p.node_id == Some(record.publisher))
+            {
+                continue;
+            }
+            let Some(advert) = CapabilityAdvert::decode(&record.value) else {
+                continue;
+            };
+            if !advert.capabilities.iter().any(|c| c == capability) {
+                continue;
+            }
+            let dht = dht.clone();
+            resolves.spawn(async move {
+                let node = dht.resolve(record.publisher).await;
+                (node, advert.capabilities)
+            });
+        }
+
+        while let Some(joined) = resolves.join_next().await {
+            let Ok((Some(node), capabilities)) = joined else {
+                continue;
+            };
+            self.remember_capabilities(node.addr, Some(node.id), capabilities, PeerSource::Dht)
+                .await;
+        }
+    }
+
+    // 유효 기간 안에 확인한 피어 중 태그를 가진 피어
+    async fn known_with_capability(&self, capability: &str) -> Vec<CapablePeer> {
+        let oldest = now_secs().saturating_sub(CAPABILITY_TTL.as_secs());
+        self.peer_capabilities
+            .read()
+            .await
+            .values()
+            .filter(|peer| peer.last_seen >= oldest && peer.has(capability))
+            .filter(|peer| peer.node_id != Some(self.node_id))
+            .cloned()
+            .collect()
+    }
+
+    async fn remember_capabilities(
+        &self,
+        addr: SocketAddr,
+        node_id: Option<NodeId>,
+        capabilities: Vec<String>,
+        source: PeerSource,
+    ) {
+        let mut peers = self.peer_capabilities.write().await;
+        // 같은 노드가 주소를 바꿨으면 이전 주소의 정보는 버림
+        if let Some(id) = node_id {
+            peers.retain(|other, peer| *other == addr || peer.node_id != Some(id));
+        }
+        peers.insert(
+            addr,
+            CapablePeer {
+                addr,
+                node_id,
+                capabilities,
+                source,
+                last_seen: now_secs(),
+            },
+        );
+    }
+
     pub fn get_node_id(&self) -> NodeId {
         self.node_id
     }
 }
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dht_sim::SimulatedDhtNetwork;
+    use crate::mdns::MdnsPeer;
+
+    fn discovery() -> Discovery {
+        Discovery::new(DiscoveryConfig {
+            enable_dht: false,
+            enable_mdns: false,
+            enable_scan: false,
+            ..DiscoveryConfig::default()
+        })
+    }
+
+    fn addr(i: usize) -> SocketAddr {
+        SocketAddr::from(([10, (i >> 16) as u8, (i >> 8) as u8, i as u8], 42000))
+    }
+
+    #[tokio::test]
+    async fn sources_are_capped() {
+        let discovery = discovery();
+        for i in 0..MAX_TRACKED_SOURCES + 100 {
+            discovery.note_source(addr(i), PeerSource::Scan).await;
+        }
+        assert_eq!(discovery.sources.read().await.len(), MAX_TRACKED_SOURCES);
+    }
+
+    #[tokio::test]
+    async fn stale_sources_are_pruned_and_connected_sources_recorded() {
+        let discovery = discovery();
+        discovery.note_source(addr(1), PeerSource::Mdns).await;
+        discovery.note_source(addr(2), PeerSource::Scan).await;
+        discovery.sources.write().await.get_mut(&addr(2)).unwrap().1 = 0;
+
+        discovery.cleanup_stale_peers().await;
+        let sources = discovery.sources.read().await.clone();
+        assert!(sources.contains_key(&addr(1)));
+        assert!(!sources.contains_key(&addr(2)));
+
+        // 연결에 성공하면 경로는 주소록으로 옮겨짐
+        discovery.add_peer(addr(1)).await;
+        assert!(discovery.sources.read().await.is_empty());
+        let entry = discovery.address_book().get(&addr(1)).await.unwrap();
+        assert_eq!(entry.source, PeerSource::Mdns);
+    }
+
+    #[tokio::test]
+    async fn removed_static_peer_is_not_rediscovered() {
+        let path = std::env::temp_dir().join(format!(
+            "guild_discovery_static_forget_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, "10.9.0.1:42000\n10.9.0.2:42000\n").unwrap();
+        let discovery = Discovery::new(DiscoveryConfig {
+            enable_dht: false,
+            enable_mdns: false,
+            enable_scan: false,
+            static_peers_file: Some(path.clone()),
+            ..DiscoveryConfig::default()
+        });
+        let static_peers = discovery.static_peers().unwrap();
+        static_peers.reload().await;
+
+        let removed: SocketAddr = "10.9.0.1:42000".parse().unwrap();
+        let kept: SocketAddr = "10.9.0.2:42000".parse().unwrap();
+        let found = discovery.start().await;
+        assert!(found.contains(&removed) && found.contains(&kept));
+        discovery.add_peer(removed).await;
+        discovery.add_peer(kept).await;
+
+        // 파일에서 한 줄을 지우면 주소록에서도 빠져서 다시 발견되지 않음
+        std::fs::write(&path, "10.9.0.2:42000\n").unwrap();
+        let changes = static_peers.reload().await;
+        assert_eq!(changes.len(), 1);
+        discovery.forget_static(removed).await;
+
+        let found = discovery.start().await;
+        assert!(!found.contains(&removed));
+        assert!(found.contains(&kept));
+        assert!(discovery.address_book().get(&removed).await.is_none());
+        assert!(discovery
+            .get_peers()
+            .await
+            .iter()
+            .all(|p| p.addr != removed));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn capability_search_finds_cached_mdns_peers() {
+        let mut discovery = discovery();
+        let mdns = MdnsDiscovery::new(discovery.get_node_id(), DEFAULT_NETWORK_ID).unwrap();
+        let validator = NodeIdentity::generate().node_id();
+        for (name, id, tag, i) in [
+            ("validator", validator, "validator", 1),
+            ("relay", NodeIdentity::generate().node_id(), "relay", 2),
+        ] {
+            let peer = MdnsPeer {
+                node_id: id,
+                addrs: vec![addr(i)],
+                capabilities: vec![tag.to_string()],
+            };
+            mdns.cache_peer(name, peer).await;
+        }
+        discovery.mdns = Some(Arc::new(mdns));
+
+        let found = discovery.find_peers_with_capability("validator", 5).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].addr, addr(1));
+        assert_eq!(found[0].node_id, Some(validator));
+        assert_eq!(found[0].source, PeerSource::Mdns);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn capability_search_finds_dht_adverts() {
+        guild_logger::init_logger(true);
+        let network = SimulatedDhtNetwork::new();
+        network.set_latency(Duration::from_millis(1));
+        let (bootstrap, _bootstrap_node) = network.add_node(DhtConfig::default());
+        let with_dht = |capabilities: Vec<String>| {
+            let discovery = Discovery::new(DiscoveryConfig {
+                enable_mdns: false,
+                enable_scan: false,
+                capabilities,
+                ..DiscoveryConfig::default()
+            });
+            let (addr, _) = network.attach(discovery.dht().unwrap());
+            (addr, discovery)
+        };
+
+        // validator는 부트스트랩 노드에 참여한 뒤 태그를 광고
+        let (validator_addr, validator) = with_dht(vec!["validator".to_string()]);
+        validator.dht().unwrap().ping(bootstrap).await.unwrap();
+        assert!(validator.advertise_capabilities().await.unwrap() > 0);
+
+        let (_, searcher) = with_dht(Vec::new());
+        searcher.dht().unwrap().ping(bootstrap).await.unwrap();
+        let found = searcher.find_peers_with_capability("validator", 1).await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].addr, validator_addr);
+        assert_eq!(found[0].node_id, Some(validator.get_node_id()));
+        assert_eq!(found[0].source, PeerSource::Dht);
+        assert!(searcher
+            .find_peers_with_capability("relay", 1)
+            .await
+            .is_empty());
+    }
+}
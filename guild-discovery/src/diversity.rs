@@ -0,0 +1,268 @@
+// 주소 다양성 규칙 - Sybil/eclipse 공격 완화
+//
+// 공격자는 한 대역에서 많은 노드를 띄우기는 쉬워도 서로 다른 대역을 많이 확보하기는 어렵다.
+// 그래서 주소를 그룹(IPv4 /24, IPv6 /48)으로 묶고, DHT 버킷과 주소록에 한 그룹이 차지할 수
+// 있는 자리 수, DHT 키 하나에 한 그룹이 STORE로 넣을 수 있는 발행자 수, 한 번의 탐색에서
+// 고르는 연결 대상 수를 그룹마다 제한한다. 그룹이 가득 차면
+// 이미 있는 (오래 살아 있는) 노드를 유지하고 새 노드를 거절한다.
+//
+// 루프백/사설/링크 로컬 주소는 기본적으로 제한하지 않는다. LAN이나 한 머신에서 여러 노드를
+// 띄우는 경우 모든 노드가 같은 그룹에 들어가기 때문.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// DHT 버킷 하나에 들어갈 수 있는 같은 그룹 노드 수
+pub const DEFAULT_MAX_PER_GROUP_PER_BUCKET: usize = 2;
+/// 주소록(부트스트랩 피어 목록 포함)에 보관하는 같은 그룹 주소 수
+pub const DEFAULT_MAX_PER_GROUP_IN_ADDRESS_BOOK: usize = 8;
+/// 탐색 한 번에 연결 대상으로 고르는 같은 그룹 피어 수
+pub const DEFAULT_MAX_OUTBOUND_PER_GROUP: usize = 2;
+/// DHT 키 하나에 같은 그룹 주소가 STORE로 넣을 수 있는 발행자 수
+pub const DEFAULT_MAX_STORES_PER_GROUP_PER_KEY: usize = 4;
+
+/// 주소 그룹 (같은 그룹의 주소는 같은 운영자가 통제할 가능성이 높다고 봄)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressGroup {
+    /// IPv4 /24
+    V4([u8; 3]),
+    /// IPv6 /48
+    V6([u8; 6]),
+}
+
+/// 주소가 속한 그룹 (IPv4-mapped IPv6 주소는 IPv4로 취급)
+///
+/// ```
+/// use guild_discovery::{address_group, AddressGroup};
+///
+/// let a = address_group("203.0.113.7".parse().unwrap());
+/// let b = address_group("203.0.113.200".parse().unwrap());
+/// let c = address_group("203.0.114.7".parse().unwrap());
+/// assert_eq!(a, b);
+/// assert_ne!(a, c);
+/// assert_eq!(a, AddressGroup::V4([203, 0, 113]));
+///
+/// let mapped = address_group("::ffff:203.0.113.9".parse().unwrap());
+/// assert_eq!(mapped, a);
+///
+/// let v6 = address_group("2001:db8:1:2::1".parse().unwrap());
+/// assert_eq!(v6, address_group("2001:db8:1:ffff::1".parse().unwrap()));
+/// assert_ne!(v6, address_group("2001:db8:2::1".parse().unwrap()));
+/// ```
+pub fn address_group(ip: IpAddr) -> AddressGroup {
+    match ip {
+        IpAddr::V4(v4) => v4_group(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => v4_group(v4),
+            None => {
+                let octets = v6.octets();
+                let mut prefix = [0u8; 6];
+                prefix.copy_from_slice(&octets[..6]);
+                AddressGroup::V6(prefix)
+            }
+        },
+    }
+}
+
+fn v4_group(ip: Ipv4Addr) -> AddressGroup {
+    let [a, b, c, _] = ip.octets();
+    AddressGroup::V4([a, b, c])
+}
+
+/// 루프백, 사설, 링크 로컬, 미지정 주소인지
+pub fn is_local_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_local_v4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_local_v4(v4),
+            None => is_local_v6(v6),
+        },
+    }
+}
+
+fn is_local_v4(ip: Ipv4Addr) -> bool {
+    ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()
+}
+
+fn is_local_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || (first & 0xfe00) == 0xfc00 // 고유 로컬 fc00::/7
+        || (first & 0xffc0) == 0xfe80 // 링크 로컬 fe80::/10
+}
+
+/// 다양성 규칙 설정
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiversityConfig {
+    /// DHT 버킷(교체 후보 포함) 하나에 들어갈 수 있는 같은 그룹 노드 수
+    pub max_per_group_per_bucket: usize,
+    /// 주소록에 보관하는 같은 그룹 주소 수
+    pub max_per_group_in_address_book: usize,
+    /// 탐색 한 번에 연결 대상으로 고르는 같은 그룹 피어 수
+    pub max_outbound_per_group: usize,
+    /// DHT 키 하나에 같은 그룹 주소가 STORE로 넣을 수 있는 발행자 수
+    pub max_stores_per_group_per_key: usize,
+    /// 루프백/사설 주소에도 제한 적용 (기본값 false)
+    pub limit_local_addresses: bool,
+}
+
+impl Default for DiversityConfig {
+    fn default() -> Self {
+        Self {
+            max_per_group_per_bucket: DEFAULT_MAX_PER_GROUP_PER_BUCKET,
+            max_per_group_in_address_book: DEFAULT_MAX_PER_GROUP_IN_ADDRESS_BOOK,
+            max_outbound_per_group: DEFAULT_MAX_OUTBOUND_PER_GROUP,
+            max_stores_per_group_per_key: DEFAULT_MAX_STORES_PER_GROUP_PER_KEY,
+            limit_local_addresses: false,
+        }
+    }
+}
+
+impl DiversityConfig {
+    /// 모든 제한 해제
+    pub fn unlimited() -> Self {
+        Self {
+            max_per_group_per_bucket: usize::MAX,
+            max_per_group_in_address_book: usize::MAX,
+            max_outbound_per_group: usize::MAX,
+            max_stores_per_group_per_key: usize::MAX,
+            limit_local_addresses: false,
+        }
+    }
+
+    /// 제한을 적용할 그룹 (제한하지 않는 주소면 None)
+    pub fn group(&self, addr: &SocketAddr) -> Option<AddressGroup> {
+        if !self.limit_local_addresses && is_local_address(addr.ip()) {
+            return None;
+        }
+        Some(address_group(addr.ip()))
+    }
+
+    /// `existing` 중 `addr`와 같은 그룹이 이미 `max`개 이상인지
+    pub fn group_full<'a>(
+        &self,
+        addr: &SocketAddr,
+        existing: impl IntoIterator<Item = &'a SocketAddr>,
+        max: usize,
+    ) -> bool {
+        let Some(group) = self.group(addr) else {
+            return false;
+        };
+        existing
+            .into_iter()
+            .filter(|other| *other != addr && self.group(other) == Some(group))
+            .count()
+            >= max
+    }
+
+    /// 순위대로 정렬된 피어에서 그룹마다 최대 `max_outbound_per_group`개씩, 최대 `limit`개 선택
+    ///
+    /// 앞쪽(순위가 높은) 피어를 우선하고, 그룹 제한에 걸린 피어는 건너뛴다.
+    ///
+    /// ```
+    /// use guild_discovery::DiversityConfig;
+    /// use std::net::SocketAddr;
+    ///
+    /// let ranked: Vec<SocketAddr> = [
+    ///     "198.51.100.1:42000",
+    ///     "198.51.100.2:42000",
+    ///     "198.51.100.3:42000",
+    ///     "203.0.113.1:42000",
+    ///     "192.168.1.10:42000",
+    ///     "192.168.1.11:42000",
+    ///     "192.168.1.12:42000",
+    /// ]
+    /// .iter()
+    /// .map(|s| s.parse().unwrap())
+    /// .collect();
+    ///
+    /// let config = DiversityConfig::default();
+    /// let picked = config.select_outbound(ranked.clone(), |addr| *addr, 10);
+    /// // 198.51.100.0/24는 2개까지만, 사설 주소는 제한 없음
+    /// assert_eq!(picked.len(), 6);
+    /// assert!(!picked.contains(&ranked[2]));
+    ///
+    /// let picked = config.select_outbound(ranked.clone(), |addr| *addr, 3);
+    /// assert_eq!(picked, vec![ranked[0], ranked[1], ranked[3]]);
+    /// ```
+    pub fn select_outbound<T>(
+        &self,
+        ranked: impl IntoIterator<Item = T>,
+        addr_of: impl Fn(&T) -> SocketAddr,
+        limit: usize,
+    ) -> Vec<T> {
+        let mut per_group: HashMap<AddressGroup, usize> = HashMap::new();
+        let mut selected = Vec::new();
+        for item in ranked {
+            if selected.len() >= limit {
+                break;
+            }
+            if let Some(group) = self.group(&addr_of(&item)) {
+                let count = per_group.entry(group).or_insert(0);
+                if *count >= self.max_outbound_per_group {
+                    continue;
+                }
+                *count += 1;
+            }
+            selected.push(item);
+        }
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs(list: &[&str]) -> Vec<SocketAddr> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn select_outbound_caps_v4_and_v6_groups() {
+        let ranked = addrs(&[
+            "198.51.100.1:42000",
+            "[2001:db8:1:1::1]:42000",
+            "198.51.100.2:42000",
+            "[2001:db8:1:2::1]:42000",
+            "198.51.100.3:42000",
+            "[2001:db8:1:3::1]:42000",
+            "[::ffff:198.51.100.4]:42000",
+            "198.51.101.1:42000",
+            "[2001:db8:2::1]:42000",
+        ]);
+        let picked = DiversityConfig::default().select_outbound(ranked.clone(), |a| *a, 100);
+        assert_eq!(
+            picked,
+            vec![ranked[0], ranked[1], ranked[2], ranked[3], ranked[7], ranked[8]]
+        );
+    }
+
+    #[test]
+    fn select_outbound_limits_local_addresses_when_asked() {
+        let ranked = addrs(&["10.0.0.1:42000", "10.0.0.2:42000", "10.0.0.3:42000"]);
+        let default = DiversityConfig::default();
+        assert_eq!(
+            default.select_outbound(ranked.clone(), |a| *a, 100).len(),
+            3
+        );
+
+        let strict = DiversityConfig {
+            limit_local_addresses: true,
+            ..DiversityConfig::default()
+        };
+        assert_eq!(strict.select_outbound(ranked.clone(), |a| *a, 100).len(), 2);
+    }
+
+    #[test]
+    fn unlimited_config_keeps_every_peer() {
+        let ranked = addrs(&[
+            "198.51.100.1:42000",
+            "198.51.100.2:42000",
+            "198.51.100.3:42000",
+        ]);
+        let picked = DiversityConfig::unlimited().select_outbound(ranked, |a| *a, 100);
+        assert_eq!(picked.len(), 3);
+    }
+}
@@ -0,0 +1,142 @@
+// 노드 신원 - ed25519 키 쌍과 거기서 유도한 노드 id
+//
+// 노드 id는 공개키의 blake3 해시이므로, DHT 레코드에 담긴 공개키와 서명만으로
+// 레코드가 그 id의 노드가 발행한 것인지 확인할 수 있다.
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use std::path::Path;
+
+use crate::dht::NodeId;
+use crate::log_discovery;
+
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+
+#[derive(Clone)]
+pub struct NodeIdentity {
+    signing_key: SigningKey,
+}
+
+impl std::fmt::Debug for NodeIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // 비밀키는 출력하지 않음
+        f.debug_struct("NodeIdentity")
+            .field("node_id", &self.node_id().to_hex())
+            .finish()
+    }
+}
+
+impl NodeIdentity {
+    pub fn generate() -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut rand::rngs::OsRng),
+        }
+    }
+
+    pub fn from_secret_bytes(secret: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(secret),
+        }
+    }
+
+    /// 파일에서 비밀키를 읽고, 없으면 새로 만들어 저장 (재시작해도 같은 노드 id 유지)
+    pub async fn load_or_generate(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        match tokio::fs::read(path).await {
+            Ok(bytes) => {
+                let secret: [u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid node key file")
+                })?;
+                Ok(Self::from_secret_bytes(&secret))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let identity = Self::generate();
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                write_secret(path, &identity.signing_key.to_bytes()).await?;
+
+                let node_id = identity.node_id().to_hex();
+                log_discovery!("🔑 Generated node key {}", node_id);
+                Ok(identity)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    pub fn node_id(&self) -> NodeId {
+        node_id_for_key(&self.public_key())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.signing_key.sign(message).to_bytes().to_vec()
+    }
+}
+
+/// 비밀키 파일 쓰기 - 처음부터 소유자만 읽을 수 있는(0600) 임시 파일에 쓰고 교체
+///
+/// 쓰는 도중 종료되어도 반쯤 쓰인 키 파일이 남지 않는다.
+async fn write_secret(path: &Path, secret: &[u8]) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let tmp = path.with_extension("tmp");
+    // 이전 실행이 남긴 임시 파일은 권한을 알 수 없으므로 지우고 새로 만듦
+    match tokio::fs::remove_file(&tmp).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&tmp).await?;
+    file.write_all(secret).await?;
+    file.sync_all().await?;
+    drop(file);
+    tokio::fs::rename(&tmp, path).await
+}
+
+/// 공개키에서 노드 id 유도
+pub fn node_id_for_key(public_key: &[u8; PUBLIC_KEY_LENGTH]) -> NodeId {
+    NodeId(*blake3::hash(public_key).as_bytes())
+}
+
+/// 서명 검증 (키나 서명 형식이 잘못되어도 false)
+pub fn verify(public_key: &[u8; PUBLIC_KEY_LENGTH], message: &[u8], signature: &[u8]) -> bool {
+    let Ok(key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+    key.verify(message, &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn generated_key_is_owner_only_and_reloaded() {
+        let dir = std::env::temp_dir().join(format!("node_key_{}", std::process::id()));
+        let path = dir.join("node_key");
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+
+        let generated = NodeIdentity::load_or_generate(&path).await.unwrap();
+        let loaded = NodeIdentity::load_or_generate(&path).await.unwrap();
+        assert_eq!(loaded.node_id(), generated.node_id());
+        assert!(!path.with_extension("tmp").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -1,13 +1,36 @@
 // Guild Discovery - P2P 노드 탐색 라이브러리
+pub mod address_book;
 pub mod bootstrap;
+pub mod capabilities;
 pub mod dht;
+pub mod dht_rpc;
+#[cfg(any(test, feature = "test-util"))]
+pub mod dht_sim;
+pub mod dht_store;
 pub mod discovery;
+pub mod diversity;
+pub mod identity;
 pub mod local_scan;
+pub mod mdns;
+pub mod static_peers;
 
-pub use bootstrap::{Bootstrap, PeerInfo};
-pub use dht::{Kademlia, NodeId};
-pub use discovery::{Discovery, DiscoveryConfig};
-pub use local_scan::{LocalScanner, DEFAULT_PORT, DEFAULT_PORT_RANGE};
+pub use address_book::{AddressBook, AddressEntry, PeerSource};
+pub use bootstrap::{Bootstrap, BootstrapEntry, PeerInfo, Resolution};
+pub use capabilities::{
+    capability_key, validate_capabilities, validate_capability, CapabilityAdvert, CapablePeer,
+    CAPABILITY_ARCHIVE, CAPABILITY_MEMBER, CAPABILITY_RELAY, CAPABILITY_VALIDATOR,
+};
+pub use dht::{DhtConfig, Kademlia, Node, NodeId};
+pub use dht_rpc::{DhtBody, DhtEnvelope, DhtError, DhtRequest, DhtResponse, DhtTransport, DHT_PROTOCOL};
+#[cfg(any(test, feature = "test-util"))]
+pub use dht_sim::SimulatedDhtNetwork;
+pub use dht_store::{record_key, DhtRecord, RecordError};
+pub use discovery::{DiscoveredPeer, Discovery, DiscoveryConfig, DiscoveryMethod, MethodReport, DEFAULT_NETWORK_ID, MAX_NETWORK_ID_LEN, validate_network_id};
+pub use diversity::{address_group, is_local_address, AddressGroup, DiversityConfig};
+pub use identity::NodeIdentity;
+pub use mdns::{MdnsDiscovery, MdnsPeer};
+pub use local_scan::{parse_cidr_hosts, LocalScanner, ScanConfig, ScannedPeer, DEFAULT_BEACON_PORT, DEFAULT_PORT, DEFAULT_PORT_RANGE, MAX_PROBES_PER_SEC};
+pub use static_peers::{ResolvedStaticPeer, StaticPeer, StaticPeerChange, StaticPeers, DEFAULT_STATIC_PEERS_POLL_INTERVAL};
 
 // Re-export logging macros
 pub use guild_logger::{
@@ -1,59 +1,522 @@
 // 로컬 네트워크 스캔을 통한 피어 발견
-use std::net::SocketAddr;
+//
+// 각 노드는 UDP 비컨 포트(기본 41999)에서 응답기를 돌린다. 스캔하는 쪽은 브로드캐스트와
+// 멀티캐스트로 probe를 보내고, 설정에 따라 지정한 CIDR 대역의 주소/포트에도 직접 probe를
+// 보낸다. 같은 network id를 가진 노드만 자신의 노드 id와 QUIC 포트로 응답하며, 스캔 결과에는
+// 실제로 응답한 노드만 들어간다.
+//
+// 비컨 패킷 (정수는 big-endian):
+//
+//   magic b"GHBC" (4) | version u8 | kind u8 (1 = probe, 2 = reply) | nonce u64
+//   | node_id (32) | port u16 | network_id 길이 u8 | network_id
+//
+// reply는 probe의 nonce를 그대로 돌려주므로 위조된 응답이나 다른 스캔의 응답은 걸러진다.
+// 한 호스트에서 여러 노드가 비컨 포트를 함께 쓸 수 있도록 SO_REUSEADDR/SO_REUSEPORT로
+// 바인딩한다 (브로드캐스트/멀티캐스트는 모든 노드가 받지만, 유니캐스트 probe는 그중 하나만 받음).
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+use crate::address_book::PeerSource;
+use crate::dht::{NodeId, NODE_ID_LENGTH};
+use crate::discovery::DiscoveryMethod;
+use crate::log_discovery;
 
 // Guild Home 기본 포트 설정
 pub const DEFAULT_PORT: u16 = 42000;
 pub const DEFAULT_PORT_RANGE: u16 = 100; // 42000-42100 범위
 
+/// 비컨 응답기 기본 포트
+pub const DEFAULT_BEACON_PORT: u16 = 41999;
+/// 비컨 멀티캐스트 그룹 (조직 로컬 범위)
+pub const DEFAULT_BEACON_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 42, 99);
+
+const BEACON_MAGIC: [u8; 4] = *b"GHBC";
+const BEACON_VERSION: u8 = 1;
+const KIND_PROBE: u8 = 1;
+const KIND_REPLY: u8 = 2;
+const MAX_BEACON_SIZE: usize = 512;
+
+/// CIDR 스캔에서 허용하는 최대 호스트 수 (/20)
+pub const MAX_PROBE_HOSTS: usize = 4096;
+/// 스캔 한 번에 보내는 최대 probe 수 (대역 × 포트 범위가 이보다 크면 앞쪽부터 자름)
+pub const MAX_PROBES_PER_SCAN: usize = 16384;
+/// max_probes_per_sec 상한 (probe 간격이 0이 되지 않도록 LocalScanner::new에서 이 범위로 맞춤)
+pub const MAX_PROBES_PER_SEC: u32 = 10_000;
+/// 응답기가 IP 하나에 초당 보내는 최대 응답 수 (증폭/스팸 방지)
+const MAX_REPLIES_PER_SEC_PER_IP: u32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanConfig {
+    /// 비컨 응답기가 바인딩하고 probe를 보낼 포트
+    pub beacon_port: u16,
+    pub multicast_group: Ipv4Addr,
+    /// 마지막 probe를 보낸 뒤 응답을 기다리는 시간
+    pub reply_window: Duration,
+    /// 직접 probe할 IPv4 대역 (예: "192.168.1.0/24", 비어 있으면 브로드캐스트/멀티캐스트만)
+    pub probe_cidrs: Vec<String>,
+    /// CIDR 대역의 각 주소에서 probe할 비컨 포트 범위
+    pub probe_ports: RangeInclusive<u16>,
+    /// 초당 보내는 최대 probe 수 (1..=MAX_PROBES_PER_SEC)
+    pub max_probes_per_sec: u32,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            beacon_port: DEFAULT_BEACON_PORT,
+            multicast_group: DEFAULT_BEACON_GROUP,
+            reply_window: Duration::from_millis(500),
+            probe_cidrs: Vec::new(),
+            probe_ports: DEFAULT_BEACON_PORT..=DEFAULT_BEACON_PORT,
+            max_probes_per_sec: 200,
+        }
+    }
+}
+
+/// 스캔에 응답한 피어
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedPeer {
+    pub node_id: NodeId,
+    /// 응답을 보낸 IP와 응답에 담긴 QUIC 포트
+    pub addr: SocketAddr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Beacon {
+    kind: u8,
+    nonce: u64,
+    node_id: NodeId,
+    port: u16,
+    network_id: String,
+}
+
+impl Beacon {
+    fn encode(&self) -> Vec<u8> {
+        let network = &self.network_id.as_bytes()[..self.network_id.len().min(u8::MAX as usize)];
+        let mut buf = Vec::with_capacity(49 + network.len());
+        buf.extend_from_slice(&BEACON_MAGIC);
+        buf.push(BEACON_VERSION);
+        buf.push(self.kind);
+        buf.extend_from_slice(&self.nonce.to_be_bytes());
+        buf.extend_from_slice(&self.node_id.0);
+        buf.extend_from_slice(&self.port.to_be_bytes());
+        buf.push(network.len() as u8);
+        buf.extend_from_slice(network);
+        buf
+    }
+
+    fn decode(buf: &[u8]) -> Option<Self> {
+        const HEADER: usize = 4 + 1 + 1 + 8 + NODE_ID_LENGTH + 2 + 1;
+        if buf.len() < HEADER || buf[..4] != BEACON_MAGIC || buf[4] != BEACON_VERSION {
+            return None;
+        }
+        let kind = buf[5];
+        let nonce = u64::from_be_bytes(buf[6..14].try_into().ok()?);
+        let node_id = NodeId(buf[14..46].try_into().ok()?);
+        let port = u16::from_be_bytes(buf[46..48].try_into().ok()?);
+        let network_len = buf[48] as usize;
+        let network_id = std::str::from_utf8(buf.get(HEADER..HEADER + network_len)?).ok()?;
+        Some(Self {
+            kind,
+            nonce,
+            node_id,
+            port,
+            network_id: network_id.to_string(),
+        })
+    }
+}
+
 pub struct LocalScanner {
-    base_port: u16,
+    node_id: NodeId,
+    network_id: String,
+    config: ScanConfig,
+    // 응답에 담을 QUIC 포트 (announce로 갱신)
+    port: Arc<AtomicU16>,
+    responder: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl LocalScanner {
-    pub fn new(current_port: u16) -> Self {
+    pub fn new(node_id: NodeId, network_id: &str, port: u16, mut config: ScanConfig) -> Self {
+        config.max_probes_per_sec = config.max_probes_per_sec.clamp(1, MAX_PROBES_PER_SEC);
         Self {
-            base_port: current_port,
+            node_id,
+            network_id: network_id.to_string(),
+            config,
+            port: Arc::new(AtomicU16::new(port)),
+            responder: std::sync::Mutex::new(None),
         }
     }
 
-    /// 로컬 네트워크에서 피어 스캔
-    pub async fn scan_local_peers(&self) -> Vec<SocketAddr> {
-        let mut discovered = Vec::new();
-
-        // 1. 기본 포트 우선 확인 (가장 일반적)
-        if self.base_port != DEFAULT_PORT {
-            let addr: SocketAddr = format!("127.0.0.1:{}", DEFAULT_PORT).parse().unwrap();
-            discovered.push(addr);
-        }
-
-        // 2. 인접 포트 확인 (가장 가능성 높은 순서)
-        // 기본 포트 근처부터 확인 (최대 3개 포트만 시도)
-        for offset in 1..=3 {
-            let port = DEFAULT_PORT + offset;
-            if port != self.base_port && port < DEFAULT_PORT + 10 {
-                let addr: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
-                if !discovered.contains(&addr) {
-                    discovered.push(addr);
+    /// 비컨 응답기 시작 (이미 실행 중이면 아무것도 하지 않음, tokio 런타임 안에서 호출)
+    pub fn start_responder(&self) -> std::io::Result<()> {
+        let mut responder = self.responder.lock().unwrap();
+        if responder.is_some() {
+            return Ok(());
+        }
+
+        let socket = bind_beacon_socket(self.config.beacon_port, self.config.multicast_group)?;
+        let node_id = self.node_id;
+        let network_id = self.network_id.clone();
+        let port = self.port.clone();
+        *responder = Some(tokio::spawn(async move {
+            let mut buf = [0u8; MAX_BEACON_SIZE];
+            let mut limiter = ReplyLimiter::default();
+            loop {
+                let (len, from) = match socket.recv_from(&mut buf).await {
+                    Ok(received) => received,
+                    Err(_) => continue,
+                };
+                let Some(probe) = Beacon::decode(&buf[..len]) else {
+                    continue;
+                };
+                // 자기 자신의 probe와 다른 network의 probe는 무시
+                if probe.kind != KIND_PROBE
+                    || probe.node_id == node_id
+                    || probe.network_id != network_id
+                    || !limiter.allow(from.ip())
+                {
+                    continue;
                 }
+
+                let reply = Beacon {
+                    kind: KIND_REPLY,
+                    nonce: probe.nonce,
+                    node_id,
+                    port: port.load(Ordering::Relaxed),
+                    network_id: network_id.clone(),
+                };
+                let _ = socket.send_to(&reply.encode(), from).await;
+            }
+        }));
+
+        let beacon_port = self.config.beacon_port;
+        log_discovery!("📡 LAN beacon responder listening on UDP {}", beacon_port);
+        Ok(())
+    }
+
+    /// 로컬 네트워크에서 응답한 피어 스캔
+    ///
+    /// 브로드캐스트와 멀티캐스트로 probe를 보내고, probe_cidrs가 있으면 대역 안의 각 주소와
+    /// probe_ports에도 보낸다 (모두 합쳐 최대 MAX_PROBES_PER_SCAN개). 전송은
+    /// max_probes_per_sec로 제한되고, 마지막 전송 후 reply_window 동안 응답을 모은다.
+    pub async fn scan(&self) -> Vec<ScannedPeer> {
+        let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await {
+            Ok(socket) => Arc::new(socket),
+            Err(e) => {
+                let error = e.to_string();
+                log_discovery!("⚠️ LAN scan socket failed: {}", error);
+                return Vec::new();
             }
+        };
+        let _ = socket.set_broadcast(true);
+        let _ = socket.set_multicast_loop_v4(true);
+
+        let nonce: u64 = rand::random();
+        let probe = Beacon {
+            kind: KIND_PROBE,
+            nonce,
+            node_id: self.node_id,
+            port: self.port.load(Ordering::Relaxed),
+            network_id: self.network_id.clone(),
         }
+        .encode();
+        let targets = self.probe_targets();
 
-        // 3. 현재 포트가 기본 범위 밖이면 추가
-        if self.base_port > DEFAULT_PORT + 10 || self.base_port < DEFAULT_PORT {
-            // 사용자 정의 포트도 스캔 범위에 포함
-            for offset in 1..=2 {
-                let port = self.base_port + offset;
-                if port != self.base_port {
-                    let addr: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
-                    if !discovered.contains(&addr) {
-                        discovered.push(addr);
+        // 보내는 동안에도 응답을 받도록 전송은 별도 태스크에서
+        let sender_socket = socket.clone();
+        let period = probe_period(self.config.max_probes_per_sec);
+        let mut sender = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            for target in targets {
+                ticker.tick().await;
+                let _ = sender_socket.send_to(&probe, target).await;
+            }
+        });
+
+        let mut peers: Vec<ScannedPeer> = Vec::new();
+        let mut buf = [0u8; MAX_BEACON_SIZE];
+        let mut deadline: Option<tokio::time::Instant> = None;
+        loop {
+            let wait_until = deadline.unwrap_or_else(|| {
+                // 전송이 끝나기 전에는 넉넉하게 대기
+                tokio::time::Instant::now() + Duration::from_secs(3600)
+            });
+            tokio::select! {
+                received = socket.recv_from(&mut buf) => {
+                    let Ok((len, from)) = received else { continue };
+                    let Some(reply) = Beacon::decode(&buf[..len]) else { continue };
+                    if reply.kind != KIND_REPLY
+                        || reply.nonce != nonce
+                        || reply.node_id == self.node_id
+                        || reply.network_id != self.network_id
+                        || reply.port == 0
+                    {
+                        continue;
+                    }
+                    if !peers.iter().any(|p| p.node_id == reply.node_id) {
+                        peers.push(ScannedPeer {
+                            node_id: reply.node_id,
+                            addr: SocketAddr::new(from.ip(), reply.port),
+                        });
                     }
                 }
+                _ = &mut sender, if deadline.is_none() => {
+                    deadline = Some(tokio::time::Instant::now() + self.config.reply_window);
+                }
+                _ = tokio::time::sleep_until(wait_until) => break,
+            }
+        }
+
+        let found = peers.len();
+        if found > 0 {
+            log_discovery!("📡 LAN scan found {} peers", found);
+        }
+        peers
+    }
+
+    /// 응답한 피어의 주소 목록
+    pub async fn scan_local_peers(&self) -> Vec<SocketAddr> {
+        self.scan()
+            .await
+            .into_iter()
+            .map(|peer| peer.addr)
+            .collect()
+    }
+
+    /// 스캔 한 번에 보내는 probe 수 (브로드캐스트/멀티캐스트 포함, 최대 MAX_PROBES_PER_SCAN)
+    pub fn probe_count(&self) -> usize {
+        let ports = self.config.probe_ports.clone().count();
+        self.config
+            .probe_cidrs
+            .iter()
+            .filter_map(|cidr| parse_cidr_hosts(cidr).ok())
+            .map(|hosts| hosts.len().saturating_mul(ports))
+            .fold(2, usize::saturating_add)
+            .min(MAX_PROBES_PER_SCAN)
+    }
+
+    // 브로드캐스트/멀티캐스트 뒤에 대역 × 포트 범위를 필요할 때 만들어 내고,
+    // 전체는 MAX_PROBES_PER_SCAN개로 제한
+    fn probe_targets(&self) -> impl Iterator<Item = SocketAddr> + Send + 'static {
+        let beacon_port = self.config.beacon_port;
+        let fixed = [
+            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::BROADCAST, beacon_port)),
+            SocketAddr::V4(SocketAddrV4::new(self.config.multicast_group, beacon_port)),
+        ];
+
+        let mut ranges = Vec::new();
+        for cidr in &self.config.probe_cidrs {
+            match parse_cidr_hosts(cidr) {
+                Ok(hosts) => ranges.push(hosts),
+                Err(e) => {
+                    let cidr = cidr.clone();
+                    log_discovery!("⚠️ Skipping probe range {}: {}", cidr, e);
+                }
             }
         }
 
-        // Return discovered peers
+        let ports = self.config.probe_ports.clone();
+        let total = ranges
+            .iter()
+            .map(|hosts| hosts.len().saturating_mul(ports.clone().count()))
+            .fold(fixed.len(), usize::saturating_add);
+        if total > MAX_PROBES_PER_SCAN {
+            log_discovery!(
+                "⚠️ LAN scan limited to {} of {} probes",
+                MAX_PROBES_PER_SCAN,
+                total
+            );
+        }
+
+        let unicast = ranges.into_iter().flatten().flat_map(move |host| {
+            ports
+                .clone()
+                .map(move |port| SocketAddr::V4(SocketAddrV4::new(host, port)))
+        });
+        fixed.into_iter().chain(unicast).take(MAX_PROBES_PER_SCAN)
+    }
+}
+
+#[async_trait]
+impl DiscoveryMethod for LocalScanner {
+    fn name(&self) -> &str {
+        "scan"
+    }
+
+    fn source(&self) -> PeerSource {
+        PeerSource::Scan
+    }
+
+    // 모든 probe를 보내고 reply_window 동안 응답을 모으는 시간 + 여유 1초
+    fn expected_duration(&self) -> Option<Duration> {
+        let sending = probe_period(self.config.max_probes_per_sec) * self.probe_count() as u32;
+        Some(sending + self.config.reply_window + Duration::from_secs(1))
+    }
+
+    async fn discover_peers(&self) -> Vec<SocketAddr> {
+        self.scan_local_peers().await
+    }
+
+    async fn announce(&self, addr: SocketAddr) {
+        self.port.store(addr.port(), Ordering::Relaxed);
+        if let Err(e) = self.start_responder() {
+            let error = e.to_string();
+            log_discovery!("⚠️ LAN beacon responder failed: {}", error);
+        }
+    }
+}
+
+impl Drop for LocalScanner {
+    fn drop(&mut self) {
+        if let Some(responder) = self.responder.lock().unwrap().take() {
+            responder.abort();
+        }
+    }
+}
+
+// probe 사이 간격 (범위를 벗어난 속도도 1..=MAX_PROBES_PER_SEC로 맞춰서 0이 되지 않음)
+fn probe_period(max_probes_per_sec: u32) -> Duration {
+    Duration::from_secs(1) / max_probes_per_sec.clamp(1, MAX_PROBES_PER_SEC)
+}
+
+// 여러 노드가 같은 비컨 포트를 쓸 수 있도록 재사용 옵션을 켜고 멀티캐스트 그룹에 가입
+fn bind_beacon_socket(port: u16, group: Ipv4Addr) -> std::io::Result<UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.set_broadcast(true)?;
+    socket.bind(&SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port)).into())?;
+    // 멀티캐스트를 쓸 수 없는 환경에서도 브로드캐스트/유니캐스트 probe는 받을 수 있음
+    let _ = socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED);
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// "a.b.c.d/len" 대역의 호스트 주소 (/31보다 넓으면 네트워크/브로드캐스트 주소 제외)
+pub fn parse_cidr_hosts(cidr: &str) -> Result<Vec<Ipv4Addr>, String> {
+    let (ip, prefix) = match cidr.trim().split_once('/') {
+        Some((ip, prefix)) => (ip, prefix),
+        None => (cidr.trim(), "32"),
+    };
+    let ip: Ipv4Addr = ip.parse().map_err(|_| "invalid IPv4 address".to_string())?;
+    let prefix: u32 = prefix
+        .parse()
+        .ok()
+        .filter(|p| *p <= 32)
+        .ok_or_else(|| "invalid prefix length".to_string())?;
+
+    let size = 1u64 << (32 - prefix);
+    if size as usize > MAX_PROBE_HOSTS + 2 {
+        return Err(format!("range larger than {} hosts", MAX_PROBE_HOSTS));
+    }
+    let mask = if prefix == 0 {
+        0
+    } else {
+        u32::MAX << (32 - prefix)
+    };
+    let network = u32::from(ip) & mask;
+
+    let hosts = if prefix >= 31 {
+        (0..size)
+            .map(|i| Ipv4Addr::from(network + i as u32))
+            .collect()
+    } else {
+        (1..size - 1)
+            .map(|i| Ipv4Addr::from(network + i as u32))
+            .collect()
+    };
+    Ok(hosts)
+}
+
+/// IP별 초당 응답 수 제한
+#[derive(Default)]
+struct ReplyLimiter {
+    windows: HashMap<IpAddr, (Instant, u32)>,
+}
+
+impl ReplyLimiter {
+    fn allow(&mut self, ip: IpAddr) -> bool {
+        let now = Instant::now();
+        if self.windows.len() > 4096 {
+            self.windows
+                .retain(|_, (start, _)| now.duration_since(*start) < Duration::from_secs(1));
+        }
+
+        let (start, count) = self.windows.entry(ip).or_insert((now, 0));
+        if now.duration_since(*start) >= Duration::from_secs(1) {
+            *start = now;
+            *count = 0;
+        }
+        *count += 1;
+        *count <= MAX_REPLIES_PER_SEC_PER_IP
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanner(config: ScanConfig) -> LocalScanner {
+        LocalScanner::new(NodeId::random(), "test", DEFAULT_PORT, config)
+    }
+
+    #[test]
+    fn probe_targets_cover_every_host_and_port() {
+        let scanner = scanner(ScanConfig {
+            probe_cidrs: vec!["10.0.0.0/30".to_string(), "bogus".to_string()],
+            probe_ports: 41999..=42000,
+            ..ScanConfig::default()
+        });
+        let targets: Vec<SocketAddr> = scanner.probe_targets().collect();
+        // 브로드캐스트 + 멀티캐스트 + 호스트 2개 × 포트 2개
+        assert_eq!(targets.len(), 6);
+        assert!(targets.contains(&"10.0.0.2:42000".parse().unwrap()));
+    }
+
+    #[test]
+    fn probe_targets_are_capped() {
+        let scanner = scanner(ScanConfig {
+            probe_cidrs: vec!["10.0.0.0/20".to_string(), "10.1.0.0/20".to_string()],
+            probe_ports: 1..=u16::MAX,
+            ..ScanConfig::default()
+        });
+        assert_eq!(scanner.probe_targets().count(), MAX_PROBES_PER_SCAN);
+    }
+
+    #[test]
+    fn expected_duration_covers_rate_limited_sending() {
+        let scanner = scanner(ScanConfig {
+            // 1022 호스트 × 2 포트 + 브로드캐스트/멀티캐스트 = 2046 probe, 초당 100개
+            probe_cidrs: vec!["10.0.0.0/22".to_string()],
+            probe_ports: 41999..=42000,
+            max_probes_per_sec: 100,
+            ..ScanConfig::default()
+        });
+        assert_eq!(scanner.probe_count(), 2046);
+        let expected = scanner.expected_duration().unwrap();
+        assert!(expected >= Duration::from_millis(20_460) + scanner.config.reply_window);
+        assert!(expected < Duration::from_secs(30));
+    }
 
-        discovered
+    #[test]
+    fn probe_rate_is_clamped() {
+        let scanner = scanner(ScanConfig {
+            max_probes_per_sec: u32::MAX,
+            ..ScanConfig::default()
+        });
+        assert_eq!(scanner.config.max_probes_per_sec, MAX_PROBES_PER_SEC);
+        assert_eq!(probe_period(u32::MAX), Duration::from_micros(100));
+        assert_eq!(probe_period(0), Duration::from_secs(1));
     }
 }
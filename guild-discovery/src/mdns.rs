@@ -0,0 +1,327 @@
+// mDNS 기반 LAN 피어 발견 (mdns-sd)
+//
+// 자신의 QUIC 포트를 `_guild._udp.local.` 서비스로 등록하고, 같은 서비스를 브라우징해서
+// 같은 network id를 가진 피어를 찾는다. TXT 레코드:
+//
+// | 키    | 값                          |
+// |-------|-----------------------------|
+// | id    | 노드 id (16진수 64자)       |
+// | net   | network id                  |
+// | v     | mDNS 레코드 버전 (현재 1)   |
+// | caps  | 역할 태그 (쉼표 구분, 선택) |
+//
+// 피어가 goodbye를 보내거나 레코드 TTL이 만료되면 ServiceRemoved 이벤트로 목록에서 제거된다.
+use async_trait::async_trait;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::address_book::PeerSource;
+use crate::capabilities::validate_capability;
+use crate::dht::NodeId;
+use crate::discovery::DiscoveryMethod;
+use crate::log_discovery;
+
+pub const SERVICE_TYPE: &str = "_guild._udp.local.";
+const RECORD_VERSION: &str = "1";
+
+/// mDNS로 발견한 피어
+#[derive(Debug, Clone)]
+pub struct MdnsPeer {
+    pub node_id: NodeId,
+    pub addrs: Vec<SocketAddr>,
+    /// TXT 레코드로 광고한 역할 태그
+    pub capabilities: Vec<String>,
+}
+
+impl MdnsPeer {
+    /// 다이얼할 주소 (IPv4 우선)
+    pub fn preferred_addr(&self) -> Option<SocketAddr> {
+        self.addrs
+            .iter()
+            .find(|addr| addr.is_ipv4())
+            .or_else(|| self.addrs.first())
+            .copied()
+    }
+}
+
+pub struct MdnsDiscovery {
+    daemon: ServiceDaemon,
+    node_id: NodeId,
+    network_id: String,
+    capabilities: Vec<String>,
+    /// 등록한 서비스의 전체 이름 (announce 전에는 None)
+    registered: std::sync::Mutex<Option<String>>,
+    /// 서비스 전체 이름 -> 피어
+    peers: Arc<RwLock<HashMap<String, MdnsPeer>>>,
+}
+
+impl MdnsDiscovery {
+    /// mDNS 데몬을 시작하고 브라우징 시작 (tokio 런타임 안에서 호출)
+    pub fn new(node_id: NodeId, network_id: &str) -> Result<Self, mdns_sd::Error> {
+        Self::with_capabilities(node_id, network_id, Vec::new())
+    }
+
+    /// 등록할 때 역할 태그도 함께 광고
+    pub fn with_capabilities(
+        node_id: NodeId,
+        network_id: &str,
+        capabilities: Vec<String>,
+    ) -> Result<Self, mdns_sd::Error> {
+        let daemon = ServiceDaemon::new()?;
+        let events = daemon.browse(SERVICE_TYPE)?;
+        let peers = Arc::new(RwLock::new(HashMap::new()));
+
+        let own_id = node_id;
+        let own_network = network_id.to_string();
+        let browse_peers = peers.clone();
+        tokio::spawn(async move {
+            while let Ok(event) = events.recv_async().await {
+                match event {
+                    ServiceEvent::ServiceResolved(info) => {
+                        let Some(peer) = Self::parse_peer(&info, &own_id, &own_network) else {
+                            continue;
+                        };
+                        let fullname = info.get_fullname().to_string();
+                        let is_new = browse_peers
+                            .write()
+                            .await
+                            .insert(fullname, peer.clone())
+                            .is_none();
+                        if is_new {
+                            if let Some(addr) = peer.preferred_addr() {
+                                log_discovery!("📡 mDNS peer found: {}", addr);
+                            }
+                        }
+                    }
+                    ServiceEvent::ServiceRemoved(_, fullname) => {
+                        // goodbye 또는 TTL 만료
+                        if let Some(peer) = browse_peers.write().await.remove(&fullname) {
+                            if let Some(addr) = peer.preferred_addr() {
+                                log_discovery!("👋 mDNS peer gone: {}", addr);
+                            }
+                        }
+                    }
+                    ServiceEvent::SearchStopped(_) => break,
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(Self {
+            daemon,
+            node_id,
+            network_id: network_id.to_string(),
+            capabilities,
+            registered: std::sync::Mutex::new(None),
+            peers,
+        })
+    }
+
+    /// 해석된 서비스에서 피어 정보 추출 (자기 자신, 다른 network, 다른 레코드 버전은 무시)
+    fn parse_peer(info: &ServiceInfo, own_id: &NodeId, network_id: &str) -> Option<MdnsPeer> {
+        if info.get_property_val_str("net") != Some(network_id)
+            || info.get_property_val_str("v") != Some(RECORD_VERSION)
+        {
+            return None;
+        }
+        let node_id = NodeId::from_hex(info.get_property_val_str("id")?)?;
+        if node_id == *own_id {
+            return None;
+        }
+
+        let port = info.get_port();
+        let mut addrs: Vec<SocketAddr> = info
+            .get_addresses()
+            .iter()
+            .map(|ip: &IpAddr| SocketAddr::new(*ip, port))
+            .collect();
+        if addrs.is_empty() {
+            return None;
+        }
+        addrs.sort();
+        // 규칙에 맞지 않는 태그는 무시
+        let capabilities = info
+            .get_property_val_str("caps")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|tag| validate_capability(tag).is_ok())
+            .map(str::to_string)
+            .collect();
+        Some(MdnsPeer {
+            node_id,
+            addrs,
+            capabilities,
+        })
+    }
+
+    /// 서비스 등록 (이미 등록되어 있으면 포트를 갱신하기 위해 다시 등록)
+    pub fn register(&self, port: u16) -> Result<(), mdns_sd::Error> {
+        // 인스턴스 이름은 63바이트 제한이 있으므로 노드 id 앞부분만 사용
+        let instance = format!("guild-{}", &self.node_id.to_hex()[..16]);
+        let host_name = format!("{}.local.", instance);
+        let node_hex = self.node_id.to_hex();
+        let capabilities = self.capabilities.join(",");
+        let mut properties = vec![
+            ("id", node_hex.as_str()),
+            ("net", self.network_id.as_str()),
+            ("v", RECORD_VERSION),
+        ];
+        if !capabilities.is_empty() {
+            properties.push(("caps", capabilities.as_str()));
+        }
+
+        let info = ServiceInfo::new(
+            SERVICE_TYPE,
+            &instance,
+            &host_name,
+            "",
+            port,
+            &properties[..],
+        )?
+        .enable_addr_auto();
+        let fullname = info.get_fullname().to_string();
+        self.daemon.register(info)?;
+
+        log_discovery!("📡 mDNS service registered on port {}", port);
+        *self.registered.lock().unwrap() = Some(fullname);
+        Ok(())
+    }
+
+    /// 현재 살아 있는 mDNS 피어 목록
+    pub async fn peers(&self) -> Vec<MdnsPeer> {
+        self.peers.read().await.values().cloned().collect()
+    }
+
+    /// 브라우징으로 받은 것처럼 피어를 목록에 넣음 (멀티캐스트 없이 테스트할 때)
+    #[cfg(test)]
+    pub(crate) async fn cache_peer(&self, fullname: &str, peer: MdnsPeer) {
+        self.peers.write().await.insert(fullname.to_string(), peer);
+    }
+}
+
+#[async_trait]
+impl DiscoveryMethod for MdnsDiscovery {
+    fn name(&self) -> &str {
+        "mdns"
+    }
+
+    fn source(&self) -> PeerSource {
+        PeerSource::Mdns
+    }
+
+    async fn discover_peers(&self) -> Vec<SocketAddr> {
+        self.peers()
+            .await
+            .iter()
+            .filter_map(MdnsPeer::preferred_addr)
+            .collect()
+    }
+
+    async fn announce(&self, addr: SocketAddr) {
+        if let Err(e) = self.register(addr.port()) {
+            let error = e.to_string();
+            log_discovery!("⚠️ mDNS registration failed: {}", error);
+        }
+    }
+}
+
+impl Drop for MdnsDiscovery {
+    fn drop(&mut self) {
+        // goodbye를 보내서 다른 노드가 TTL 만료를 기다리지 않고 바로 제거하도록 함
+        if let Some(fullname) = self.registered.lock().unwrap().take() {
+            let _ = self.daemon.unregister(&fullname);
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NET: &str = "guild-test";
+
+    fn service(id: &NodeId, properties: &[(&str, &str)]) -> ServiceInfo {
+        let id_hex = id.to_hex();
+        let mut txt = vec![("id", id_hex.as_str())];
+        txt.extend_from_slice(properties);
+        ServiceInfo::new(
+            SERVICE_TYPE,
+            "guild-test",
+            "guild-test.local.",
+            "10.0.0.5",
+            42000,
+            &txt[..],
+        )
+        .unwrap()
+    }
+
+    fn node_id(byte: u8) -> NodeId {
+        NodeId([byte; 32])
+    }
+
+    #[test]
+    fn valid_record_is_parsed() {
+        let info = service(&node_id(2), &[("net", NET), ("v", RECORD_VERSION)]);
+        let peer = MdnsDiscovery::parse_peer(&info, &node_id(1), NET).unwrap();
+        assert_eq!(peer.node_id, node_id(2));
+        assert_eq!(
+            peer.preferred_addr(),
+            Some("10.0.0.5:42000".parse().unwrap())
+        );
+        assert!(peer.capabilities.is_empty());
+    }
+
+    #[test]
+    fn own_record_and_bad_ids_are_ignored() {
+        let own = service(&node_id(1), &[("net", NET), ("v", RECORD_VERSION)]);
+        assert!(MdnsDiscovery::parse_peer(&own, &node_id(1), NET).is_none());
+
+        let bad_id = ServiceInfo::new(
+            SERVICE_TYPE,
+            "guild-test",
+            "guild-test.local.",
+            "10.0.0.5",
+            42000,
+            &[("id", "not-hex"), ("net", NET), ("v", RECORD_VERSION)][..],
+        )
+        .unwrap();
+        assert!(MdnsDiscovery::parse_peer(&bad_id, &node_id(1), NET).is_none());
+    }
+
+    #[test]
+    fn records_from_other_networks_are_ignored() {
+        let foreign = service(&node_id(2), &[("net", "other-net"), ("v", RECORD_VERSION)]);
+        assert!(MdnsDiscovery::parse_peer(&foreign, &node_id(1), NET).is_none());
+        let missing = service(&node_id(2), &[("v", RECORD_VERSION)]);
+        assert!(MdnsDiscovery::parse_peer(&missing, &node_id(1), NET).is_none());
+    }
+
+    #[test]
+    fn other_record_versions_are_ignored() {
+        let newer = service(&node_id(2), &[("net", NET), ("v", "2")]);
+        assert!(MdnsDiscovery::parse_peer(&newer, &node_id(1), NET).is_none());
+        let missing = service(&node_id(2), &[("net", NET)]);
+        assert!(MdnsDiscovery::parse_peer(&missing, &node_id(1), NET).is_none());
+    }
+
+    #[test]
+    fn invalid_capability_tags_are_dropped() {
+        let info = service(
+            &node_id(2),
+            &[
+                ("net", NET),
+                ("v", RECORD_VERSION),
+                ("caps", "validator,Bad Tag,,relay"),
+            ],
+        );
+        let peer = MdnsDiscovery::parse_peer(&info, &node_id(1), NET).unwrap();
+        assert_eq!(
+            peer.capabilities,
+            vec!["validator".to_string(), "relay".to_string()]
+        );
+    }
+}
@@ -0,0 +1,323 @@
+// 정적 피어 파일 - 운영자가 직접 적어 둔 피어 목록
+//
+// 한 줄에 하나씩 부트스트랩 항목과 같은 형식(`1.2.3.4:42000`, `[::1]:42000`, `host:port`,
+// `host`)으로 적고, 뒤에 `id=<64자리 hex>`(node id 고정)와 `label=<이름>`을 선택적으로
+// 붙인다. 빈 줄과 `#` 뒤의 내용은 무시한다.
+//
+//     # 서울 검증 노드
+//     203.0.113.7:42000 id=5f3a...e1 label=seoul-validator
+//     seed.guild.network
+//
+// 탐색할 때마다 파일을 다시 읽고, `spawn_watcher`를 실행하면 파일의 수정 시각을 주기적으로
+// 확인해서 바뀔 때마다 이전 목록과의 차이(추가/제거)를 보낸다. 받는 쪽(guild-home)은 이를
+// 연결/연결 종료로 반영하므로 재시작 없이 고정 피어를 바꿀 수 있다.
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{mpsc, Mutex, RwLock};
+
+use crate::address_book::PeerSource;
+use crate::bootstrap::{self, BootstrapEntry, DEFAULT_RESOLVE_INTERVAL};
+use crate::dht::NodeId;
+use crate::discovery::DiscoveryMethod;
+use crate::log_discovery;
+
+/// 파일 수정 확인 주기
+pub const DEFAULT_STATIC_PEERS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 파일의 한 줄
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticPeer {
+    pub entry: BootstrapEntry,
+    /// 고정한 node id (연결 후 실제 id가 다르면 끊음)
+    pub node_id: Option<NodeId>,
+    pub label: Option<String>,
+}
+
+impl StaticPeer {
+    /// 한 줄 해석 (주석은 미리 제거되어 있어야 함)
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut tokens = line.split_whitespace();
+        let entry = tokens.next().ok_or_else(|| "empty line".to_string())?;
+        let entry = BootstrapEntry::parse(entry).map_err(|e| e.to_string())?;
+
+        let mut peer = StaticPeer {
+            entry,
+            node_id: None,
+            label: None,
+        };
+        for token in tokens {
+            match token.split_once('=') {
+                Some(("id", hex)) => {
+                    let id =
+                        NodeId::from_hex(hex).ok_or_else(|| format!("invalid node id: {}", hex))?;
+                    peer.node_id = Some(id);
+                }
+                Some(("label", label)) if !label.is_empty() => {
+                    peer.label = Some(label.to_string());
+                }
+                _ => return Err(format!("unknown option: {}", token)),
+            }
+        }
+        Ok(peer)
+    }
+}
+
+/// 주소로 해석된 정적 피어
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedStaticPeer {
+    /// 파일에 적힌 항목
+    pub entry: BootstrapEntry,
+    pub addr: SocketAddr,
+    pub node_id: Option<NodeId>,
+    pub label: Option<String>,
+}
+
+impl fmt::Display for ResolvedStaticPeer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{} ({})", self.addr, label),
+            None => write!(f, "{}", self.addr),
+        }
+    }
+}
+
+/// 파일을 다시 읽었을 때 이전 목록과의 차이
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StaticPeerChange {
+    /// 연결할 피어 (고정 id가 바뀐 주소도 제거 후 다시 추가됨)
+    Added(ResolvedStaticPeer),
+    /// 연결을 끊을 피어
+    Removed(ResolvedStaticPeer),
+}
+
+#[derive(Default)]
+struct WatchState {
+    // 마지막으로 읽은 파일 수정 시각 (파일이 없으면 None)
+    modified: Option<SystemTime>,
+    // 마지막으로 해석한 시각 (호스트 이름 재조회용)
+    resolved_at: Option<Instant>,
+    // 파일에 호스트 이름 항목이 있는지
+    has_hosts: bool,
+}
+
+pub struct StaticPeers {
+    path: PathBuf,
+    // 마지막으로 반영한 목록 (reload 기준)
+    applied: RwLock<HashMap<SocketAddr, ResolvedStaticPeer>>,
+    watch: Mutex<WatchState>,
+}
+
+impl StaticPeers {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            applied: RwLock::new(HashMap::new()),
+            watch: Mutex::new(WatchState::default()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 파일의 항목 목록 (파일이 없으면 빈 목록, 잘못된 줄은 로그를 남기고 건너뜀)
+    pub async fn entries(&self) -> Vec<StaticPeer> {
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+            Err(e) => {
+                let path = self.path.display().to_string();
+                let error = e.to_string();
+                log_discovery!("⚠️ Failed to read static peers {}: {}", path, error);
+                return Vec::new();
+            }
+        };
+
+        let mut entries: Vec<StaticPeer> = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            match StaticPeer::parse(line) {
+                Ok(peer) => {
+                    if !entries.iter().any(|e| e.entry == peer.entry) {
+                        entries.push(peer);
+                    }
+                }
+                Err(error) => {
+                    let line_number = index + 1;
+                    log_discovery!("⚠️ static peers line {}: {}", line_number, error);
+                }
+            }
+        }
+        entries
+    }
+
+    /// 모든 항목을 주소로 해석
+    ///
+    /// 조회에 실패한 호스트 이름은 마지막으로 반영한 주소를 계속 쓴다 (일시적인 DNS 장애로
+    /// 연결이 끊기지 않도록).
+    pub async fn resolve(&self) -> Vec<ResolvedStaticPeer> {
+        let mut resolved: Vec<ResolvedStaticPeer> = Vec::new();
+        for peer in self.entries().await {
+            match bootstrap::resolve(&peer.entry).await {
+                Ok(addrs) => {
+                    for addr in addrs {
+                        if !resolved.iter().any(|r| r.addr == addr) {
+                            resolved.push(ResolvedStaticPeer {
+                                entry: peer.entry.clone(),
+                                addr,
+                                node_id: peer.node_id,
+                                label: peer.label.clone(),
+                            });
+                        }
+                    }
+                }
+                Err(error) => {
+                    let entry = peer.entry.to_string();
+                    log_discovery!("⚠️ Failed to resolve static peer {}: {}", entry, error);
+                    let applied = self.applied.read().await;
+                    for previous in applied.values().filter(|r| r.entry == peer.entry) {
+                        if !resolved.iter().any(|r| r.addr == previous.addr) {
+                            resolved.push(ResolvedStaticPeer {
+                                node_id: peer.node_id,
+                                label: peer.label.clone(),
+                                ..previous.clone()
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        resolved
+    }
+
+    /// 파일을 다시 읽고 마지막으로 반영한 목록과의 차이를 반환 (제거 → 추가 순)
+    pub async fn reload(&self) -> Vec<StaticPeerChange> {
+        let resolved = self.resolve().await;
+        let mut applied = self.applied.write().await;
+
+        let mut changes = Vec::new();
+        for (addr, old) in applied.iter() {
+            let kept = resolved
+                .iter()
+                .any(|r| r.addr == *addr && r.node_id == old.node_id);
+            if !kept {
+                changes.push(StaticPeerChange::Removed(old.clone()));
+            }
+        }
+        for peer in &resolved {
+            let known = applied
+                .get(&peer.addr)
+                .is_some_and(|old| old.node_id == peer.node_id);
+            if !known {
+                changes.push(StaticPeerChange::Added(peer.clone()));
+            }
+        }
+
+        *applied = resolved.into_iter().map(|r| (r.addr, r)).collect();
+        changes
+    }
+
+    /// 파일이 바뀌었거나(생성/삭제 포함) 호스트 이름 재조회 주기가 지났으면 reload
+    pub async fn reload_if_changed(&self) -> Option<Vec<StaticPeerChange>> {
+        let modified = tokio::fs::metadata(&self.path)
+            .await
+            .and_then(|meta| meta.modified())
+            .ok();
+
+        let mut watch = self.watch.lock().await;
+        let changed = watch.resolved_at.is_none() || watch.modified != modified;
+        let re_resolve = watch.has_hosts
+            && watch
+                .resolved_at
+                .is_some_and(|at| at.elapsed() >= DEFAULT_RESOLVE_INTERVAL);
+        if !changed && !re_resolve {
+            return None;
+        }
+
+        watch.modified = modified;
+        watch.resolved_at = Some(Instant::now());
+        watch.has_hosts = self
+            .entries()
+            .await
+            .iter()
+            .any(|peer| matches!(peer.entry, BootstrapEntry::Host { .. }));
+        drop(watch);
+        Some(self.reload().await)
+    }
+
+    /// 파일 감시 시작 (첫 확인에서 현재 목록 전체가 Added로 나옴)
+    ///
+    /// 받는 쪽이 수신기를 버리거나 StaticPeers가 해제되면 멈춘다.
+    pub fn spawn_watcher(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> mpsc::UnboundedReceiver<StaticPeerChange> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(static_peers) = weak.upgrade() {
+                if let Some(changes) = static_peers.reload_if_changed().await {
+                    if !changes.is_empty() {
+                        let path = static_peers.path.display().to_string();
+                        let count = changes.len();
+                        log_discovery!("📄 Reloaded {}: {} changes", path, count);
+                    }
+                    for change in changes {
+                        if tx.send(change).is_err() {
+                            return;
+                        }
+                    }
+                }
+                drop(static_peers);
+                if tx.is_closed() {
+                    break;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        rx
+    }
+
+    /// 주소에 고정된 node id (마지막으로 반영한 목록 기준)
+    pub async fn pinned_id(&self, addr: &SocketAddr) -> Option<NodeId> {
+        self.applied
+            .read()
+            .await
+            .get(addr)
+            .and_then(|peer| peer.node_id)
+    }
+
+    /// 마지막으로 반영한 목록
+    pub async fn applied(&self) -> Vec<ResolvedStaticPeer> {
+        self.applied.read().await.values().cloned().collect()
+    }
+}
+
+#[async_trait]
+impl DiscoveryMethod for StaticPeers {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    fn source(&self) -> PeerSource {
+        PeerSource::Static
+    }
+
+    async fn discover_peers(&self) -> Vec<SocketAddr> {
+        self.resolve()
+            .await
+            .into_iter()
+            .map(|peer| peer.addr)
+            .collect()
+    }
+
+    async fn announce(&self, _addr: SocketAddr) {}
+}
@@ -0,0 +1,119 @@
+// 스트림 재사용/배치 벤치마크
+//
+// 로컬 QUIC 연결 두 개로 "메시지마다 새 스트림" 방식과 "클래스별 스트림 재사용 + 배치"
+// 방식의 처리량과 Ping 왕복 지연을 비교한다.
+//
+//   cargo run --release -p guild-home --example stream_bench -- [메시지 수] [메시지 크기]
+use guild_home::{Network, NetworkConfig};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SENDERS: usize = 32;
+const PING_ROUNDS: usize = 200;
+
+struct BenchResult {
+    messages_per_sec: f64,
+    avg_batch: f64,
+    ping_p50: Duration,
+    ping_p99: Duration,
+}
+
+#[tokio::main]
+async fn main() {
+    // TUI 모드로 초기화해서 로그를 화면에 출력하지 않음
+    guild_logger::init_logger(true);
+
+    let args: Vec<String> = std::env::args().collect();
+    let messages: usize = args.get(1).and_then(|v| v.parse().ok()).unwrap_or(20_000);
+    let size: usize = args.get(2).and_then(|v| v.parse().ok()).unwrap_or(64);
+
+    println!(
+        "📊 {} messages x {} bytes, {} concurrent senders",
+        messages, size, SENDERS
+    );
+    println!();
+
+    let per_message = NetworkConfig {
+        persistent_streams: false,
+        ..NetworkConfig::default()
+    };
+    let persistent = NetworkConfig::default();
+    let windowed = NetworkConfig {
+        batch_window: Duration::from_millis(1),
+        ..NetworkConfig::default()
+    };
+
+    let baseline = run("stream per message", per_message, messages, size).await;
+    for (name, config) in [
+        ("persistent + batching", persistent),
+        ("  + 1ms batch window", windowed),
+    ] {
+        let result = run(name, config, messages, size).await;
+        println!(
+            "{:<24} 🚀 throughput x{:.2}, ping p50 x{:.2}",
+            "",
+            result.messages_per_sec / baseline.messages_per_sec,
+            baseline.ping_p50.as_secs_f64() / result.ping_p50.as_secs_f64().max(f64::EPSILON)
+        );
+    }
+}
+
+async fn run(name: &str, config: NetworkConfig, messages: usize, size: usize) -> BenchResult {
+    let sender = Arc::new(Network::with_config(0, config.clone()).await);
+    let receiver = Network::with_config(0, config).await;
+
+    let addr = format!("127.0.0.1:{}", receiver.local_port())
+        .parse()
+        .unwrap();
+    sender.connect(addr).await.expect("connect failed");
+
+    // 처리량: 여러 태스크가 동시에 작은 메시지를 전송
+    let start = Instant::now();
+    let mut tasks = Vec::new();
+    for sender_index in 0..SENDERS {
+        let sender = sender.clone();
+        let count = messages / SENDERS + usize::from(sender_index < messages % SENDERS);
+        tasks.push(tokio::spawn(async move {
+            let payload = vec![0xab; size];
+            for _ in 0..count {
+                sender.broadcast(&payload).await;
+            }
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+    while receiver.get_stats().await.messages_received < messages as u64 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    let elapsed = start.elapsed();
+
+    let stats = sender.get_stats().await;
+    let avg_batch = stats.messages_sent as f64 / stats.batches_sent.max(1) as f64;
+
+    // 지연: Ping을 보내고 Pong이 돌아올 때까지의 시간
+    let mut rtts = Vec::with_capacity(PING_ROUNDS);
+    for _ in 0..PING_ROUNDS {
+        let before = sender.get_stats().await.pongs_received;
+        let ping_start = Instant::now();
+        sender.send_ping().await;
+        while sender.get_stats().await.pongs_received == before {
+            tokio::task::yield_now().await;
+        }
+        rtts.push(ping_start.elapsed());
+    }
+    rtts.sort();
+
+    let result = BenchResult {
+        messages_per_sec: messages as f64 / elapsed.as_secs_f64(),
+        avg_batch,
+        ping_p50: rtts[rtts.len() / 2],
+        ping_p99: rtts[rtts.len() * 99 / 100],
+    };
+
+    println!(
+        "{:<24} {:>8.1?}  {:>10.0} msg/s  batch {:>6.1}  ping p50 {:>8.1?}  p99 {:>8.1?}",
+        name, elapsed, result.messages_per_sec, result.avg_batch, result.ping_p50, result.ping_p99
+    );
+    result
+}
@@ -0,0 +1,254 @@
+// Guild Replay - 녹화된 트래픽 확인 및 재현 도구
+//
+// 기본 동작은 녹화 파일의 레코드를 시간 순서대로 출력하는 것이고, --replay를 주면
+// 수신(inbound) 레코드를 소켓 없는 전송 계층 위의 새 Network에 원래 간격대로 다시 주입한다.
+use async_trait::async_trait;
+use guild_discovery::identity::node_id_for_key;
+use guild_home::blockchain_bridge::BlockchainBridge;
+use guild_home::network::Message;
+use guild_home::recorder::{Direction, Record};
+use guild_home::wire::{self, Frame};
+use guild_home::transport::TransportError;
+use guild_home::{Network, NetworkConfig, RecordingReader, Transport, TransportConnection};
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 재현용 전송 계층 - 연결을 맺거나 수락하지 않음 (수신 레코드는 replay_inbound로 주입)
+struct ReplayTransport;
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn connect(
+        &self,
+        addr: SocketAddr,
+    ) -> Result<Arc<dyn TransportConnection>, TransportError> {
+        Err(format!("Replay does not dial peers: {}", addr).into())
+    }
+
+    async fn accept(&self) -> Option<Arc<dyn TransportConnection>> {
+        std::future::pending().await
+    }
+
+    fn local_addr(&self) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], 0))
+    }
+}
+
+struct ReplayArgs {
+    path: String,
+    replay: bool,
+    speed: f64,
+    bridge: bool,
+    hold_secs: u64,
+}
+
+fn print_usage() {
+    println!(
+        r#"
+🎞️ Guild Replay - 녹화된 트래픽 확인 및 재현
+
+USAGE:
+    guild-replay <RECORDING> [OPTIONS]
+
+OPTIONS:
+        --replay                  수신 메시지를 새 Network 인스턴스에 다시 주입
+        --speed <FACTOR>          재생 속도 배율 (기본: 1.0, 0 = 대기 없이)
+        --bridge                  블록체인 브리지를 함께 시작 (IPC 클라이언트가 접속 가능)
+        --hold <SECONDS>          재생이 끝난 뒤 유지할 시간 (기본: 0)
+    -h, --help                    도움말 표시
+"#
+    );
+}
+
+fn parse_args() -> Result<ReplayArgs, String> {
+    let args: Vec<String> = env::args().collect();
+    let mut parsed = ReplayArgs {
+        path: String::new(),
+        replay: false,
+        speed: 1.0,
+        bridge: false,
+        hold_secs: 0,
+    };
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--replay" => parsed.replay = true,
+            "--bridge" => parsed.bridge = true,
+            "--speed" => {
+                i += 1;
+                parsed.speed = args
+                    .get(i)
+                    .and_then(|v| v.parse().ok())
+                    .filter(|speed: &f64| *speed >= 0.0)
+                    .ok_or("Invalid --speed value")?;
+            }
+            "--hold" => {
+                i += 1;
+                parsed.hold_secs = args
+                    .get(i)
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("Invalid --hold value")?;
+            }
+            "--help" | "-h" => {
+                print_usage();
+                std::process::exit(0);
+            }
+            other if parsed.path.is_empty() && !other.starts_with('-') => {
+                parsed.path = other.to_string();
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+        i += 1;
+    }
+
+    if parsed.path.is_empty() {
+        return Err("Missing recording path".to_string());
+    }
+    Ok(parsed)
+}
+
+/// 레코드에 담긴 프레임 요약
+fn describe_frames(frames: &[u8]) -> String {
+    let mut parts = Vec::new();
+    let mut offset = 0;
+    while offset < frames.len() {
+        match wire::decode(&frames[offset..]) {
+            Ok((frame, used)) => {
+                offset += used;
+                parts.push(match frame {
+                    Frame::Message(Message::Ping { id, .. }) => format!("Ping {}", id),
+                    Frame::Message(Message::Pong { id, .. }) => format!("Pong {}", id),
+                    Frame::Message(Message::Data(data)) => format!("Data {}B", data.len()),
+                    Frame::Message(Message::Datagram(data)) => {
+                        format!("Datagram {}B", data.len())
+                    }
+                    Frame::Message(Message::Protocol { protocol, data }) => {
+                        format!("Protocol {} {}B", protocol, data.len())
+                    }
+                    Frame::Message(Message::ProtocolUnsupported { protocol }) => {
+                        format!("ProtocolUnsupported {}", protocol)
+                    }
+                    Frame::Message(Message::OpenStream { protocol }) => {
+                        format!("OpenStream {}", protocol)
+                    }
+                    Frame::Message(Message::Hello { network_id, .. }) => {
+                        format!("Hello {}", network_id)
+                    }
+                    Frame::Message(Message::HelloAuth { public_key, .. }) => {
+                        format!("HelloAuth {}", node_id_for_key(&public_key).to_hex())
+                    }
+                    Frame::Skipped { kind, version } => {
+                        format!("Unknown 0x{:02x} (v{})", kind, version)
+                    }
+                });
+            }
+            Err(e) => {
+                parts.push(format!("<{}>", e));
+                break;
+            }
+        }
+    }
+    parts.join(", ")
+}
+
+fn print_record(record: &Record, start_us: u64) {
+    let offset = record.timestamp_us.saturating_sub(start_us) as f64 / 1_000_000.0;
+    let direction = match record.direction {
+        Direction::Inbound => "⬅️ in ",
+        Direction::Outbound => "➡️ out",
+    };
+    println!(
+        "+{:>10.3}s {} {:<21} {:<8?} {}",
+        offset,
+        direction,
+        record.peer,
+        record.path,
+        describe_frames(&record.frames)
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        print_usage();
+        std::process::exit(1);
+    });
+
+    guild_logger::init_logger(false);
+
+    let reader = RecordingReader::open(&args.path).await.unwrap_or_else(|e| {
+        eprintln!("❌ Failed to open {}: {}", args.path, e);
+        std::process::exit(1);
+    });
+
+    let mut records = Vec::new();
+    for record in reader {
+        match record {
+            Ok(record) => records.push(record),
+            Err(e) => {
+                // 녹화 도중 종료되어 잘린 마지막 레코드는 무시
+                eprintln!("⚠️ Stopped reading recording: {}", e);
+                break;
+            }
+        }
+    }
+
+    let start_us = records.first().map(|r| r.timestamp_us).unwrap_or(0);
+    let inbound = records
+        .iter()
+        .filter(|r| r.direction == Direction::Inbound)
+        .count();
+    println!(
+        "🎞️ {} records ({} inbound, {} outbound)",
+        records.len(),
+        inbound,
+        records.len() - inbound
+    );
+
+    if !args.replay {
+        for record in &records {
+            print_record(record, start_us);
+        }
+        return;
+    }
+
+    // 실제 소켓 없이 재현
+    let network = Arc::new(
+        Network::with_transport(Arc::new(ReplayTransport), NetworkConfig::default()).await,
+    );
+
+    let mut bridge = None;
+    if args.bridge {
+        let mut new_bridge = BlockchainBridge::new(network.clone());
+        if let Err(e) = new_bridge.start().await {
+            eprintln!("❌ Failed to start blockchain bridge: {}", e);
+            std::process::exit(1);
+        }
+        bridge = Some(new_bridge);
+    }
+
+    let replay_start = tokio::time::Instant::now();
+    for record in records.iter().filter(|r| r.direction == Direction::Inbound) {
+        if args.speed > 0.0 {
+            let offset_us = record.timestamp_us.saturating_sub(start_us) as f64 / args.speed;
+            tokio::time::sleep_until(replay_start + Duration::from_micros(offset_us as u64)).await;
+        }
+        print_record(record, start_us);
+        network
+            .replay_inbound(record.peer, record.path, &record.frames)
+            .await;
+    }
+
+    let stats = network.get_stats().await;
+    println!("✅ Replay finished: {:?}", stats);
+
+    if args.hold_secs > 0 {
+        println!("⏳ Holding for {} seconds", args.hold_secs);
+        tokio::time::sleep(Duration::from_secs(args.hold_secs)).await;
+    }
+    drop(bridge);
+}
@@ -1,82 +1,209 @@
 // 블록체인 브리지 - Guild-Home과 블록체인 프로세스 연결
-use crate::network::Network;
-use serde::{Deserialize, Serialize};
+//
+// IPC 프로토콜(메시지, 프레이밍, 버전 핸드셰이크)은 guild-ipc 크레이트에 있다.
+//
+// 여러 애플리케이션(블록체인, 채팅 등)이 동시에 연결할 수 있다. 애플리케이션 id를 등록한
+// 클라이언트의 트래픽은 `guild/app/<id>` 프로토콜 메시지로 오가므로, 같은 id를 등록한
+// 클라이언트끼리만 메시지를 주고받는다. id 없이 연결한 클라이언트는 태그 없는 데이터를 쓴다.
+//
+// 탐색기(Discovery)를 연결해 두면 클라이언트가 FindPeers로 validator 같은 역할 태그를 가진
+// 피어를 찾을 수 있다. 찾은 피어에는 연결까지 해서, 응답한 id로 바로 SendTo를 보낼 수 있다.
+//
+// 클라이언트는 TCP(127.0.0.1:P2P 포트 + 1)나 Unix 소켓으로 연결한다. Unix 소켓은 소유자만
+// 읽고 쓸 수 있는 권한(0600)으로 만들고, 소켓 파일 소유자와 다른 사용자의 연결은 거부한다.
+use crate::network::{Network, PeerEvent, TrafficClass};
+use crate::protocol::ProtocolError;
+use guild_discovery::{Discovery, NodeId};
+use guild_ipc::{accept_handshake, read_message, write_message, IPCMessage, PeerId};
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{mpsc, RwLock};
-
-pub type PeerId = [u8; 32];
-
-/// IPC 메시지
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum IPCMessage {
-    // Guild-Home -> Blockchain
-    PeerMessage { from: PeerId, data: Vec<u8> },
-    PeerJoined(PeerId),
-    PeerLeft(PeerId),
-    
-    // Blockchain -> Guild-Home
-    Broadcast(Vec<u8>),
-    SendTo { peer: PeerId, data: Vec<u8> },
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, RwLock};
+
+/// 애플리케이션 id -> 피어에게서 받은 그 애플리케이션의 메시지
+type AppChannels = Arc<Mutex<HashMap<String, broadcast::Sender<(SocketAddr, Vec<u8>)>>>>;
+
+/// 애플리케이션 메시지가 오가는 네트워크 프로토콜 id
+pub fn app_protocol(app: &str) -> String {
+    format!("guild/app/{}", app)
+}
+
+/// IPC 리스너 설정
+#[derive(Debug, Clone)]
+pub struct BridgeConfig {
+    /// 127.0.0.1:P2P 포트 + 1 에서 TCP 연결 수락
+    pub tcp: bool,
+    /// Unix 소켓 경로 (None이면 Unix 소켓을 열지 않음)
+    pub unix_socket: Option<PathBuf>,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            tcp: true,
+            unix_socket: None,
+        }
+    }
 }
 
 /// 블록체인 브리지
 pub struct BlockchainBridge {
     network: Arc<Network>,
+    config: BridgeConfig,
     ipc_listener: Option<TcpListener>,
     peer_map: Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+    apps: AppChannels,
+    discovery: Option<Arc<Discovery>>,
     ipc_port: u16,
+    ipc_socket: Option<PathBuf>,
 }
 
 impl BlockchainBridge {
-    /// IPC 포트 가져오기
+    /// IPC 포트 가져오기 (TCP 리스너가 없으면 0)
     pub fn get_ipc_port(&self) -> u16 {
         self.ipc_port
     }
-    /// 새 브리지 생성
+    
+    /// 대기 중인 Unix 소켓 경로
+    pub fn get_ipc_socket(&self) -> Option<&Path> {
+        self.ipc_socket.as_deref()
+    }
+    
+    /// 새 브리지 생성 (TCP 리스너만 사용)
     pub fn new(network: Arc<Network>) -> Self {
+        Self::with_config(network, BridgeConfig::default())
+    }
+    
+    pub fn with_config(network: Arc<Network>, config: BridgeConfig) -> Self {
         Self {
             network,
+            config,
             ipc_listener: None,
             peer_map: Arc::new(RwLock::new(HashMap::new())),
+            apps: Arc::new(Mutex::new(HashMap::new())),
+            discovery: None,
             ipc_port: 0,
+            ipc_socket: None,
         }
     }
     
+    /// FindPeers 요청에 쓸 탐색기 연결 (start 전에 호출, 없으면 FindPeers에 빈 목록으로 응답)
+    pub fn set_discovery(&mut self, discovery: Arc<Discovery>) {
+        self.discovery = Some(discovery);
+    }
+    
     /// 브리지 시작
     pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         guild_logger::log_info!("🌉 블록체인 브리지 시작...");
+        if !self.config.tcp && self.config.unix_socket.is_none() {
+            return Err("no IPC listener configured".into());
+        }
+        
+        // 피어 id 매핑은 한 태스크에서만 정리 (클라이언트마다 지우면 다른 클라이언트가 id를 잃음)
+        tokio::spawn(Self::track_peers(self.network.clone(), self.peer_map.clone()));
         
         // IPC 서버 시작 (P2P 포트 + 1 사용)
-        let p2p_port = self.network.local_port();
-        let ipc_port = p2p_port + 1;
-        self.ipc_port = ipc_port;
-        self.ipc_listener = Some(TcpListener::bind(("127.0.0.1", ipc_port)).await?);
-        guild_logger::log_info!("📡 IPC 서버 대기 중: 포트 {}", ipc_port);
-        guild_logger::log_info!("💡 minimal-blockchain을 다음 명령으로 실행하세요:");
-        guild_logger::log_info!("   cargo run --bin minimal-blockchain -- --port {}", ipc_port);
+        if self.config.tcp {
+            let p2p_port = self.network.local_port();
+            let ipc_port = p2p_port + 1;
+            self.ipc_listener = Some(TcpListener::bind(("127.0.0.1", ipc_port)).await?);
+            self.ipc_port = ipc_port;
+            guild_logger::log_info!("📡 IPC 서버 대기 중: 포트 {}", ipc_port);
+            guild_logger::log_info!("💡 minimal-blockchain을 다음 명령으로 실행하세요:");
+            guild_logger::log_info!("   cargo run --bin minimal-blockchain -- --port {}", ipc_port);
+        }
         
         // IPC 연결 대기 및 메시지 라우팅
         if let Some(listener) = self.ipc_listener.take() {
             let network = self.network.clone();
             let peer_map = self.peer_map.clone();
+            let apps = self.apps.clone();
+            let discovery = self.discovery.clone();
             
             tokio::spawn(async move {
-                Self::handle_ipc_connections(listener, network, peer_map).await;
+                Self::handle_ipc_connections(listener, network, peer_map, apps, discovery).await;
             });
         }
         
+        if let Some(path) = self.config.unix_socket.clone() {
+            self.start_unix(path).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Unix 소켓 IPC 서버 시작
+    #[cfg(unix)]
+    async fn start_unix(&mut self, path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        let (listener, owner) = Self::bind_unix(&path).await?;
+        self.ipc_socket = Some(path.clone());
+        let path_str = path.display().to_string();
+        let hint_path = path_str.clone();
+        guild_logger::log_info!("📡 IPC 서버 대기 중: {}", path_str);
+        guild_logger::log_info!("💡 minimal-blockchain을 다음 명령으로 실행하세요:");
+        guild_logger::log_info!("   cargo run --bin minimal-blockchain -- --socket {}", hint_path);
+        
+        let network = self.network.clone();
+        let peer_map = self.peer_map.clone();
+        let apps = self.apps.clone();
+        let discovery = self.discovery.clone();
+        tokio::spawn(async move {
+            Self::handle_unix_connections(listener, owner, network, peer_map, apps, discovery).await;
+        });
         Ok(())
     }
     
+    #[cfg(not(unix))]
+    async fn start_unix(&mut self, _path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        Err("Unix socket IPC is not supported on this platform".into())
+    }
+    
+    /// 소켓 파일을 만들고 소유자만 접근할 수 있게 권한 설정 (소켓 파일 소유자 uid 반환)
+    ///
+    /// 이전 실행이 남긴 소켓 파일은 지우지만, 다른 프로세스가 대기 중이거나 소켓이 아닌
+    /// 파일이 있으면 에러를 반환한다.
+    #[cfg(unix)]
+    async fn bind_unix(path: &Path) -> std::io::Result<(UnixListener, u32)> {
+        use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+        
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        if let Ok(metadata) = tokio::fs::symlink_metadata(path).await {
+            if !metadata.file_type().is_socket() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ));
+            }
+            if UnixStream::connect(path).await.is_ok() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AddrInUse,
+                    format!("{} is in use by another process", path.display()),
+                ));
+            }
+            tokio::fs::remove_file(path).await?;
+        }
+        
+        let listener = UnixListener::bind(path)?;
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).await?;
+        let owner = tokio::fs::metadata(path).await?.uid();
+        Ok((listener, owner))
+    }
+    
     /// IPC 연결 처리
     async fn handle_ipc_connections(
         listener: TcpListener,
         network: Arc<Network>,
         peer_map: Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        apps: AppChannels,
+        discovery: Option<Arc<Discovery>>,
     ) {
         loop {
             match listener.accept().await {
@@ -85,9 +212,11 @@ impl BlockchainBridge {
                     
                     let net = network.clone();
                     let map = peer_map.clone();
+                    let apps = apps.clone();
+                    let discovery = discovery.clone();
                     
                     tokio::spawn(async move {
-                        Self::handle_blockchain_connection(stream, net, map).await;
+                        Self::handle_blockchain_connection(stream, net, map, apps, discovery).await;
                     });
                 }
                 Err(e) => {
@@ -97,110 +226,548 @@ impl BlockchainBridge {
         }
     }
     
-    /// 블록체인 연결 처리
-    async fn handle_blockchain_connection(
-        mut stream: TcpStream,
+    /// Unix 소켓 연결 처리
+    ///
+    /// 권한을 설정하기 전에 들어온 연결이 있을 수 있으므로 상대 프로세스의 uid도 확인한다.
+    #[cfg(unix)]
+    async fn handle_unix_connections(
+        listener: UnixListener,
+        owner: u32,
         network: Arc<Network>,
         peer_map: Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        apps: AppChannels,
+        discovery: Option<Arc<Discovery>>,
     ) {
-        let (tx, mut rx) = mpsc::channel::<Vec<u8>>(100);
-        
-        // 네트워크 메시지 수신 태스크
-        let _net_clone = network.clone();
-        let _tx_clone = tx.clone();
-        tokio::spawn(async move {
-            // 네트워크에서 메시지 수신하여 블록체인으로 전달
-            // (실제 구현 필요)
-        });
-        
         loop {
-            tokio::select! {
-                // 블록체인에서 메시지 수신
-                result = Self::read_message(&mut stream) => {
-                    match result {
-                        Ok(msg) => {
-                            Self::handle_blockchain_message(msg, &network, &peer_map).await;
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let uid = stream.peer_cred().map(|cred| cred.uid());
+                    match uid {
+                        Ok(uid) if uid == owner => {}
+                        Ok(uid) => {
+                            guild_logger::log_error!("IPC 연결 거부: uid {} (소켓 소유자 {})", uid, owner);
+                            continue;
                         }
                         Err(e) => {
-                            let err_msg = e.to_string();
-                    guild_logger::log_error!("블록체인 연결 끊김: {}", err_msg);
-                            break;
+                            guild_logger::log_error!("IPC 연결 거부: 상대 확인 실패 ({})", e);
+                            continue;
                         }
                     }
+                    guild_logger::log_info!("📞 블록체인 프로세스 연결: unix (uid {})", owner);
+                    
+                    let net = network.clone();
+                    let map = peer_map.clone();
+                    let apps = apps.clone();
+                    let discovery = discovery.clone();
+                    
+                    tokio::spawn(async move {
+                        Self::handle_blockchain_connection(stream, net, map, apps, discovery).await;
+                    });
                 }
-                
-                // 네트워크 메시지를 블록체인으로 전달
-                Some(data) = rx.recv() => {
-                    if let Err(e) = Self::write_message(&mut stream, &data).await {
-                        let err_msg = e.to_string();
-                        guild_logger::log_error!("블록체인 전송 실패: {}", err_msg);
-                        break;
+                Err(e) => {
+                    guild_logger::log_error!("IPC 연결 실패: {}", e);
+                }
+            }
+        }
+    }
+    
+    /// 블록체인 연결 처리 (TCP, Unix 소켓 공통)
+    async fn handle_blockchain_connection<S>(
+        mut stream: S,
+        network: Arc<Network>,
+        peer_map: Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        apps: AppChannels,
+        discovery: Option<Arc<Discovery>>,
+    ) where
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        // 버전 확인과 애플리케이션 등록 (실패하면 Error를 보내고 끊음)
+        let app = match accept_handshake(&mut stream).await {
+            Ok(app) => app,
+            Err(e) => {
+                let err_msg = e.to_string();
+                guild_logger::log_error!("IPC 핸드셰이크 실패: {}", err_msg);
+                return;
+            }
+        };
+        
+        // 이 클라이언트가 받을 피어 메시지
+        let data_rx = match &app {
+            Some(app) => match Self::subscribe_app(&network, &apps, app) {
+                Ok(rx) => rx,
+                Err(e) => {
+                    let err_msg = e.to_string();
+                    guild_logger::log_error!("애플리케이션 등록 실패: {}", err_msg);
+                    return;
+                }
+            },
+            None => network.subscribe_data(),
+        };
+        let app_name = app.clone().unwrap_or_else(|| "-".to_string());
+        guild_logger::log_info!("📱 IPC 애플리케이션 등록: {}", app_name);
+        
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        let (tx, mut rx) = mpsc::channel::<IPCMessage>(100);
+        
+        // 네트워크 메시지와 피어 연결/종료를 블록체인으로 전달
+        let forward_task = tokio::spawn(Self::forward_network_events(
+            network.clone(),
+            peer_map.clone(),
+            data_rx,
+            tx.clone(),
+        ));
+        
+        // 블록체인으로 보내는 메시지는 한 태스크에서만 씀 (읽기와 독립적으로 진행)
+        let write_task = tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                if let Err(e) = write_message(&mut writer, &msg).await {
+                    let err_msg = e.to_string();
+                    guild_logger::log_error!("블록체인 전송 실패: {}", err_msg);
+                    break;
+                }
+            }
+        });
+        
+        // 블록체인에서 메시지 수신
+        loop {
+            match read_message(&mut reader).await {
+                Ok(msg) => {
+                    Self::handle_blockchain_message(
+                        msg,
+                        app.as_deref(),
+                        &network,
+                        &peer_map,
+                        discovery.as_deref(),
+                        &tx,
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    let err_msg = e.to_string();
+                    guild_logger::log_error!("블록체인 연결 끊김: {}", err_msg);
+                    break;
+                }
+            }
+        }
+        forward_task.abort();
+        write_task.abort();
+    }
+    
+    /// 애플리케이션 메시지 구독 (처음 등록되는 id면 네트워크 프로토콜 핸들러도 등록)
+    ///
+    /// 핸들러는 마지막 클라이언트가 끊겨도 남겨 둔다. 제거하면 그 사이에 메시지를 보낸 피어가
+    /// 이 노드를 미지원으로 기록해서, 애플리케이션이 다시 연결해도 메시지를 받지 못한다.
+    fn subscribe_app(
+        network: &Arc<Network>,
+        apps: &AppChannels,
+        app: &str,
+    ) -> Result<broadcast::Receiver<(SocketAddr, Vec<u8>)>, ProtocolError> {
+        let mut apps = apps.lock().unwrap();
+        if let Some(sender) = apps.get(app) {
+            return Ok(sender.subscribe());
+        }
+        
+        let (sender, rx) = broadcast::channel(1024);
+        let handler_sender = sender.clone();
+        network.register_message_handler(
+            &app_protocol(app),
+            move |from: SocketAddr, data: Vec<u8>| {
+                // 연결된 클라이언트가 없으면 send가 실패하지만 무시
+                let _ = handler_sender.send((from, data));
+                async {}
+            },
+        )?;
+        apps.insert(app.to_string(), sender);
+        Ok(rx)
+    }
+    
+    /// 피어 연결/종료 이벤트로 peer_map 갱신 (브리지마다 하나만 실행)
+    ///
+    /// 끊긴 피어의 매핑은 같은 노드를 가리킬 때만 지운다 (그 사이 다른 노드가 같은 주소로
+    /// 연결했을 수 있으므로).
+    async fn track_peers(network: Arc<Network>, peer_map: Arc<RwLock<HashMap<SocketAddr, PeerId>>>) {
+        let mut events_rx = network.subscribe_peer_events();
+        loop {
+            match events_rx.recv().await {
+                Ok(PeerEvent::Connected(addr, node_id)) => {
+                    peer_map.write().await.insert(addr, node_id.0);
+                }
+                Ok(PeerEvent::Disconnected(addr, node_id)) => {
+                    let mut map = peer_map.write().await;
+                    if map.get(&addr) == Some(&node_id.0) {
+                        map.remove(&addr);
                     }
                 }
+                Err(RecvError::Lagged(skipped)) => {
+                    // 놓친 이벤트 대신 현재 연결된 피어로 다시 채움
+                    guild_logger::log_error!("피어 id 매핑 지연: 피어 이벤트 {}개 누락", skipped);
+                    let peers = network.get_peers_info().await;
+                    *peer_map.write().await = peers
+                        .into_iter()
+                        .filter_map(|(addr, info)| Some((addr, info.node_id?.0)))
+                        .collect();
+                }
+                Err(RecvError::Closed) => break,
             }
         }
     }
     
-    /// 메시지 읽기
-    async fn read_message(stream: &mut TcpStream) -> Result<IPCMessage, Box<dyn std::error::Error + Send + Sync>> {
-        let mut len_bytes = [0u8; 4];
-        stream.read_exact(&mut len_bytes).await?;
-        let len = u32::from_be_bytes(len_bytes) as usize;
+    /// 네트워크 → 블록체인 전달 (PeerMessage, PeerJoined, PeerLeft)
+    ///
+    /// 연결 직후 이미 연결된 피어들을 PeerJoined로 먼저 알린다. 발신자 id는 GetPeers,
+    /// SendTo와 같은 peer_map 매핑을 쓰므로 블록체인이 받은 id로 바로 응답할 수 있다.
+    /// PeerJoined와 PeerLeft는 이벤트에 담긴 노드 id를 그대로 쓴다 (모든 클라이언트가 같은 id를 받음).
+    async fn forward_network_events(
+        network: Arc<Network>,
+        peer_map: Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        mut data_rx: broadcast::Receiver<(SocketAddr, Vec<u8>)>,
+        tx: mpsc::Sender<IPCMessage>,
+    ) {
+        // 기존 피어 목록을 보내는 동안의 이벤트를 놓치지 않도록 먼저 구독
+        let mut events_rx = network.subscribe_peer_events();
         
-        let mut buffer = vec![0u8; len];
-        stream.read_exact(&mut buffer).await?;
+        for (addr, _) in network.get_peers_info().await {
+            let peer = Self::peer_id_for(&network, &peer_map, addr).await;
+            if tx.send(IPCMessage::PeerJoined(peer)).await.is_err() {
+                return;
+            }
+        }
         
-        let msg = bincode::deserialize(&buffer)?;
-        Ok(msg)
+        loop {
+            let msg = tokio::select! {
+                result = data_rx.recv() => match result {
+                    Ok((addr, data)) => IPCMessage::PeerMessage {
+                        from: Self::peer_id_for(&network, &peer_map, addr).await,
+                        data,
+                    },
+                    Err(RecvError::Lagged(skipped)) => {
+                        guild_logger::log_error!("블록체인 전달 지연: 피어 메시지 {}개 누락", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                result = events_rx.recv() => match result {
+                    Ok(PeerEvent::Connected(_, node_id)) => IPCMessage::PeerJoined(node_id.0),
+                    Ok(PeerEvent::Disconnected(_, node_id)) => IPCMessage::PeerLeft(node_id.0),
+                    Err(RecvError::Lagged(skipped)) => {
+                        guild_logger::log_error!("블록체인 전달 지연: 피어 이벤트 {}개 누락", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            if tx.send(msg).await.is_err() {
+                break;
+            }
+        }
     }
     
-    /// 메시지 쓰기
-    async fn write_message(stream: &mut TcpStream, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
-        let len = data.len() as u32;
-        stream.write_all(&len.to_be_bytes()).await?;
-        stream.write_all(data).await?;
-        stream.flush().await?;
-        Ok(())
+    /// 피어 주소에 대응하는 IPC 피어 id
+    ///
+    /// 핸드셰이크에서 확인한 노드 id를 쓰고, 신원을 알 수 없을 때만 주소 해시로 대신한다
+    /// (주소 해시는 기록하지 않으므로 나중에 신원이 확인되면 노드 id로 바뀜).
+    async fn peer_id_for(
+        network: &Network,
+        peer_map: &Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        addr: SocketAddr,
+    ) -> PeerId {
+        if let Some(id) = peer_map.read().await.get(&addr) {
+            return *id;
+        }
+        match network.peer_node_id(addr).await {
+            Some(node_id) => {
+                peer_map.write().await.insert(addr, node_id.0);
+                node_id.0
+            }
+            None => NodeId::from_addr(&addr).0,
+        }
     }
     
-    /// 블록체인 메시지 처리
+    /// 블록체인 메시지 처리 (app이 있으면 그 애플리케이션 프로토콜로 전송)
     async fn handle_blockchain_message(
         msg: IPCMessage,
+        app: Option<&str>,
         network: &Arc<Network>,
-        _peer_map: &Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        peer_map: &Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        discovery: Option<&Discovery>,
+        reply: &mpsc::Sender<IPCMessage>,
     ) {
         match msg {
             IPCMessage::Broadcast(data) => {
                 let data_len = data.len();
                 guild_logger::log_network!("📢 블록체인 브로드캐스트: {} bytes", data_len);
                 
-                // 모든 피어에게 전송
-                network.broadcast(&data).await;
+                // 모든 피어에게 전송 (컨센서스 클래스 - 대용량 전송에 밀리지 않음)
+                let report = match app {
+                    Some(app) => {
+                        network.broadcast_protocol(&app_protocol(app), &data, TrafficClass::Consensus).await
+                    }
+                    None => network.broadcast_with_class(&data, TrafficClass::Consensus).await,
+                };
+                if !report.is_complete() {
+                    let failed = report.failed.len();
+                    let delivered = report.delivered.len();
+                    guild_logger::log_network!("⚠️ 브로드캐스트 일부 실패: {} 성공, {} 실패", delivered, failed);
+                }
             }
             
             IPCMessage::SendTo { peer, data } => {
                 let data_len = data.len();
                 guild_logger::log_network!("📤 블록체인 메시지 전송: {} bytes", data_len);
                 
-                // 특정 피어에게 전송 (현재는 브로드캐스트로 대체)
-                // TODO: 특정 피어에게만 전송하는 기능 구현 필요
-                let _ = peer; // unused warning 제거
-                network.broadcast(&data).await;
+                let Some(addr) = Self::find_peer_address(network, peer_map, peer).await else {
+                    guild_logger::log_network!("⚠️ 알 수 없는 피어: {}", NodeId(peer).to_hex());
+                    return;
+                };
+                let result = match app {
+                    Some(app) => {
+                        network.send_protocol(addr, &app_protocol(app), &data, TrafficClass::Consensus).await
+                    }
+                    None => network.send_with_class(addr, &data, TrafficClass::Consensus).await,
+                };
+                if let Err(e) = result {
+                    let err_msg = e.to_string();
+                    guild_logger::log_network!("⚠️ 전송 실패: {}", err_msg);
+                }
+            }
+            
+            IPCMessage::GetPeers => {
+                let mut peers = Vec::new();
+                for (addr, _) in network.get_peers_info().await {
+                    peers.push(Self::peer_id_for(network, peer_map, addr).await);
+                }
+                let _ = reply.send(IPCMessage::PeerList(peers)).await;
+            }
+            
+            IPCMessage::FindPeers { capability, count } => {
+                let peers = match discovery {
+                    Some(discovery) => {
+                        Self::connect_capable_peers(network, peer_map, discovery, &capability, count as usize).await
+                    }
+                    None => Vec::new(),
+                };
+                let _ = reply.send(IPCMessage::PeerList(peers)).await;
             }
             
             _ => {}
         }
     }
     
-    /// 피어 ID로 주소 찾기
+    /// capability 태그를 가진 피어를 찾아 연결하고, 연결된 피어의 id 반환
+    ///
+    /// 아직 연결하지 않은 피어는 광고한 노드 id로 핸드셰이크를 확인하며 연결한다 (다른 노드가
+    /// 그 주소에 있으면 결과에서 뺌). 연결 결과는 탐색기에도 기록한다.
+    async fn connect_capable_peers(
+        network: &Network,
+        peer_map: &Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
+        discovery: &Discovery,
+        capability: &str,
+        count: usize,
+    ) -> Vec<PeerId> {
+        let mut peers = Vec::new();
+        for peer in discovery.find_peers_with_capability(capability, count).await {
+            match network.peer_node_id(peer.addr).await {
+                Some(id) if peer.node_id.is_none_or(|expected| expected == id) => {}
+                Some(_) => continue,
+                None => match network.connect_expecting(peer.addr, peer.node_id).await {
+                    Ok(()) => discovery.add_peer(peer.addr).await,
+                    Err(e) => {
+                        discovery.record_failure(peer.addr).await;
+                        let tag = capability.to_string();
+                        let addr = peer.addr;
+                        let err_msg = e.to_string();
+                        guild_logger::log_network!("⚠️ {} 피어 연결 실패 {}: {}", tag, addr, err_msg);
+                        continue;
+                    }
+                },
+            }
+            peers.push(Self::peer_id_for(network, peer_map, peer.addr).await);
+        }
+        peers
+    }
+    
+    /// 피어 ID로 주소 찾기 (peer_map에 아직 없으면 연결된 피어의 노드 id에서 찾음)
     async fn find_peer_address(
+        network: &Network,
         peer_map: &Arc<RwLock<HashMap<SocketAddr, PeerId>>>,
         peer_id: PeerId,
     ) -> Option<SocketAddr> {
-        let map = peer_map.read().await;
-        map.iter()
+        let mapped = peer_map
+            .read()
+            .await
+            .iter()
             .find(|(_, id)| **id == peer_id)
-            .map(|(addr, _)| *addr)
+            .map(|(addr, _)| *addr);
+        if mapped.is_some() {
+            return mapped;
+        }
+        network
+            .get_peers_info()
+            .await
+            .into_iter()
+            .find(|(_, info)| info.node_id.map(|id| id.0) == Some(peer_id))
+            .map(|(addr, _)| addr)
     }
     
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::memory_transport::MemoryNetwork;
+    use crate::network::NetworkConfig;
+    use guild_ipc::IPCClient;
+    use std::time::Duration;
+
+    async fn node(net: &MemoryNetwork, addr: &str) -> Arc<Network> {
+        guild_logger::init_logger(true);
+        let transport = net.bind(addr.parse().unwrap());
+        Arc::new(Network::with_transport(Arc::new(transport), NetworkConfig::default()).await)
+    }
+
+    /// Unix 소켓으로만 받는 브리지 시작 (반환한 브리지를 버리면 안 됨)
+    async fn bridge(network: &Arc<Network>, name: &str) -> (BlockchainBridge, PathBuf) {
+        let socket = std::env::temp_dir().join(format!("guild_home_{}_{}.sock", name, std::process::id()));
+        let mut bridge = BlockchainBridge::with_config(
+            network.clone(),
+            BridgeConfig {
+                tcp: false,
+                unix_socket: Some(socket.clone()),
+            },
+        );
+        bridge.start().await.unwrap();
+        (bridge, socket)
+    }
+
+    async fn recv(client: &IPCClient) -> IPCMessage {
+        tokio::time::timeout(Duration::from_secs(5), client.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn ipc_clients_see_the_same_id_when_a_peer_joins_and_leaves() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let (_bridge, socket) = bridge(&a, "bridge_events").await;
+
+        a.connect(b_addr).await.unwrap();
+        let b_id = b.node_id().0;
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            let client = IPCClient::connect_unix(&socket, None).await.unwrap();
+            // 이미 연결된 피어는 구독 후 PeerJoined로 알려짐
+            assert_eq!(recv(&client).await, IPCMessage::PeerJoined(b_id));
+            clients.push(client);
+        }
+
+        // 모든 클라이언트가 PeerJoined와 같은 id로 PeerLeft를 받음
+        a.disconnect(b_addr).await;
+        for client in &clients {
+            assert_eq!(recv(client).await, IPCMessage::PeerLeft(b_id));
+        }
+
+        std::fs::remove_file(&socket).ok();
+    }
+
+    #[tokio::test]
+    async fn clients_only_receive_their_own_app_messages() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let (_bridge, socket) = bridge(&a, "bridge_apps").await;
+
+        b.connect(a_addr).await.unwrap();
+        let b_id = b.node_id().0;
+        let chat = IPCClient::connect_unix(&socket, Some("chat")).await.unwrap();
+        let game = IPCClient::connect_unix(&socket, Some("game")).await.unwrap();
+        for client in [&chat, &game] {
+            assert_eq!(recv(client).await, IPCMessage::PeerJoined(b_id));
+        }
+
+        for app in ["chat", "game"] {
+            b.send_protocol(a_addr, &app_protocol(app), app.as_bytes(), TrafficClass::Consensus)
+                .await
+                .unwrap();
+        }
+        for (client, app) in [(&chat, "chat"), (&game, "game")] {
+            let expected = IPCMessage::PeerMessage {
+                from: b_id,
+                data: app.as_bytes().to_vec(),
+            };
+            assert_eq!(recv(client).await, expected);
+            // 다른 애플리케이션의 메시지는 오지 않음
+            let more = tokio::time::timeout(Duration::from_millis(100), client.recv()).await;
+            assert!(more.is_err());
+        }
+
+        std::fs::remove_file(&socket).ok();
+    }
+
+    #[tokio::test]
+    async fn untagged_send_to_reaches_only_that_peer() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let c = node(&net, "10.0.0.3:42000").await;
+        let (_bridge, socket) = bridge(&a, "bridge_send_to").await;
+        a.connect("10.0.0.2:42000".parse().unwrap()).await.unwrap();
+        a.connect("10.0.0.3:42000".parse().unwrap()).await.unwrap();
+        let mut b_data = b.subscribe_data();
+        let mut c_data = c.subscribe_data();
+
+        // 애플리케이션 id 없이 등록한 클라이언트도 지정한 피어에게만 보냄
+        let client = IPCClient::connect_unix(&socket, None).await.unwrap();
+        client.send_to(b.node_id().0, vec![1, 2, 3]).await.unwrap();
+        let (_, data) = tokio::time::timeout(Duration::from_secs(5), b_data.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data, vec![1, 2, 3]);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(c_data.try_recv().is_err());
+
+        std::fs::remove_file(&socket).ok();
+    }
+
+    #[tokio::test]
+    async fn socket_is_only_accessible_by_its_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let (_bridge, socket) = bridge(&a, "bridge_mode").await;
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::remove_file(&socket).ok();
+    }
+
+    #[tokio::test]
+    async fn stale_socket_is_replaced_but_other_files_are_kept() {
+        let socket = std::env::temp_dir().join(format!(
+            "guild_home_bridge_stale_{}.sock",
+            std::process::id()
+        ));
+        std::fs::remove_file(&socket).ok();
+
+        // 이전 실행이 남긴 소켓 (대기 중인 프로세스 없음)
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        let (listener, _) = BlockchainBridge::bind_unix(&socket).await.unwrap();
+        assert!(UnixStream::connect(&socket).await.is_ok());
+        drop(listener);
+        std::fs::remove_file(&socket).unwrap();
+
+        // 소켓이 아닌 파일은 지우지 않고 에러
+        std::fs::write(&socket, b"data").unwrap();
+        let error = BlockchainBridge::bind_unix(&socket).await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(&socket).unwrap(), b"data");
+
+        std::fs::remove_file(&socket).ok();
+    }
+}
@@ -1,11 +1,48 @@
 use std::env;
-use guild_discovery::DEFAULT_PORT;
+use std::ops::RangeInclusive;
+use guild_discovery::{
+    parse_cidr_hosts, validate_capabilities, validate_network_id, BootstrapEntry, ScanConfig,
+    DEFAULT_NETWORK_ID, DEFAULT_PORT, MAX_PROBES_PER_SEC,
+};
 
 #[derive(Debug)]
 pub enum ConfigError {
     InvalidPort(String),
     InvalidBlockTime(String),
     InvalidBootstrap(String),
+    InvalidNetworkId(String),
+    InvalidCapabilities(String),
+    InvalidIpcMode(String),
+    InvalidScan(String),
+}
+
+/// 로컬 애플리케이션이 브리지에 연결하는 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpcMode {
+    /// 127.0.0.1:P2P 포트 + 1
+    Tcp,
+    /// data_dir의 Unix 소켓 (소유자만 접근 가능)
+    Unix,
+    Both,
+}
+
+impl IpcMode {
+    pub fn parse(value: &str) -> Result<Self, ConfigError> {
+        match value {
+            "tcp" => Ok(IpcMode::Tcp),
+            "unix" => Ok(IpcMode::Unix),
+            "both" => Ok(IpcMode::Both),
+            _ => Err(ConfigError::InvalidIpcMode(value.to_string())),
+        }
+    }
+    
+    pub fn tcp(self) -> bool {
+        self != IpcMode::Unix
+    }
+    
+    pub fn unix(self) -> bool {
+        self != IpcMode::Tcp
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +52,21 @@ pub struct Config {
     pub data_dir: String,
     pub heartbeat_interval: u64,
     pub log_level: String,
+    pub datagram_pings: bool,
+    /// 송수신 트래픽을 data_dir/recordings 아래에 녹화
+    pub record_traffic: bool,
+    /// 같은 network id를 가진 노드끼리만 연결 (길드 이름, 제네시스 해시 등)
+    pub network_id: String,
+    /// 광고할 역할 태그 (validator, relay, archive, member 등)
+    pub capabilities: Vec<String>,
+    /// IPC 리스너 (TCP, Unix 소켓, 둘 다)
+    pub ipc_mode: IpcMode,
+    /// LAN 스캔에서 직접 probe할 IPv4 대역 (비어 있으면 브로드캐스트/멀티캐스트만)
+    pub scan_cidrs: Vec<String>,
+    /// 대역의 각 주소에서 probe할 비컨 포트 범위
+    pub scan_ports: RangeInclusive<u16>,
+    /// 초당 보내는 최대 probe 수
+    pub scan_rate: u32,
 }
 
 impl Default for Config {
@@ -25,6 +77,14 @@ impl Default for Config {
             data_dir: "./data".to_string(),
             heartbeat_interval: 5,
             log_level: "info".to_string(),
+            datagram_pings: false,
+            record_traffic: false,
+            network_id: DEFAULT_NETWORK_ID.to_string(),
+            capabilities: vec![],
+            ipc_mode: IpcMode::Tcp,
+            scan_cidrs: vec![],
+            scan_ports: ScanConfig::default().probe_ports,
+            scan_rate: ScanConfig::default().max_probes_per_sec,
         }
     }
 }
@@ -61,13 +121,9 @@ impl Config {
                         return Err(ConfigError::InvalidBootstrap("Missing bootstrap value".to_string()));
                     }
                 }
-                "--data-dir" | "-d" => {
-                    if i + 1 < args.len() {
-                        config.data_dir = args[i + 1].clone();
-                        i += 2;
-                    } else {
-                        i += 1;
-                    }
+                "--data-dir" | "-d" if i + 1 < args.len() => {
+                    config.data_dir = args[i + 1].clone();
+                    i += 2;
                 }
                 "--interval" | "-i" => {
                     if i + 1 < args.len() {
@@ -81,18 +137,71 @@ impl Config {
                         return Err(ConfigError::InvalidBlockTime("Missing heartbeat interval value".to_string()));
                     }
                 }
-                "--log" | "-l" => {
+                "--log" | "-l" if i + 1 < args.len() => {
+                    let log_level = &args[i + 1];
+                    if !["error", "warn", "info", "debug"].contains(&log_level.as_str()) {
+                        eprintln!("Warning: Invalid log level '{}', using 'info'", log_level);
+                        config.log_level = "info".to_string();
+                    } else {
+                        config.log_level = log_level.clone();
+                    }
+                    i += 2;
+                }
+                "--datagram-pings" => {
+                    config.datagram_pings = true;
+                    i += 1;
+                }
+                "--record" => {
+                    config.record_traffic = true;
+                    i += 1;
+                }
+                "--capabilities" | "-c" => {
                     if i + 1 < args.len() {
-                        let log_level = &args[i + 1];
-                        if !["error", "warn", "info", "debug"].contains(&log_level.as_str()) {
-                            eprintln!("Warning: Invalid log level '{}', using 'info'", log_level);
-                            config.log_level = "info".to_string();
-                        } else {
-                            config.log_level = log_level.clone();
-                        }
+                        config.capabilities = parse_list(&args[i + 1]);
+                        i += 2;
+                    } else {
+                        return Err(ConfigError::InvalidCapabilities("Missing capabilities value".to_string()));
+                    }
+                }
+                "--network" | "-n" => {
+                    if i + 1 < args.len() {
+                        config.network_id = args[i + 1].clone();
+                        i += 2;
+                    } else {
+                        return Err(ConfigError::InvalidNetworkId("Missing network id value".to_string()));
+                    }
+                }
+                "--ipc" => {
+                    if i + 1 < args.len() {
+                        config.ipc_mode = IpcMode::parse(&args[i + 1])?;
                         i += 2;
                     } else {
-                        i += 1;
+                        return Err(ConfigError::InvalidIpcMode("Missing IPC mode value".to_string()));
+                    }
+                }
+                "--scan-cidr" => {
+                    if i + 1 < args.len() {
+                        config.scan_cidrs = parse_list(&args[i + 1]);
+                        i += 2;
+                    } else {
+                        return Err(ConfigError::InvalidScan("Missing scan CIDR value".to_string()));
+                    }
+                }
+                "--scan-ports" => {
+                    if i + 1 < args.len() {
+                        config.scan_ports = parse_port_range(&args[i + 1])?;
+                        i += 2;
+                    } else {
+                        return Err(ConfigError::InvalidScan("Missing scan port range".to_string()));
+                    }
+                }
+                "--scan-rate" => {
+                    if i + 1 < args.len() {
+                        config.scan_rate = args[i + 1].parse()
+                            .map_err(|_| ConfigError::InvalidScan(format!("Invalid scan rate: {}", args[i + 1])))?;
+                        i += 2;
+                    } else {
+                        return Err(ConfigError::InvalidScan("Missing scan rate value".to_string()));
                     }
                 }
                 "--help" | "-h" => {
@@ -107,10 +216,50 @@ impl Config {
         
         // 환경변수도 체크 (CLI가 우선순위 높음)
         config.load_from_env()?;
+        config.validate_bootstrap()?;
+        config.validate()?;
         
         Ok(config)
     }
 
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_network_id(&self.network_id).map_err(ConfigError::InvalidNetworkId)?;
+        validate_capabilities(&self.capabilities).map_err(ConfigError::InvalidCapabilities)?;
+        self.validate_scan()
+    }
+    
+    fn validate_scan(&self) -> Result<(), ConfigError> {
+        for cidr in &self.scan_cidrs {
+            parse_cidr_hosts(cidr)
+                .map_err(|e| ConfigError::InvalidScan(format!("{}: {}", cidr, e)))?;
+        }
+        if self.scan_rate == 0 || self.scan_rate > MAX_PROBES_PER_SEC {
+            return Err(ConfigError::InvalidScan(format!(
+                "Scan rate must be between 1 and {} probes per second", MAX_PROBES_PER_SEC
+            )));
+        }
+        Ok(())
+    }
+    
+    /// LAN 스캔 설정 (CIDR, 포트 범위, 속도)
+    pub fn scan_config(&self) -> ScanConfig {
+        ScanConfig {
+            probe_cidrs: self.scan_cidrs.clone(),
+            probe_ports: self.scan_ports.clone(),
+            max_probes_per_sec: self.scan_rate,
+            ..ScanConfig::default()
+        }
+    }
+
+    /// 부트스트랩 항목 형식 검사 (호스트 이름은 시작 후 DNS로 조회)
+    fn validate_bootstrap(&self) -> Result<(), ConfigError> {
+        for entry in &self.bootstrap {
+            BootstrapEntry::parse(entry)
+                .map_err(|e| ConfigError::InvalidBootstrap(e.to_string()))?;
+        }
+        Ok(())
+    }
+
     fn load_from_env(&mut self) -> Result<(), ConfigError> {
         if self.port == DEFAULT_PORT {  // 기본값인 경우에만 환경변수 체크
             if let Ok(port_str) = env::var("GUILD_PORT") {
@@ -150,6 +299,55 @@ impl Config {
                 self.log_level = log_level;
             }
         }
+
+        if let Ok(value) = env::var("GUILD_DATAGRAM_PINGS") {
+            if value == "1" || value == "true" {
+                self.datagram_pings = true;
+            }
+        }
+
+        if let Ok(value) = env::var("GUILD_RECORD") {
+            if value == "1" || value == "true" {
+                self.record_traffic = true;
+            }
+        }
+
+        if self.network_id == DEFAULT_NETWORK_ID {
+            if let Ok(network_id) = env::var("GUILD_NETWORK") {
+                self.network_id = network_id;
+            }
+        }
+
+        if self.capabilities.is_empty() {
+            if let Ok(capabilities) = env::var("GUILD_CAPABILITIES") {
+                self.capabilities = parse_list(&capabilities);
+            }
+        }
+
+        if self.ipc_mode == IpcMode::Tcp {
+            if let Ok(mode) = env::var("GUILD_IPC") {
+                self.ipc_mode = IpcMode::parse(&mode)?;
+            }
+        }
+
+        if self.scan_cidrs.is_empty() {
+            if let Ok(cidrs) = env::var("GUILD_SCAN_CIDR") {
+                self.scan_cidrs = parse_list(&cidrs);
+            }
+        }
+
+        if self.scan_ports == Config::default().scan_ports {
+            if let Ok(ports) = env::var("GUILD_SCAN_PORTS") {
+                self.scan_ports = parse_port_range(&ports)?;
+            }
+        }
+
+        if self.scan_rate == Config::default().scan_rate {
+            if let Ok(rate) = env::var("GUILD_SCAN_RATE") {
+                self.scan_rate = rate.parse()
+                    .map_err(|_| ConfigError::InvalidScan(format!("Invalid GUILD_SCAN_RATE: {}", rate)))?;
+            }
+        }
         
         Ok(())
     }
@@ -157,6 +355,31 @@ impl Config {
     pub fn from_env() -> Result<Self, ConfigError> {
         let mut config = Config::default();
         config.load_from_env()?;
+        config.validate_bootstrap()?;
+        config.validate()?;
         Ok(config)
     }
-}
\ No newline at end of file
+}
+
+// 쉼표로 구분된 목록 (빈 항목 제외)
+fn parse_list(value: &str) -> Vec<String> {
+    value.split(',')
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().to_string())
+        .collect()
+}
+
+// "41999" 또는 "41999-42010" 형식의 포트 범위
+fn parse_port_range(value: &str) -> Result<RangeInclusive<u16>, ConfigError> {
+    let invalid = || ConfigError::InvalidScan(format!("Invalid scan port range: {}", value));
+    let (start, end) = match value.trim().split_once('-') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => (value.trim(), value.trim()),
+    };
+    let start: u16 = start.parse().map_err(|_| invalid())?;
+    let end: u16 = end.parse().map_err(|_| invalid())?;
+    if start == 0 || start > end {
+        return Err(invalid());
+    }
+    Ok(start..=end)
+}
@@ -0,0 +1,234 @@
+// DHT RPC 서비스 - Kademlia 요청/응답을 Network 프로토콜(guild/kad/1) 위로 전달
+//
+// 요청을 보낼 때는 request_id별로 대기 중인 oneshot을 등록해 두고, 같은 id의 응답이
+// 요청한 주소에서 도착하면 깨운다. 다른 노드의 요청은 로컬 Kademlia가 처리해서 같은
+// 프로토콜로 응답한다. 연결되어 있지 않은 노드에게 요청하면 먼저 연결한다.
+//
+// 봉투의 sender는 보낸 쪽이 마음대로 적을 수 있으므로, 핸드셰이크에서 서명으로 확인한
+// 노드 id와 다르면 요청과 응답을 버린다. 라우팅 테이블에는 확인된 id만 들어간다.
+use async_trait::async_trait;
+use guild_discovery::{
+    DhtBody, DhtEnvelope, DhtError, DhtRequest, DhtResponse, DhtTransport, Kademlia, NodeId,
+    DHT_PROTOCOL,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::oneshot;
+
+use crate::log_network;
+use crate::network::{Network, TrafficClass};
+use crate::protocol::ProtocolError;
+
+type PendingMap = Mutex<HashMap<u64, (SocketAddr, oneshot::Sender<(NodeId, DhtResponse)>)>>;
+
+pub struct DhtService {
+    network: Arc<Network>,
+    local_id: NodeId,
+    next_request_id: AtomicU64,
+    pending: Arc<PendingMap>,
+}
+
+impl DhtService {
+    /// DHT 프로토콜 핸들러를 Network에 등록하고 서비스 생성
+    pub fn register(network: Arc<Network>, dht: Arc<Kademlia>) -> Result<Arc<Self>, ProtocolError> {
+        let pending: Arc<PendingMap> = Arc::new(Mutex::new(HashMap::new()));
+
+        // 핸들러가 Network를 강하게 잡으면 순환 참조가 되므로 Weak 사용
+        let handler_network = Arc::downgrade(&network);
+        let handler_pending = pending.clone();
+        let handler_dht = dht.clone();
+        network.register_message_handler(
+            DHT_PROTOCOL,
+            move |from: SocketAddr, data: Vec<u8>| {
+                let network = handler_network.clone();
+                let pending = handler_pending.clone();
+                let dht = handler_dht.clone();
+                async move {
+                    Self::handle_message(from, data, network, pending, dht);
+                }
+            },
+        )?;
+
+        Ok(Arc::new(Self {
+            network,
+            local_id: dht.get_node_id(),
+            next_request_id: AtomicU64::new(1),
+            pending,
+        }))
+    }
+
+    fn handle_message(
+        from: SocketAddr,
+        data: Vec<u8>,
+        network: Weak<Network>,
+        pending: Arc<PendingMap>,
+        dht: Arc<Kademlia>,
+    ) {
+        let Some(envelope) = DhtEnvelope::decode(&data) else {
+            log_network!("⚠️ Invalid DHT message from {}", from);
+            return;
+        };
+
+        match envelope.body {
+            DhtBody::Request(request) => {
+                // 응답 전송은 별도 태스크에서 (같은 피어의 다른 메시지 처리를 막지 않도록)
+                tokio::spawn(async move {
+                    let Some(network) = network.upgrade() else {
+                        return;
+                    };
+                    let Some(sender) = Self::verified_sender(&network, from, envelope.sender).await
+                    else {
+                        return;
+                    };
+                    let response = dht.handle_request(from, sender, request).await;
+                    let reply = DhtEnvelope {
+                        request_id: envelope.request_id,
+                        sender: dht.get_node_id(),
+                        body: DhtBody::Response(response),
+                    };
+                    if let Err(e) = network
+                        .send_protocol(from, DHT_PROTOCOL, &reply.encode(), TrafficClass::Control)
+                        .await
+                    {
+                        let error = e.to_string();
+                        log_network!("⚠️ Failed to answer DHT request from {}: {}", from, error);
+                    }
+                });
+            }
+            DhtBody::Response(response) => {
+                let mut pending = pending.lock().unwrap();
+                // 요청을 보낸 주소에서 온 응답만 받음
+                let expected =
+                    matches!(pending.get(&envelope.request_id), Some((addr, _)) if *addr == from);
+                if expected {
+                    if let Some((_, tx)) = pending.remove(&envelope.request_id) {
+                        let _ = tx.send((envelope.sender, response));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 봉투에 적힌 sender가 핸드셰이크로 확인한 피어의 노드 id와 같으면 그 id (다르면 None)
+    async fn verified_sender(network: &Network, from: SocketAddr, claimed: NodeId) -> Option<NodeId> {
+        let verified = network.peer_node_id(from).await;
+        if verified != Some(claimed) {
+            let claimed_hex = claimed.to_hex();
+            log_network!(
+                "🚫 Dropping DHT message from {}: claimed node id {} is not its verified id",
+                from,
+                &claimed_hex[..16]
+            );
+            return None;
+        }
+        verified
+    }
+}
+
+/// 응답을 받지 못하고 요청이 끝나면 (시간 초과로 취소된 경우 포함) 대기 항목 제거
+struct PendingGuard<'a> {
+    pending: &'a PendingMap,
+    request_id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.request_id);
+    }
+}
+
+#[async_trait]
+impl DhtTransport for DhtService {
+    async fn request(
+        &self,
+        to: SocketAddr,
+        request: DhtRequest,
+    ) -> Result<(NodeId, DhtResponse), DhtError> {
+        if self.network.peer_supports(to, DHT_PROTOCOL).await.is_none() {
+            self.network
+                .connect(to)
+                .await
+                .map_err(|e| DhtError::Unreachable(e.to_string()))?;
+        }
+
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id, (to, tx));
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            request_id,
+        };
+
+        let envelope = DhtEnvelope {
+            request_id,
+            sender: self.local_id,
+            body: DhtBody::Request(request),
+        };
+        self.network
+            .send_protocol(to, DHT_PROTOCOL, &envelope.encode(), TrafficClass::Control)
+            .await
+            .map_err(|e| DhtError::Unreachable(e.to_string()))?;
+
+        let (sender, response) = rx
+            .await
+            .map_err(|_| DhtError::Unreachable("request cancelled".to_string()))?;
+        // 응답한 노드는 라우팅 테이블에 들어가므로 확인된 id여야 함
+        let sender = Self::verified_sender(&self.network, to, sender)
+            .await
+            .ok_or(DhtError::UnexpectedResponse)?;
+        Ok((sender, response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_transport::MemoryNetwork;
+    use crate::network::NetworkConfig;
+    use guild_discovery::{DhtConfig, NodeIdentity};
+    use std::time::Duration;
+
+    /// 같은 키로 Network와 Kademlia를 만들고 DHT 서비스로 연결
+    async fn dht_node(net: &MemoryNetwork, addr: &str) -> (Arc<Network>, Arc<Kademlia>) {
+        guild_logger::init_logger(true);
+        let identity = NodeIdentity::generate();
+        let config = NetworkConfig {
+            identity: identity.clone(),
+            ..NetworkConfig::default()
+        };
+        let transport = net.bind(addr.parse().unwrap());
+        let network = Arc::new(Network::with_transport(Arc::new(transport), config).await);
+        let dht = Arc::new(Kademlia::with_identity(identity, DhtConfig::default()));
+        let service = DhtService::register(network.clone(), dht.clone()).unwrap();
+        dht.set_transport(service);
+        (network, dht)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requests_with_forged_sender_ids_are_dropped() {
+        let net = MemoryNetwork::new(1);
+        let (a, a_dht) = dht_node(&net, "10.0.0.1:42000").await;
+        let (b, b_dht) = dht_node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        a.connect(b_addr).await.unwrap();
+
+        // 연결은 a의 키로 확인되었지만 봉투에는 다른 id를 적음
+        let forged = DhtEnvelope {
+            request_id: 1,
+            sender: NodeIdentity::generate().node_id(),
+            body: DhtBody::Request(DhtRequest::Ping),
+        };
+        a.send_protocol(b_addr, DHT_PROTOCOL, &forged.encode(), TrafficClass::Control)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(b_dht.node_count().await, 0);
+
+        // 확인된 id로 보낸 요청은 양쪽 라우팅 테이블에 들어감
+        assert_eq!(a_dht.ping(b_addr).await.unwrap(), b.node_id());
+        assert!(b_dht.node(&a.node_id()).await.is_some());
+        assert!(a_dht.node(&b.node_id()).await.is_some());
+    }
+}
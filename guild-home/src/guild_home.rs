@@ -1,33 +1,132 @@
 use std::sync::Arc;
 
-use crate::blockchain_bridge::BlockchainBridge;
+use crate::blockchain_bridge::{BlockchainBridge, BridgeConfig};
 use crate::config::Config;
-use crate::network::Network;
+use crate::dht_service::DhtService;
+use crate::network::{Network, NetworkConfig};
 use crate::log_network;
-use guild_discovery::{Discovery, DiscoveryConfig};
+use guild_discovery::{
+    AddressBook, Discovery, DiscoveryConfig, NodeIdentity, PeerSource, StaticPeerChange,
+    DEFAULT_STATIC_PEERS_POLL_INTERVAL,
+};
+
+/// data_dir 아래 주소록 파일 이름
+const ADDRESS_BOOK_FILE: &str = "address_book.bin";
+/// 주소록 압축/저장 주기
+const ADDRESS_BOOK_COMPACT_INTERVAL_SECS: u64 = 60;
+/// 이 기간 동안 연결되지 않은 주소는 압축 시 제거 (7일)
+const ADDRESS_BOOK_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+const ADDRESS_BOOK_MAX_ENTRIES: usize = 1000;
+/// data_dir 아래 노드 비밀키 파일 이름 (노드 id와 DHT 레코드 서명에 사용)
+const NODE_KEY_FILE: &str = "node_key";
+/// data_dir 아래 정적 피어 파일 이름 (수정하면 재시작 없이 반영)
+const STATIC_PEERS_FILE: &str = "peers.txt";
+/// data_dir 아래 IPC Unix 소켓 이름 (--ipc unix/both)
+const IPC_SOCKET_FILE: &str = "ipc.sock";
 
 pub struct GuildHome {
     pub config: Config,
     pub network: Arc<Network>,
     pub blockchain_bridge: Option<BlockchainBridge>,
+    /// 핸드셰이크와 DHT 레코드 서명에 쓰는 노드 키
+    identity: NodeIdentity,
 }
 
 impl GuildHome {
     pub async fn new(config: Config) -> Self {
-        let network = Arc::new(Network::with_port(config.port).await);
-        let blockchain_bridge = Some(BlockchainBridge::new(network.clone()));
+        let recording_path = config.record_traffic.then(|| {
+            let started = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            std::path::Path::new(&config.data_dir)
+                .join("recordings")
+                .join(format!("traffic-{}.ghrec", started))
+        });
+        // 재시작해도 같은 노드 id와 레코드 발행자 키를 유지
+        let node_key_path = std::path::Path::new(&config.data_dir).join(NODE_KEY_FILE);
+        let identity = match NodeIdentity::load_or_generate(&node_key_path).await {
+            Ok(identity) => identity,
+            Err(e) => {
+                let error_msg = e.to_string();
+                log_network!("⚠️ Failed to load node key, using a temporary one: {}", error_msg);
+                NodeIdentity::generate()
+            }
+        };
+        let network_config = NetworkConfig {
+            datagram_pings: config.datagram_pings,
+            recording_path,
+            network_id: config.network_id.clone(),
+            identity: identity.clone(),
+            ..NetworkConfig::default()
+        };
+        let network = Arc::new(Network::with_config(config.port, network_config).await);
+        let bridge_config = BridgeConfig {
+            tcp: config.ipc_mode.tcp(),
+            unix_socket: config
+                .ipc_mode
+                .unix()
+                .then(|| std::path::Path::new(&config.data_dir).join(IPC_SOCKET_FILE)),
+        };
+        let blockchain_bridge = Some(BlockchainBridge::with_config(network.clone(), bridge_config));
 
         GuildHome { 
             config, 
             network,
             blockchain_bridge,
+            identity,
         }
     }
 
     pub async fn start(&mut self) {
+        // Discovery 설정
+        let discovery_config = DiscoveryConfig {
+            bootstrap_nodes: self.config.bootstrap.clone(),
+            enable_dht: true,
+            max_peers: 100,
+            port: self.network.local_port(),
+            network_id: self.config.network_id.clone(),
+            capabilities: self.config.capabilities.clone(),
+            scan: self.config.scan_config(),
+            static_peers_file: Some(
+                std::path::Path::new(&self.config.data_dir).join(STATIC_PEERS_FILE),
+            ),
+            ..DiscoveryConfig::default()
+        };
+
+        // 이전 실행에서 저장한 주소록으로 다이얼 시작
+        let address_book_path =
+            std::path::Path::new(&self.config.data_dir).join(ADDRESS_BOOK_FILE);
+        let address_book = Arc::new(AddressBook::load(&address_book_path).await);
+        let discovery = Arc::new(Discovery::with_identity(
+            discovery_config,
+            address_book.clone(),
+            self.identity.clone(),
+        ));
+        let network = self.network.clone();
+
+        // DHT 요청/응답을 Network 프로토콜로 주고받도록 연결
+        if let Some(dht) = discovery.dht() {
+            match DhtService::register(network.clone(), dht.clone()) {
+                Ok(service) => {
+                    discovery.set_dht_transport(service);
+                    // 오래된 버킷을 주기적으로 무작위 조회해서 라우팅 테이블 유지
+                    dht.spawn_maintenance();
+                    // 역할 태그를 DHT에 광고 (다른 노드가 find_peers_with_capability로 찾음)
+                    discovery.spawn_capability_advertiser();
+                }
+                Err(e) => {
+                    let error_msg = e.to_string();
+                    log_network!("⚠️ Failed to register DHT protocol: {}", error_msg);
+                }
+            }
+        }
+
         // Start blockchain bridge
         log_network!("Starting blockchain bridge...");
         if let Some(ref mut bridge) = self.blockchain_bridge {
+            // 애플리케이션의 FindPeers 요청은 같은 탐색기로 처리
+            bridge.set_discovery(discovery.clone());
             match bridge.start().await {
                 Ok(_) => log_network!("Blockchain bridge started successfully"),
                 Err(e) => {
@@ -39,16 +138,73 @@ impl GuildHome {
             log_network!("No blockchain bridge configured");
         }
         log_network!("Blockchain bridge initialization complete");
-        // Discovery 설정
-        let discovery_config = DiscoveryConfig {
-            bootstrap_nodes: self.config.bootstrap.clone(),
-            enable_dht: true,
-            max_peers: 100,
-            port: self.network.local_port(),
-        };
 
-        let discovery = Discovery::new(discovery_config);
-        let network = self.network.clone();
+        // 오래 확인되지 않은 피어를 주기적으로 정리
+        discovery.spawn_maintenance();
+
+        // 핸드셰이크 완료/Pong 수신 시 피어의 마지막 확인 시각 갱신
+        let mut contacts = network.subscribe_contacts();
+        let discovery_contacts = discovery.clone();
+        tokio::spawn(async move {
+            loop {
+                match contacts.recv().await {
+                    Ok(addr) => discovery_contacts.record_contact(addr).await,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        // 정적 피어 파일 감시 - 추가된 피어는 바로 연결하고, 빠진 피어는 연결 종료
+        if let Some(static_peers) = discovery.static_peers() {
+            let mut changes = static_peers.spawn_watcher(DEFAULT_STATIC_PEERS_POLL_INTERVAL);
+            let network = network.clone();
+            let discovery = discovery.clone();
+            tokio::spawn(async move {
+                while let Some(change) = changes.recv().await {
+                    match change {
+                        StaticPeerChange::Added(peer) => {
+                            let peer_str = peer.to_string();
+                            log_network!("📄 Static peer added: {}", peer_str);
+                            discovery.note_source(peer.addr, PeerSource::Static).await;
+                            let network = network.clone();
+                            let discovery = discovery.clone();
+                            tokio::spawn(async move {
+                                connect_peer(&network, &discovery, peer.addr).await;
+                            });
+                        }
+                        StaticPeerChange::Removed(peer) => {
+                            let peer_str = peer.to_string();
+                            log_network!("📄 Static peer removed: {}", peer_str);
+                            discovery.forget_static(peer.addr).await;
+                            network.disconnect(peer.addr).await;
+                        }
+                    }
+                }
+            });
+        }
+
+        // 주소록 주기적 압축 및 저장
+        tokio::spawn(async move {
+            let mut compact_interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                ADDRESS_BOOK_COMPACT_INTERVAL_SECS,
+            ));
+            compact_interval.tick().await;
+            loop {
+                compact_interval.tick().await;
+
+                let removed = address_book
+                    .compact(ADDRESS_BOOK_MAX_AGE_SECS, ADDRESS_BOOK_MAX_ENTRIES)
+                    .await;
+                if removed > 0 {
+                    log_network!("📒 Compacted address book: removed {} entries", removed);
+                }
+                if let Err(e) = address_book.save().await {
+                    let error_msg = e.to_string();
+                    log_network!("⚠️ Failed to save address book: {}", error_msg);
+                }
+            }
+        });
 
         // 피어 탐색 루프 (즉시 시작, 30초마다 재시도)
         tokio::spawn(async move {
@@ -58,24 +214,11 @@ impl GuildHome {
 
             // 즉시 첫 탐색 실행
             loop {
-                let peers = discovery.start().await;
-
-                for peer_addr in peers {
-                    // 발견된 피어에 연결 시도
-                    let connect_result = network.connect(peer_addr).await;
-
-                    if connect_result.is_ok() {
-                        log_network!("✅ Connected to peer: {}", peer_addr);
-                        // 연결 성공한 피어를 discovery에 추가
-                        discovery.add_peer(peer_addr).await;
-                    } else if let Err(e) = connect_result {
-                        // 연결 실패는 조용히 처리 (피어가 실제로 없을 수 있음)
+                // 새로 연결된 피어가 있으면 바로 저장 (압축 주기 전에 종료되어도 유지)
+                if discovery_round(&network, &discovery).await {
+                    if let Err(e) = discovery.address_book().save().await {
                         let error_msg = e.to_string();
-                        if !error_msg.contains("refused")
-                            && !error_msg.contains("Connection refused")
-                        {
-                            log_network!("⚠️ Failed to connect to {}: {}", peer_addr, error_msg);
-                        }
+                        log_network!("⚠️ Failed to save address book: {}", error_msg);
                     }
                 }
 
@@ -126,3 +269,98 @@ impl GuildHome {
         });
     }
 }
+
+/// 한 번 탐색해서 아직 연결하지 않은 피어에 연결 (새로 연결한 피어가 있으면 true)
+///
+/// 이미 연결되어 있거나 핸드셰이크 중인 주소는 다시 다이얼하지 않는다.
+async fn discovery_round(network: &Network, discovery: &Discovery) -> bool {
+    let mut connected_any = false;
+    for peer_addr in discovery.start().await {
+        if network.has_connection(peer_addr).await {
+            continue;
+        }
+        // 발견된 피어에 연결 시도
+        if connect_peer(network, discovery, peer_addr).await {
+            connected_any = true;
+        }
+    }
+    connected_any
+}
+
+/// 피어에 연결하고 결과를 discovery에 기록 (연결되면 true)
+///
+/// 정적 피어 파일에서 node id를 고정한 주소는 연결 후 실제 id를 확인해서, 다르면 끊는다.
+async fn connect_peer(network: &Network, discovery: &Discovery, peer_addr: std::net::SocketAddr) -> bool {
+    // 고정된 id가 있으면 핸드셰이크에서 확인 (다르면 peers에 등록되지 않음)
+    let expected_id = discovery.pinned_id(peer_addr).await;
+    if let Err(e) = network.connect_expecting(peer_addr, expected_id).await {
+        discovery.record_failure(peer_addr).await;
+
+        // 연결 실패는 조용히 처리 (피어가 실제로 없을 수 있음)
+        let error_msg = e.to_string();
+        if !error_msg.contains("refused") && !error_msg.contains("Connection refused") {
+            log_network!("⚠️ Failed to connect to {}: {}", peer_addr, error_msg);
+        }
+        return false;
+    }
+
+    log_network!("✅ Connected to peer: {}", peer_addr);
+    // 연결 성공한 피어를 discovery에 추가
+    discovery.add_peer(peer_addr).await;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_transport::MemoryNetwork;
+    use crate::network::PeerEvent;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    async fn node(net: &MemoryNetwork, addr: &str) -> Arc<Network> {
+        guild_logger::init_logger(true);
+        let transport = net.bind(addr.parse().unwrap());
+        Arc::new(Network::with_transport(Arc::new(transport), NetworkConfig::default()).await)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn second_discovery_round_does_not_redial_connected_peers() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "guild_home_discovery_round_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, "10.0.0.2:42000\n").unwrap();
+        let discovery = Discovery::new(DiscoveryConfig {
+            enable_dht: false,
+            enable_mdns: false,
+            enable_scan: false,
+            static_peers_file: Some(path.clone()),
+            ..DiscoveryConfig::default()
+        });
+        let mut a_events = a.subscribe_peer_events();
+        let mut b_events = b.subscribe_peer_events();
+
+        assert!(discovery_round(&a, &discovery).await);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            a_events.try_recv().unwrap(),
+            PeerEvent::Connected(b_addr, b.node_id())
+        );
+        assert!(matches!(b_events.try_recv(), Ok(PeerEvent::Connected(..))));
+
+        // 이미 연결된 피어는 다시 다이얼하지 않음 (새 연결도, 새 이벤트도 없음)
+        assert!(!discovery_round(&a, &discovery).await);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(a_events.try_recv().is_err());
+        assert!(b_events.try_recv().is_err());
+        assert_eq!(a.peer_count().await, 1);
+        assert_eq!(b.peer_count().await, 1);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -7,10 +7,21 @@ USAGE:
 
 OPTIONS:
     -p, --port <PORT>             Port to listen on (0 = auto)
-    -b, --bootstrap <PEERS>       Bootstrap peers (comma separated)
+    -b, --bootstrap <PEERS>       Bootstrap peers or DNS seed hostnames (comma separated)
     -d, --data-dir <DIR>          Data directory (default: ./data)
     -i, --interval <SECONDS>      Heartbeat interval (default: 5)
     -l, --log <LEVEL>             Log level (error/warn/info/debug)
+        --datagram-pings          Send pings over QUIC datagrams when supported
+        --record                  Record wire traffic to <data-dir>/recordings
+    -n, --network <ID>            Network id; only peers with the same id connect (default: guild)
+    -c, --capabilities <TAGS>     Role tags to advertise (comma separated, e.g. validator,relay)
+        --ipc <MODE>              Local app IPC: tcp (127.0.0.1:<port>+1), unix (<data-dir>/ipc.sock)
+                                  or both (default: tcp)
+        --scan-cidr <CIDRS>       IPv4 ranges to probe directly in LAN scans (comma separated,
+                                  up to /20 each, e.g. 192.168.1.0/24)
+        --scan-ports <PORTS>      Beacon ports to probe on each scanned host (e.g. 41999-42010,
+                                  default: 41999)
+        --scan-rate <PER_SEC>     Maximum LAN scan probes per second (1-10000, default: 200)
     -h, --help                    Show this help message
 
 ENVIRONMENT VARIABLES:
@@ -19,6 +30,20 @@ ENVIRONMENT VARIABLES:
     GUILD_DATA_DIR                Same as --data-dir
     GUILD_HEARTBEAT_INTERVAL      Same as --interval
     GUILD_LOG_LEVEL               Same as --log
+    GUILD_DATAGRAM_PINGS          Same as --datagram-pings (1/true)
+    GUILD_RECORD                  Same as --record (1/true)
+    GUILD_NETWORK                 Same as --network
+    GUILD_CAPABILITIES            Same as --capabilities
+    GUILD_IPC                     Same as --ipc
+    GUILD_SCAN_CIDR               Same as --scan-cidr
+    GUILD_SCAN_PORTS              Same as --scan-ports
+    GUILD_SCAN_RATE               Same as --scan-rate
+
+FILES:
+    <data-dir>/peers.txt          Static peers, one per line: <addr|host[:port]> [id=<hex>] [label=<name>]
+                                  Edits are picked up without restart (added peers connect,
+                                  removed peers disconnect); id= pins the peer's node id
+    <data-dir>/ipc.sock           IPC Unix socket (--ipc unix/both), owner-only (0600)
 
 EXAMPLES:
     # Run with auto-discovery
@@ -27,6 +52,21 @@ EXAMPLES:
     # Connect to specific peers
     guild-home --bootstrap 192.168.1.10:8000,192.168.1.11:8000
 
+    # Bootstrap from a DNS seed (every A/AAAA record becomes a peer)
+    guild-home --bootstrap seed.guild.network:9000
+
+    # Run a separate guild that ignores nodes of the default network
+    guild-home --network my-guild
+
+    # Advertise this node as a validator and archive node
+    guild-home --capabilities validator,archive
+
+    # Also probe every host of a /24 on a small port range, 500 probes per second
+    guild-home --scan-cidr 192.168.1.0/24 --scan-ports 41999-42002 --scan-rate 500
+
+    # Let local apps connect only through the owner-only Unix socket
+    guild-home --ipc unix
+
     # Run on specific port with 10-second heartbeat
     guild-home --port 8080 --interval 10
 "#);
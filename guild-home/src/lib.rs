@@ -2,17 +2,30 @@
 
 pub mod blockchain_bridge;
 pub mod config;
+pub mod dht_service;
 pub mod guild_home;
 pub mod help;
+#[cfg(any(test, feature = "test-util"))]
+pub mod memory_transport;
 pub mod network;
+pub mod protocol;
+pub mod recorder;
+pub mod transport;
 pub mod tui;
+pub mod wire;
 
 // Re-export main types for convenience
-pub use config::{Config, ConfigError};
+pub use config::{Config, ConfigError, IpcMode};
 pub use guild_home::GuildHome;
 
 // Re-export other core types
-pub use network::Network;
+pub use dht_service::DhtService;
+#[cfg(any(test, feature = "test-util"))]
+pub use memory_transport::{LinkConditions, MemoryNetwork, MemoryTransport};
+pub use network::{DeliveryPath, Network, NetworkConfig, PeerEvent, SendReport, TrafficClass};
+pub use protocol::{MessageHandler, ProtocolError, StreamHandler};
+pub use recorder::{RecordingReader, TrafficRecorder};
+pub use transport::{QuicTransport, Transport, TransportConnection};
 
 // Re-export logging macros
 pub use guild_logger::{
@@ -1,7 +1,6 @@
 // Guild Home - P2P 네트워킹 실행 파일
 use guild_home::{Config, GuildHome};
 use std::env;
-use std::sync::Arc;
 
 #[tokio::main]
 async fn main() {
@@ -20,6 +19,7 @@ async fn main() {
         // TUI 모드로 실행 - 초기 메시지만 출력하고 나머지는 TUI에서 처리
         println!("🎨 Starting Guild Home in TUI mode...");
         println!("📁 Data directory: {}", config.data_dir);
+        println!("🏷️ Network: {}", config.network_id);
         if !config.bootstrap.is_empty() {
             println!("🌐 Bootstrap peers: {:?}", config.bootstrap);
         }
@@ -61,6 +61,7 @@ async fn main() {
 
         println!("🏰 Guild Home Starting...");
         println!("📁 Data directory: {}", config.data_dir);
+        println!("🏷️ Network: {}", config.network_id);
         if !config.bootstrap.is_empty() {
             println!("🌐 Bootstrap peers: {:?}", config.bootstrap);
         }
@@ -0,0 +1,1152 @@
+// 인메모리 Transport - 실제 소켓 없이 결정적인 다중 노드 테스트용
+//
+// 모든 지연은 tokio 타이머로 처리되므로 `tokio::time::pause()`(test-util)와 함께 쓰면
+// 시뮬레이션 시간이 자동으로 진행되고, 난수는 시드 고정 RNG에서 나오므로
+// 같은 시드와 같은 실행 순서면 항상 같은 결과가 나온다. 테스트 빌드나 `test-util` 기능에서만
+// 포함된다.
+use crate::transport::{RecvStream, SendStream, Transport, TransportConnection, TransportError};
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+/// 스트림 하나에서 아직 도착하지 않은 데이터의 최대 크기 (QUIC 흐름 제어 창처럼 넘으면 쓰기가 대기)
+const STREAM_WINDOW: usize = 1024 * 1024;
+
+/// 두 노드 사이 링크 특성
+#[derive(Debug, Clone, Default)]
+pub struct LinkConditions {
+    /// 단방향 기본 지연
+    pub latency: Duration,
+    /// 메시지마다 더해지는 0..jitter 범위의 무작위 지연 (데이터그램과 스트림 간 순서가 뒤바뀜)
+    pub jitter: Duration,
+    /// 데이터그램 손실 확률 (0.0 ~ 1.0, 스트림은 신뢰성 보장)
+    pub loss: f64,
+}
+
+struct Fabric {
+    listeners: HashMap<SocketAddr, mpsc::UnboundedSender<Arc<dyn TransportConnection>>>,
+    default_link: LinkConditions,
+    links: HashMap<(SocketAddr, SocketAddr), LinkConditions>,
+    partitions: HashSet<(SocketAddr, SocketAddr)>,
+    max_datagram_size: Option<usize>,
+    // 데이터그램을 지원하지 않는 노드 (연결 양쪽 중 하나라도 있으면 미지원)
+    no_datagrams: HashSet<SocketAddr>,
+    rng: StdRng,
+    next_port: u16,
+    // 연결마다 다른 채널 바인딩을 만들기 위한 카운터
+    next_connection: u64,
+}
+
+impl Fabric {
+    fn link(&self, from: SocketAddr, to: SocketAddr) -> &LinkConditions {
+        self.links.get(&(from, to)).unwrap_or(&self.default_link)
+    }
+
+    fn is_partitioned(&self, a: SocketAddr, b: SocketAddr) -> bool {
+        self.partitions.contains(&Self::pair(a, b))
+    }
+
+    fn datagram_limit(&self, a: SocketAddr, b: SocketAddr) -> Option<usize> {
+        if self.no_datagrams.contains(&a) || self.no_datagrams.contains(&b) {
+            return None;
+        }
+        self.max_datagram_size
+    }
+
+    fn pair(a: SocketAddr, b: SocketAddr) -> (SocketAddr, SocketAddr) {
+        if a <= b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    /// 링크 지연 + 지터 계산
+    fn delay(&mut self, from: SocketAddr, to: SocketAddr) -> Duration {
+        let link = self.link(from, to).clone();
+        let jitter = if link.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            link.jitter.mul_f64(self.rng.gen::<f64>())
+        };
+        link.latency + jitter
+    }
+
+    /// 데이터그램 손실 여부
+    fn drops(&mut self, from: SocketAddr, to: SocketAddr) -> bool {
+        let loss = self.link(from, to).loss;
+        loss > 0.0 && self.rng.gen::<f64>() < loss
+    }
+}
+
+/// 인메모리 네트워크 - 여러 MemoryTransport가 공유하는 가상 회선
+#[derive(Clone)]
+pub struct MemoryNetwork {
+    fabric: Arc<Mutex<Fabric>>,
+    // 파티션이 바뀔 때마다 증가 (멈춘 스트림 전달 재개용)
+    generation: Arc<watch::Sender<u64>>,
+}
+
+impl MemoryNetwork {
+    /// 시드 고정 RNG로 새 네트워크 생성
+    pub fn new(seed: u64) -> Self {
+        let (generation, _) = watch::channel(0);
+        Self {
+            fabric: Arc::new(Mutex::new(Fabric {
+                listeners: HashMap::new(),
+                default_link: LinkConditions::default(),
+                links: HashMap::new(),
+                partitions: HashSet::new(),
+                max_datagram_size: Some(1200),
+                no_datagrams: HashSet::new(),
+                rng: StdRng::seed_from_u64(seed),
+                next_port: 42000,
+                next_connection: 0,
+            })),
+            generation: Arc::new(generation),
+        }
+    }
+
+    /// 127.0.0.1의 다음 빈 포트에 Transport 바인딩
+    pub fn transport(&self) -> MemoryTransport {
+        let port = {
+            let mut fabric = self.fabric.lock().unwrap();
+            while fabric.listeners.contains_key(&SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                fabric.next_port,
+            )) {
+                fabric.next_port += 1;
+            }
+            fabric.next_port
+        };
+        self.bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port))
+    }
+
+    /// 지정한 주소에 Transport 바인딩 (기존 바인딩은 대체됨)
+    pub fn bind(&self, addr: SocketAddr) -> MemoryTransport {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.fabric
+            .lock()
+            .unwrap()
+            .listeners
+            .insert(addr, tx.clone());
+        MemoryTransport {
+            network: self.clone(),
+            local_addr: addr,
+            listener: tx,
+            incoming: tokio::sync::Mutex::new(rx),
+        }
+    }
+
+    /// 모든 링크의 기본 특성 설정
+    pub fn set_default_link(&self, conditions: LinkConditions) {
+        self.fabric.lock().unwrap().default_link = conditions;
+    }
+
+    /// 특정 방향(from → to) 링크 특성 설정
+    pub fn set_link(&self, from: SocketAddr, to: SocketAddr, conditions: LinkConditions) {
+        self.fabric
+            .lock()
+            .unwrap()
+            .links
+            .insert((from, to), conditions);
+    }
+
+    /// 최대 데이터그램 크기 설정 (None이면 데이터그램 미지원 피어처럼 동작)
+    pub fn set_max_datagram_size(&self, size: Option<usize>) {
+        self.fabric.lock().unwrap().max_datagram_size = size;
+    }
+
+    /// 한 노드만 데이터그램 미지원으로 설정 (그 노드와의 연결은 양쪽 모두 미지원)
+    pub fn disable_datagrams(&self, addr: SocketAddr) {
+        self.fabric.lock().unwrap().no_datagrams.insert(addr);
+    }
+
+    /// 두 노드 사이 파티션 (데이터그램은 버려지고 스트림 데이터는 복구될 때까지 멈춤)
+    pub fn partition(&self, a: SocketAddr, b: SocketAddr) {
+        self.fabric
+            .lock()
+            .unwrap()
+            .partitions
+            .insert(Fabric::pair(a, b));
+        self.bump_generation();
+    }
+
+    /// 두 그룹 사이 모든 링크 파티션
+    pub fn partition_groups(&self, left: &[SocketAddr], right: &[SocketAddr]) {
+        {
+            let mut fabric = self.fabric.lock().unwrap();
+            for a in left {
+                for b in right {
+                    fabric.partitions.insert(Fabric::pair(*a, *b));
+                }
+            }
+        }
+        self.bump_generation();
+    }
+
+    /// 두 노드 사이 파티션 복구
+    pub fn heal(&self, a: SocketAddr, b: SocketAddr) {
+        self.fabric
+            .lock()
+            .unwrap()
+            .partitions
+            .remove(&Fabric::pair(a, b));
+        self.bump_generation();
+    }
+
+    /// 모든 파티션 복구
+    pub fn heal_all(&self) {
+        self.fabric.lock().unwrap().partitions.clear();
+        self.bump_generation();
+    }
+
+    fn bump_generation(&self) {
+        self.generation.send_modify(|generation| *generation += 1);
+    }
+
+    fn is_partitioned(&self, a: SocketAddr, b: SocketAddr) -> bool {
+        self.fabric.lock().unwrap().is_partitioned(a, b)
+    }
+
+    /// 파티션이 풀릴 때까지 대기
+    async fn wait_reachable(&self, a: SocketAddr, b: SocketAddr) {
+        let mut generation = self.generation.subscribe();
+        while self.is_partitioned(a, b) {
+            if generation.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// MemoryNetwork 위의 한 노드 엔드포인트
+pub struct MemoryTransport {
+    network: MemoryNetwork,
+    local_addr: SocketAddr,
+    listener: mpsc::UnboundedSender<Arc<dyn TransportConnection>>,
+    incoming: tokio::sync::Mutex<mpsc::UnboundedReceiver<Arc<dyn TransportConnection>>>,
+}
+
+impl Drop for MemoryTransport {
+    fn drop(&mut self) {
+        // 같은 주소에 다시 바인딩된 Transport는 건드리지 않음
+        let mut fabric = self.network.fabric.lock().unwrap();
+        if fabric
+            .listeners
+            .get(&self.local_addr)
+            .is_some_and(|listener| listener.same_channel(&self.listener))
+        {
+            fabric.listeners.remove(&self.local_addr);
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn connect(
+        &self,
+        addr: SocketAddr,
+    ) -> Result<Arc<dyn TransportConnection>, TransportError> {
+        let (listener, delay) = {
+            let mut fabric = self.network.fabric.lock().unwrap();
+            let listener = fabric
+                .listeners
+                .get(&addr)
+                .cloned()
+                .ok_or_else(|| format!("Connection refused: {}", addr))?;
+            let rtt = fabric.delay(self.local_addr, addr) + fabric.delay(addr, self.local_addr);
+            (listener, rtt)
+        };
+
+        // 핸드셰이크 왕복 시간
+        tokio::time::sleep(delay).await;
+        if self.network.is_partitioned(self.local_addr, addr) {
+            return Err(format!("Connection timed out: {}", addr).into());
+        }
+
+        let (client, server) = MemoryConnection::pair(&self.network, self.local_addr, addr);
+        listener
+            .send(server)
+            .map_err(|_| format!("Connection refused: {}", addr))?;
+        Ok(client)
+    }
+
+    async fn accept(&self) -> Option<Arc<dyn TransportConnection>> {
+        self.incoming.lock().await.recv().await
+    }
+
+    fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// 한쪽 방향 연결 상태 (스트림/데이터그램 수신 큐)
+struct Inbox {
+    streams: tokio::sync::Mutex<mpsc::UnboundedReceiver<MemoryRecvStream>>,
+    datagrams: tokio::sync::Mutex<mpsc::UnboundedReceiver<Vec<u8>>>,
+}
+
+/// 인메모리 연결 한쪽 끝
+pub struct MemoryConnection {
+    network: MemoryNetwork,
+    local: SocketAddr,
+    remote: SocketAddr,
+    inbox: Inbox,
+    peer_streams: mpsc::UnboundedSender<MemoryRecvStream>,
+    peer_datagrams: mpsc::UnboundedSender<Vec<u8>>,
+    // 양쪽이 공유하는 종료 신호
+    closed: Arc<watch::Sender<bool>>,
+    // 양쪽이 공유하는 채널 바인딩 (연결마다 다름)
+    binding: [u8; 32],
+}
+
+impl fmt::Debug for MemoryConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryConnection")
+            .field("local", &self.local)
+            .field("remote", &self.remote)
+            .finish()
+    }
+}
+
+impl MemoryConnection {
+    fn pair(
+        network: &MemoryNetwork,
+        client: SocketAddr,
+        server: SocketAddr,
+    ) -> (Arc<dyn TransportConnection>, Arc<dyn TransportConnection>) {
+        let (client_streams_tx, client_streams_rx) = mpsc::unbounded_channel();
+        let (server_streams_tx, server_streams_rx) = mpsc::unbounded_channel();
+        let (client_datagrams_tx, client_datagrams_rx) = mpsc::unbounded_channel();
+        let (server_datagrams_tx, server_datagrams_rx) = mpsc::unbounded_channel();
+        let (closed, _) = watch::channel(false);
+        let closed = Arc::new(closed);
+        let mut binding = [0u8; 32];
+        {
+            let mut fabric = network.fabric.lock().unwrap();
+            binding[..8].copy_from_slice(&fabric.next_connection.to_le_bytes());
+            fabric.next_connection += 1;
+        }
+
+        let client_conn = MemoryConnection {
+            network: network.clone(),
+            local: client,
+            remote: server,
+            inbox: Inbox {
+                streams: tokio::sync::Mutex::new(client_streams_rx),
+                datagrams: tokio::sync::Mutex::new(client_datagrams_rx),
+            },
+            peer_streams: server_streams_tx,
+            peer_datagrams: server_datagrams_tx,
+            closed: closed.clone(),
+            binding,
+        };
+        let server_conn = MemoryConnection {
+            network: network.clone(),
+            local: server,
+            remote: client,
+            inbox: Inbox {
+                streams: tokio::sync::Mutex::new(server_streams_rx),
+                datagrams: tokio::sync::Mutex::new(server_datagrams_rx),
+            },
+            peer_streams: client_streams_tx,
+            peer_datagrams: client_datagrams_tx,
+            closed,
+            binding,
+        };
+
+        (Arc::new(client_conn), Arc::new(server_conn))
+    }
+
+    fn is_closed(&self) -> bool {
+        *self.closed.borrow()
+    }
+
+    async fn wait_closed(&self) {
+        let mut closed = self.closed.subscribe();
+        let _ = closed.wait_for(|closed| *closed).await;
+    }
+}
+
+#[async_trait]
+impl TransportConnection for MemoryConnection {
+    fn remote_address(&self) -> SocketAddr {
+        self.remote
+    }
+
+    async fn open_uni(&self, _priority: i32) -> Result<Box<dyn SendStream>, TransportError> {
+        if self.is_closed() {
+            return Err("Connection closed".into());
+        }
+
+        let (relay_tx, mut relay_rx) = mpsc::unbounded_channel::<(Instant, Vec<u8>)>();
+        let (deliver_tx, deliver_rx) = mpsc::unbounded_channel();
+        let in_flight = Arc::new(watch::Sender::new(0usize));
+
+        // 스트림 내 순서를 지키면서 각 청크를 도착 시각에 전달
+        let network = self.network.clone();
+        let (local, remote) = (self.local, self.remote);
+        let window = in_flight.clone();
+        tokio::spawn(async move {
+            while let Some((deliver_at, chunk)) = relay_rx.recv().await {
+                tokio::time::sleep_until(deliver_at).await;
+                network.wait_reachable(local, remote).await;
+                let len = chunk.len();
+                if deliver_tx.send(chunk).is_err() {
+                    break;
+                }
+                window.send_modify(|in_flight| *in_flight -= len);
+            }
+            // 더 전달하지 않으므로 대기 중인 쓰기가 멈추지 않고 에러를 받도록 창을 비움
+            window.send_replace(0);
+        });
+
+        self.peer_streams
+            .send(MemoryRecvStream {
+                rx: deliver_rx,
+                pending: Vec::new(),
+            })
+            .map_err(|_| "Connection closed")?;
+
+        Ok(Box::new(MemorySendStream {
+            network: self.network.clone(),
+            local: self.local,
+            remote: self.remote,
+            relay: Some(relay_tx),
+            last_delivery: Instant::now(),
+            in_flight,
+        }))
+    }
+
+    async fn accept_uni(&self) -> Result<Box<dyn RecvStream>, TransportError> {
+        let mut streams = self.inbox.streams.lock().await;
+        tokio::select! {
+            stream = streams.recv() => match stream {
+                Some(stream) => Ok(Box::new(stream) as Box<dyn RecvStream>),
+                None => Err("Connection closed".into()),
+            },
+            _ = self.wait_closed() => Err("Connection closed".into()),
+        }
+    }
+
+    fn max_datagram_size(&self) -> Option<usize> {
+        self.network.fabric.lock().unwrap().datagram_limit(self.local, self.remote)
+    }
+
+    fn send_datagram(&self, data: Vec<u8>) -> Result<(), TransportError> {
+        if self.is_closed() {
+            return Err("Connection closed".into());
+        }
+
+        let delay = {
+            let mut fabric = self.network.fabric.lock().unwrap();
+            match fabric.datagram_limit(self.local, self.remote) {
+                None => return Err("Datagrams unsupported by peer".into()),
+                Some(max) if data.len() > max => return Err("Datagram too large".into()),
+                Some(_) => {}
+            }
+            // 파티션이나 손실이면 조용히 버림
+            if fabric.is_partitioned(self.local, self.remote)
+                || fabric.drops(self.local, self.remote)
+            {
+                return Ok(());
+            }
+            fabric.delay(self.local, self.remote)
+        };
+
+        let peer_datagrams = self.peer_datagrams.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = peer_datagrams.send(data);
+        });
+        Ok(())
+    }
+
+    async fn read_datagram(&self) -> Result<Vec<u8>, TransportError> {
+        let mut datagrams = self.inbox.datagrams.lock().await;
+        tokio::select! {
+            datagram = datagrams.recv() => datagram.ok_or_else(|| "Connection closed".into()),
+            _ = self.wait_closed() => Err("Connection closed".into()),
+        }
+    }
+
+    fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    fn channel_binding(&self) -> Result<[u8; 32], TransportError> {
+        Ok(self.binding)
+    }
+}
+
+impl Drop for MemoryConnection {
+    fn drop(&mut self) {
+        self.closed.send_replace(true);
+    }
+}
+
+struct MemorySendStream {
+    network: MemoryNetwork,
+    local: SocketAddr,
+    remote: SocketAddr,
+    relay: Option<mpsc::UnboundedSender<(Instant, Vec<u8>)>>,
+    last_delivery: Instant,
+    // 보냈지만 아직 상대에게 전달되지 않은 바이트 수
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+#[async_trait]
+impl SendStream for MemorySendStream {
+    async fn write_all(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        if self.relay.is_none() {
+            return Err("Stream already finished".into());
+        }
+        // 창이 비어 있으면 큰 쓰기도 한 번은 받아들임 (파티션 중에는 여기서 멈춤)
+        let len = data.len();
+        let mut window = self.in_flight.subscribe();
+        window
+            .wait_for(|in_flight| *in_flight == 0 || in_flight + len <= STREAM_WINDOW)
+            .await
+            .map_err(|_| "Connection closed")?;
+        let relay = self.relay.as_ref().ok_or("Stream already finished")?;
+        self.in_flight.send_modify(|in_flight| *in_flight += len);
+        let delay = self
+            .network
+            .fabric
+            .lock()
+            .unwrap()
+            .delay(self.local, self.remote);
+
+        // 지터가 있어도 스트림 내 순서는 유지
+        let deliver_at = (Instant::now() + delay).max(self.last_delivery);
+        self.last_delivery = deliver_at;
+        relay
+            .send((deliver_at, data.to_vec()))
+            .map_err(|_| "Connection closed")?;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<(), TransportError> {
+        self.relay = None;
+        Ok(())
+    }
+}
+
+struct MemoryRecvStream {
+    rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+#[async_trait]
+impl RecvStream for MemoryRecvStream {
+    async fn read_chunk(&mut self, max: usize) -> Result<Option<Vec<u8>>, TransportError> {
+        if self.pending.is_empty() {
+            match self.rx.recv().await {
+                Some(chunk) => self.pending = chunk,
+                None => return Ok(None),
+            }
+        }
+
+        let take = self.pending.len().min(max);
+        let rest = self.pending.split_off(take);
+        Ok(Some(std::mem::replace(&mut self.pending, rest)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{
+        handshake_transcript, DeliveryPath, Message, Network, NetworkConfig, PeerEvent,
+        TrafficClass,
+    };
+    use crate::wire;
+
+    async fn node(net: &MemoryNetwork, addr: &str) -> Arc<Network> {
+        node_with_config(net, addr, NetworkConfig::default()).await
+    }
+
+    async fn node_with_config(net: &MemoryNetwork, addr: &str, config: NetworkConfig) -> Arc<Network> {
+        guild_logger::init_logger(true);
+        let transport = net.bind(addr.parse().unwrap());
+        Arc::new(Network::with_transport(Arc::new(transport), config).await)
+    }
+
+    fn link(latency_ms: u64, loss: f64) -> LinkConditions {
+        LinkConditions {
+            latency: Duration::from_millis(latency_ms),
+            jitter: Duration::ZERO,
+            loss,
+        }
+    }
+
+    async fn pongs(network: &Network) -> u64 {
+        network.get_stats().await.pongs_received
+    }
+
+    /// Network 없이 직접 핸드셰이크하는 피어 - Hello를 보낸 Control 스트림과,
+    /// 이 연결과 양쪽 nonce에 서명한 HelloAuth 프레임(아직 보내지 않음)을 반환
+    async fn raw_peer(
+        net: &MemoryNetwork,
+        addr: SocketAddr,
+        target: SocketAddr,
+        identity: &guild_discovery::NodeIdentity,
+    ) -> (
+        MemoryTransport,
+        Arc<dyn TransportConnection>,
+        Box<dyn SendStream>,
+        Vec<u8>,
+    ) {
+        let transport = net.bind(addr);
+        let conn = transport.connect(target).await.unwrap();
+        let mut control = conn.accept_uni().await.unwrap();
+        let hello = control.read_chunk(64 * 1024).await.unwrap().unwrap();
+        let Ok((wire::Frame::Message(Message::Hello { network_id, nonce }), _)) =
+            wire::decode(&hello)
+        else {
+            panic!("expected Hello");
+        };
+        let mut stream = conn.open_uni(0).await.unwrap();
+        let hello = wire::encode(&Message::Hello {
+            network_id: network_id.clone(),
+            nonce: [0; 32],
+        });
+        stream.write_all(&hello).await.unwrap();
+        let binding = conn.channel_binding().unwrap();
+        let transcript = handshake_transcript(&network_id, &binding, &nonce, &[0; 32]);
+        let auth = wire::encode(&Message::HelloAuth {
+            public_key: identity.public_key(),
+            signature: identity.sign(&transcript),
+        });
+        (transport, conn, stream, auth)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ping_pong_takes_one_round_trip() {
+        let net = MemoryNetwork::new(1);
+        net.set_default_link(link(50, 0.0));
+        let a = node(&net, "10.0.0.1:42000").await;
+        let _b = node(&net, "10.0.0.2:42000").await;
+
+        // 연결 왕복 + 우리 Hello에 상대가 서명한 HelloAuth의 왕복
+        let started = Instant::now();
+        a.connect("10.0.0.2:42000".parse().unwrap()).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_millis(200));
+
+        let report = a.send_ping().await;
+        assert_eq!(report.delivered.len(), 1);
+        tokio::time::sleep(Duration::from_millis(99)).await;
+        assert_eq!(pongs(&a).await, 0);
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        assert_eq!(pongs(&a).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn datagram_loss_is_seeded() {
+        let received = |seed: u64| async move {
+            let net = MemoryNetwork::new(seed);
+            let a = node(&net, "10.0.0.1:42000").await;
+            let b = node(&net, "10.0.0.2:42000").await;
+            let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+            let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+            a.connect(b_addr).await.unwrap();
+            net.set_link(a_addr, b_addr, link(10, 0.5));
+
+            let mut datagrams = b.subscribe_datagrams();
+            for i in 0..200u8 {
+                a.send_datagram(b_addr, &[i]).await.unwrap();
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let mut count = 0;
+            while datagrams.try_recv().is_ok() {
+                count += 1;
+            }
+            count
+        };
+
+        let first = received(7).await;
+        assert!((60..140).contains(&first), "received {}", first);
+        // 같은 시드면 같은 결과
+        assert_eq!(received(7).await, first);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn datagrams_fall_back_to_the_stream_when_the_peer_has_none() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let c = node(&net, "10.0.0.3:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let c_addr: SocketAddr = "10.0.0.3:42000".parse().unwrap();
+        net.disable_datagrams(b_addr);
+        a.connect(b_addr).await.unwrap();
+        a.connect(c_addr).await.unwrap();
+        assert_eq!(a.max_datagram_size(b_addr).await, None);
+        assert_eq!(a.max_datagram_size(c_addr).await, Some(1200));
+
+        // 미지원 피어에는 Control 스트림으로, 지원하는 피어에는 데이터그램으로
+        let mut b_datagrams = b.subscribe_datagrams();
+        let mut c_datagrams = c.subscribe_datagrams();
+        let path = a.send_datagram(b_addr, b"fallback").await.unwrap();
+        assert_eq!(path, DeliveryPath::Stream);
+        let path = a.send_datagram(c_addr, b"direct").await.unwrap();
+        assert_eq!(path, DeliveryPath::Datagram);
+
+        // 데이터그램 한도를 넘는 페이로드도 스트림으로
+        let path = a.send_datagram(c_addr, &[7; 2000]).await.unwrap();
+        assert_eq!(path, DeliveryPath::Stream);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        assert_eq!(b_datagrams.try_recv().unwrap(), (a_addr, b"fallback".to_vec()));
+        assert_eq!(c_datagrams.try_recv().unwrap(), (a_addr, b"direct".to_vec()));
+        assert_eq!(c_datagrams.try_recv().unwrap(), (a_addr, vec![7; 2000]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn total_loss_drops_every_datagram() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        a.connect(b_addr).await.unwrap();
+        net.set_default_link(link(0, 1.0));
+
+        let mut datagrams = b.subscribe_datagrams();
+        for _ in 0..10 {
+            a.send_datagram(b_addr, b"lost").await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(datagrams.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn partition_stalls_streams_until_healed() {
+        let net = MemoryNetwork::new(1);
+        net.set_default_link(link(10, 0.0));
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let c = node(&net, "10.0.0.3:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        a.connect(b_addr).await.unwrap();
+        a.connect("10.0.0.3:42000".parse().unwrap()).await.unwrap();
+
+        let mut b_data = b.subscribe_data();
+        let mut c_data = c.subscribe_data();
+        net.partition(a_addr, b_addr);
+        a.broadcast(b"block").await;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(c_data.try_recv().unwrap(), (a_addr, b"block".to_vec()));
+        assert!(b_data.try_recv().is_err());
+
+        // Ping도 막히므로 Pong이 오지 않음
+        a.send_ping().await;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(pongs(&a).await, 1);
+
+        // 복구되면 멈춰 있던 스트림 데이터가 순서대로 도착
+        net.heal(a_addr, b_addr);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(b_data.try_recv().unwrap(), (a_addr, b"block".to_vec()));
+        assert_eq!(pongs(&a).await, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stalled_peer_fails_alone_in_fan_out() {
+        let net = MemoryNetwork::new(1);
+        net.set_default_link(link(10, 0.0));
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let _c = node(&net, "10.0.0.3:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let c_addr: SocketAddr = "10.0.0.3:42000".parse().unwrap();
+        a.connect(b_addr).await.unwrap();
+        a.connect(c_addr).await.unwrap();
+
+        // c로 가는 스트림은 흐름 제어 창이 차면 쓰기가 멈춤
+        net.partition(a_addr, c_addr);
+        let mut b_data = b.subscribe_data();
+        let payload = vec![1u8; 900 * 1024];
+        let report = a.broadcast_with_class(&payload, TrafficClass::Bulk).await;
+        assert!(report.is_complete());
+
+        let started = Instant::now();
+        let received = tokio::spawn(async move {
+            b_data.recv().await.unwrap();
+            b_data.recv().await.unwrap();
+            started.elapsed()
+        });
+        let report = a.broadcast_with_class(&payload, TrafficClass::Bulk).await;
+        assert_eq!(report.delivered, vec![b_addr]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, c_addr);
+        // 멈춘 피어는 send_timeout만큼 기다리지만 b는 그동안 바로 받음
+        assert!(started.elapsed() >= NetworkConfig::default().send_timeout);
+        assert!(received.await.unwrap() < Duration::from_secs(1));
+
+        // Ping 팬아웃도 c를 기다리지 않고 b의 Pong을 받음
+        let ping_started = Instant::now();
+        a.send_ping().await;
+        assert!(ping_started.elapsed() < Duration::from_secs(1));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(pongs(&a).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn partitioned_connect_times_out() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let _b = node(&net, "10.0.0.2:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+
+        net.partition_groups(&[a_addr], &[b_addr]);
+        assert!(a.connect(b_addr).await.is_err());
+        net.heal_all();
+        a.connect(b_addr).await.unwrap();
+        assert_eq!(a.peer_count().await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn handshake_exposes_verified_node_ids() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+
+        a.connect_expecting(b_addr, Some(b.node_id())).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(a.peer_node_id(b_addr).await, Some(b.node_id()));
+        assert_eq!(b.peer_node_id(a_addr).await, Some(a.node_id()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pinned_id_mismatch_is_rejected_before_registering() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let _b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+
+        let mut events = a.subscribe_peer_events();
+        let pinned = guild_discovery::NodeIdentity::generate().node_id();
+        let error = a.connect_expecting(b_addr, Some(pinned)).await.unwrap_err();
+        assert!(error.to_string().contains("does not match pinned"));
+        assert_eq!(a.peer_count().await, 0);
+        assert!(events.try_recv().is_err());
+        assert_eq!(a.get_stats().await.handshakes_rejected, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn forged_handshake_signature_is_rejected() {
+        let net = MemoryNetwork::new(1);
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let victim = guild_discovery::NodeIdentity::generate();
+        let impostor = guild_discovery::NodeIdentity::generate();
+
+        // 피해자의 공개키를 내세우지만 b의 nonce를 모르고 다른 키로 서명
+        let attacker = net.bind("10.0.0.9:42000".parse().unwrap());
+        let conn = attacker.connect(b_addr).await.unwrap();
+        let mut stream = conn.open_uni(0).await.unwrap();
+        let mut frames = wire::encode(&Message::Hello {
+            network_id: guild_discovery::DEFAULT_NETWORK_ID.to_string(),
+            nonce: [0; 32],
+        });
+        frames.extend(wire::encode(&Message::HelloAuth {
+            public_key: victim.public_key(),
+            signature: impostor.sign(b"guild-handshake-v2"),
+        }));
+        stream.write_all(&frames).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(b.peer_count().await, 0);
+        assert_eq!(b.get_stats().await.handshakes_rejected, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn relayed_handshake_fails_the_pinned_dial() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let b_id = b.node_id();
+
+        // 중계자는 a의 연결을 받아 b에 따로 연결하고, 양쪽 프레임을 그대로 넘김
+        let relay_addr: SocketAddr = "10.0.0.9:42000".parse().unwrap();
+        let relay = net.bind(relay_addr);
+        let dial = tokio::spawn({
+            let a = a.clone();
+            async move { a.connect_expecting(relay_addr, Some(b_id)).await }
+        });
+        let to_a = relay.accept().await.unwrap();
+        let to_b = relay.connect(b_addr).await.unwrap();
+        for (from, to) in [(to_a.clone(), to_b.clone()), (to_b, to_a)] {
+            tokio::spawn(async move {
+                let mut incoming = from.accept_uni().await.unwrap();
+                let mut outgoing = to.open_uni(0).await.unwrap();
+                while let Ok(Some(chunk)) = incoming.read_chunk(64 * 1024).await {
+                    if outgoing.write_all(&chunk).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        // b의 진짜 HelloAuth지만 a-중계자 연결의 채널 바인딩과 맞지 않음
+        let error = dial.await.unwrap().unwrap_err();
+        assert!(error.to_string().contains("invalid handshake signature"));
+        assert_eq!(a.peer_count().await, 0);
+        assert_eq!(a.get_stats().await.handshakes_rejected, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn other_network_never_reaches_peers() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let other = NetworkConfig {
+            network_id: "other-net".to_string(),
+            ..NetworkConfig::default()
+        };
+        let b = node_with_config(&net, "10.0.0.2:42000", other).await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+
+        let mut a_events = a.subscribe_peer_events();
+        let mut b_events = b.subscribe_peer_events();
+        assert!(a.connect_expecting(b_addr, None).await.is_err());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // b는 a의 Hello에서 거부하고, 어느 쪽도 상대를 피어로 등록하지 않음
+        assert_eq!(b.get_stats().await.handshakes_rejected, 1);
+        for (network, events) in [(&a, &mut a_events), (&b, &mut b_events)] {
+            assert_eq!(network.peer_count().await, 0);
+            assert!(events.try_recv().is_err());
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bulk_backlog_does_not_delay_control_and_classes_keep_order() {
+        let net = MemoryNetwork::new(1);
+        net.set_default_link(link(50, 0.0));
+        // Bulk/Consensus 배치는 20ms씩 기다리므로 완료를 기다리는 송신자는 밀림
+        let config = NetworkConfig {
+            batch_window: Duration::from_millis(20),
+            ..NetworkConfig::default()
+        };
+        let a = node_with_config(&net, "10.0.0.1:42000", config).await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        a.connect("10.0.0.2:42000".parse().unwrap()).await.unwrap();
+        let mut b_data = b.subscribe_data();
+
+        let sender = |class: TrafficClass, tag: u8| {
+            let a = a.clone();
+            tokio::spawn(async move {
+                for i in 0..50u8 {
+                    assert!(a.broadcast_with_class(&[tag, i], class).await.is_complete());
+                }
+            })
+        };
+        let bulk = sender(TrafficClass::Bulk, 0);
+        let consensus = sender(TrafficClass::Consensus, 1);
+
+        // Bulk가 1초 넘게 밀려 있어도 Ping은 한 번의 왕복으로 Pong을 받음
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        a.send_ping().await;
+        tokio::time::sleep(Duration::from_millis(101)).await;
+        assert_eq!(pongs(&a).await, 1);
+        assert!(!bulk.is_finished());
+
+        bulk.await.unwrap();
+        consensus.await.unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let mut received = [Vec::new(), Vec::new()];
+        while let Ok((_, data)) = b_data.try_recv() {
+            received[data[0] as usize].push(data[1]);
+        }
+        let expected: Vec<u8> = (0..50).collect();
+        assert_eq!(received, [expected.clone(), expected]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn queued_messages_share_a_batch() {
+        let net = MemoryNetwork::new(1);
+        let config = NetworkConfig {
+            batch_window: Duration::from_millis(5),
+            ..NetworkConfig::default()
+        };
+        let a = node_with_config(&net, "10.0.0.1:42000", config).await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        a.connect("10.0.0.2:42000".parse().unwrap()).await.unwrap();
+        let mut b_data = b.subscribe_data();
+        let before = a.get_stats().await;
+
+        let mut sends = tokio::task::JoinSet::new();
+        for i in 0..20u8 {
+            let a = a.clone();
+            sends.spawn(async move { a.broadcast(&[i]).await.is_complete() });
+        }
+        while let Some(sent) = sends.join_next().await {
+            assert!(sent.unwrap());
+        }
+
+        let after = a.get_stats().await;
+        assert_eq!(after.messages_sent - before.messages_sent, 20);
+        assert_eq!(after.batches_sent - before.batches_sent, 1);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let mut count = 0;
+        while b_data.try_recv().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 20);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn oversize_payload_fails_without_breaking_the_stream() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        a.connect(b_addr).await.unwrap();
+        let mut b_data = b.subscribe_data();
+
+        a.broadcast(b"before").await;
+        let oversize = vec![0; wire::MAX_PAYLOAD_LEN + 1];
+        let report = a.broadcast(&oversize).await;
+        assert!(report.delivered.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert!(a
+            .send_protocol(b_addr, "guild/test/1", &oversize, TrafficClass::Bulk)
+            .await
+            .is_err());
+        assert!(a.broadcast(b"after").await.is_complete());
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(b_data.try_recv().unwrap(), (a_addr, b"before".to_vec()));
+        assert_eq!(b_data.try_recv().unwrap(), (a_addr, b"after".to_vec()));
+        assert!(b_data.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bad_frames_are_skipped_without_closing_the_stream() {
+        let net = MemoryNetwork::new(1);
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let peer_addr: SocketAddr = "10.0.0.9:42000".parse().unwrap();
+        let identity = guild_discovery::NodeIdentity::generate();
+
+        let (_peer, _conn, mut stream, auth) = raw_peer(&net, peer_addr, b_addr, &identity).await;
+        stream.write_all(&auth).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(b.peer_node_id(peer_addr).await, Some(identity.node_id()));
+        let mut b_data = b.subscribe_data();
+
+        let mut too_large = wire::encode_raw(wire::MessageKind::Data as u8, 0, &[]);
+        too_large[5..9].copy_from_slice(&(wire::MAX_PAYLOAD_LEN as u32 + 1).to_be_bytes());
+        too_large.extend(vec![0; wire::MAX_PAYLOAD_LEN + 1]);
+        let mut frames = wire::encode_raw(wire::MessageKind::Ping as u8, 0, &[0xff]);
+        frames.extend(wire::encode(&Message::Data(b"one".to_vec())));
+        frames.extend(too_large);
+        frames.extend(wire::encode(&Message::Data(b"two".to_vec())));
+        frames.extend(wire::encode_raw(0x7f, wire::FLAG_CRITICAL, &[1]));
+        frames.extend(wire::encode(&Message::Data(b"three".to_vec())));
+        // 청크 경계가 버리는 프레임 중간에 걸쳐도 계속 읽음
+        for chunk in frames.chunks(100_000) {
+            stream.write_all(chunk).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        for expected in [&b"one"[..], b"two", b"three"] {
+            assert_eq!(b_data.try_recv().unwrap(), (peer_addr, expected.to_vec()));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn frames_before_our_handshake_completes_are_delivered_in_order() {
+        let net = MemoryNetwork::new(1);
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let peer_addr: SocketAddr = "10.0.0.9:42000".parse().unwrap();
+        let identity = guild_discovery::NodeIdentity::generate();
+        let mut b_data = b.subscribe_data();
+
+        // 상대는 이미 핸드셰이크를 마쳤다고 보고 Bulk 스트림으로 먼저 보냄
+        let (_peer, conn, mut control, auth) = raw_peer(&net, peer_addr, b_addr, &identity).await;
+        let mut bulk = conn.open_uni(0).await.unwrap();
+        let mut early = wire::encode(&Message::Data(b"first".to_vec()));
+        early.extend(wire::encode(&Message::Data(b"second".to_vec())));
+        bulk.write_all(&early).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(b.peer_count().await, 0);
+        assert!(b_data.try_recv().is_err());
+
+        control.write_all(&auth).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        bulk.write_all(&wire::encode(&Message::Data(b"third".to_vec())))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(b.peer_count().await, 1);
+        for expected in [&b"first"[..], b"second", b"third"] {
+            assert_eq!(b_data.try_recv().unwrap(), (peer_addr, expected.to_vec()));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_dials_to_one_address_both_complete() {
+        let net = MemoryNetwork::new(1);
+        net.set_default_link(link(10, 0.0));
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+
+        let mut events = a.subscribe_peer_events();
+
+        let (first, second) = tokio::join!(a.connect(b_addr), a.connect(b_addr));
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(a.peer_count().await, 1);
+        assert_eq!(a.peer_node_id(b_addr).await, Some(b.node_id()));
+
+        // 나중에 끝난 연결이 앞의 연결을 대신하고, 같은 노드이므로 Connected는 한 번만
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(
+            events.try_recv().unwrap(),
+            PeerEvent::Connected(b_addr, b.node_id())
+        );
+        assert!(events.try_recv().is_err());
+        assert_eq!(a.peer_count().await, 1);
+        assert_eq!(b.peer_count().await, 1);
+    }
+
+
+    #[tokio::test(start_paused = true)]
+    async fn dead_peer_is_closed_when_evicted() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        a.connect(b_addr).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(b.peer_count().await, 1);
+
+        // Pong이 오지 않아 헬스 체크가 제거하면 연결도 닫혀서 b 쪽도 정리됨
+        net.partition(a_addr, b_addr);
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        a.check_peer_health().await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(a.peer_count().await, 0);
+        assert_eq!(b.peer_count().await, 0);
+    }
+}
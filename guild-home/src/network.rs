@@ -1,26 +1,287 @@
 // Guild Home Network - QUIC 기반 초고속 P2P
+use crate::protocol::{
+    MessageHandler, PrefixedRecvStream, ProtocolError, ProtocolHandler, ProtocolRegistry,
+    StreamHandler,
+};
+use crate::recorder::{Direction, TrafficRecorder};
+use crate::transport::{QuicTransport, RecvStream, SendStream, Transport, TransportConnection};
+use crate::wire::{self, Frame, WireError};
 use crate::{log_connection, log_network, log_success};
-use quinn::{ClientConfig, Connection, Endpoint, ServerConfig};
+use guild_discovery::identity::{node_id_for_key, verify};
+use guild_discovery::{NodeId, NodeIdentity, DEFAULT_NETWORK_ID};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::{broadcast, oneshot, watch, RwLock};
+use tokio::task::JoinSet;
+// tokio Instant - 일시정지된 테스트 시계에서도 헬스 체크가 결정적으로 동작
+use tokio::time::Instant;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Message {
-    Ping { id: String, timestamp: u64 },
-    Pong { id: String, timestamp: u64 },
+    Ping {
+        id: String,
+        timestamp: u64,
+    },
+    Pong {
+        id: String,
+        timestamp: u64,
+    },
     Data(Vec<u8>),
+    /// 데이터그램 채널 페이로드 (스트림으로 폴백되어도 구독자에게 전달)
+    Datagram(Vec<u8>),
+    /// 등록된 프로토콜 핸들러로 전달되는 메시지
+    Protocol {
+        protocol: String,
+        data: Vec<u8>,
+    },
+    /// 수신 측에 해당 프로토콜 핸들러가 없음
+    ProtocolUnsupported {
+        protocol: String,
+    },
+    /// 프로토콜 스트림의 첫 프레임
+    OpenStream {
+        protocol: String,
+    },
+    /// 연결 직후 양쪽이 보내는 핸드셰이크 (network id가 다르면 연결을 끊음)
+    Hello {
+        network_id: String,
+        /// 상대가 서명할 일회용 챌린지
+        nonce: [u8; 32],
+    },
+    /// 이 연결과 양쪽 nonce에 대한 서명 (노드 id의 키를 가지고 있음을 증명)
+    HelloAuth {
+        public_key: [u8; 32],
+        signature: Vec<u8>,
+    },
+}
+
+/// 메시지가 전달된 경로
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryPath {
+    /// QUIC 비신뢰 데이터그램
+    Datagram,
+    /// 신뢰성 있는 uni 스트림 (피어가 데이터그램 미지원이거나 크기 초과 시)
+    Stream,
+}
+
+/// 피어 연결 상태 변화 (핸드셰이크에서 확인한 노드 id 포함)
+///
+/// 노드 id를 함께 보내므로 Disconnected를 받은 시점에 peers에서 이미 빠졌어도 어떤 노드가
+/// 떠났는지 알 수 있다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerEvent {
+    /// 핸드셰이크를 마치고 peers에 등록됨
+    Connected(SocketAddr, NodeId),
+    /// 연결이 끊겼거나 응답이 없어 peers에서 제거됨
+    Disconnected(SocketAddr, NodeId),
+}
+
+/// 트래픽 클래스 - 클래스마다 별도 큐와 QUIC 스트림 우선순위를 가짐
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrafficClass {
+    /// Ping/Pong 등 연결 유지 메시지 (최우선)
+    Control,
+    /// 블록 제안, 투표 등 컨센서스 메시지
+    Consensus,
+    /// 일반 데이터 및 대용량 전송
+    Bulk,
+}
+
+impl TrafficClass {
+    pub const ALL: [TrafficClass; 3] = [
+        TrafficClass::Control,
+        TrafficClass::Consensus,
+        TrafficClass::Bulk,
+    ];
+
+    /// QUIC 스트림 우선순위 (값이 클수록 먼저 전송됨)
+    pub fn priority(self) -> i32 {
+        match self {
+            TrafficClass::Control => 2,
+            TrafficClass::Consensus => 1,
+            TrafficClass::Bulk => 0,
+        }
+    }
+
+    /// 피어별 전송 큐 크기
+    fn queue_capacity(self) -> usize {
+        match self {
+            TrafficClass::Control => 64,
+            TrafficClass::Consensus => 256,
+            TrafficClass::Bulk => 1024,
+        }
+    }
+}
+
+/// 전송 큐 항목 (ack가 있으면 스트림 쓰기 결과를 알려줌)
+#[derive(Debug)]
+struct Outbound {
+    frame: Vec<u8>,
+    ack: Option<oneshot::Sender<Result<(), String>>>,
+}
+
+/// 한 번의 스트림 쓰기로 보낼 프레임 묶음
+#[derive(Default)]
+struct Batch {
+    bytes: Vec<u8>,
+    acks: Vec<Option<oneshot::Sender<Result<(), String>>>>,
+}
+
+impl Batch {
+    fn push(&mut self, outbound: Outbound) {
+        self.bytes.extend_from_slice(&outbound.frame);
+        self.acks.push(outbound.ack);
+    }
+
+    /// 배치에 포함된 모든 전송 결과 통지
+    fn complete(self, result: Result<(), String>) {
+        for ack in self.acks.into_iter().flatten() {
+            let _ = ack.send(result.clone());
+        }
+    }
+}
+
+/// 피어별 트래픽 클래스 전송 큐
+#[derive(Debug, Clone)]
+pub struct PeerQueues {
+    control: mpsc::Sender<Outbound>,
+    consensus: mpsc::Sender<Outbound>,
+    bulk: mpsc::Sender<Outbound>,
+}
+
+impl PeerQueues {
+    fn get(&self, class: TrafficClass) -> &mpsc::Sender<Outbound> {
+        match class {
+            TrafficClass::Control => &self.control,
+            TrafficClass::Consensus => &self.consensus,
+            TrafficClass::Bulk => &self.bulk,
+        }
+    }
+
+    /// 큐에 메시지 추가 (가득 차면 버리고 false 반환 - 다른 클래스는 막지 않음)
+    fn enqueue(&self, addr: SocketAddr, class: TrafficClass, frame: Vec<u8>) -> bool {
+        let outbound = Outbound { frame, ack: None };
+        match self.get(class).try_send(outbound) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                log_network!("⚠️ {:?} queue full for {}, dropping message", class, addr);
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// 큐에 넣고 스트림에 실제로 쓰일 때까지 대기 (timeout 초과 시 실패)
+    async fn send(
+        &self,
+        class: TrafficClass,
+        frame: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        let (ack, done) = oneshot::channel();
+        let outbound = Outbound {
+            frame,
+            ack: Some(ack),
+        };
+        match self.get(class).try_send(outbound) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => return Err(format!("{:?} queue full", class)),
+            Err(TrySendError::Closed(_)) => return Err("connection closed".to_string()),
+        }
+
+        match tokio::time::timeout(timeout, done).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("connection closed".to_string()),
+            Err(_) => Err(format!("timed out after {:?}", timeout)),
+        }
+    }
+}
+
+/// 여러 피어에게 동시에 전송한 결과
+#[derive(Debug, Clone, Default)]
+pub struct SendReport {
+    pub delivered: Vec<SocketAddr>,
+    pub failed: Vec<(SocketAddr, String)>,
+}
+
+impl SendReport {
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// 네트워크 설정
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    /// 피어가 지원하면 Ping/Pong을 데이터그램으로 전송
+    pub datagram_pings: bool,
+    /// 로컬 데이터그램 수신 버퍼 크기 (None이면 데이터그램 비활성화)
+    pub datagram_receive_buffer: Option<usize>,
+    /// 피어별 전송 타임아웃 (브로드캐스트/Ping 팬아웃)
+    pub send_timeout: Duration,
+    /// 트래픽 클래스마다 오래 유지되는 스트림을 재사용 (false면 메시지마다 새 스트림)
+    pub persistent_streams: bool,
+    /// 작은 메시지를 모으는 최대 대기 시간 (Control 클래스는 기다리지 않음)
+    ///
+    /// 0이면 기다리지 않고 이전 쓰기 동안 큐에 쌓인 메시지만 합친다. 전송 완료를
+    /// 기다리는 송신자가 대부분이면 창을 늘려도 배치가 커지지 않고 지연만 늘어난다.
+    pub batch_window: Duration,
+    /// 한 번에 쓰는 배치의 최대 크기
+    pub max_batch_bytes: usize,
+    /// 송수신 프레임을 기록할 파일 (None이면 녹화하지 않음)
+    pub recording_path: Option<PathBuf>,
+    /// 이 노드가 속한 network id (핸드셰이크에서 다른 network의 피어를 거부)
+    pub network_id: String,
+    /// 연결 후 상대의 Hello를 기다리는 최대 시간
+    pub handshake_timeout: Duration,
+    /// 핸드셰이크에서 노드 id를 증명할 때 쓰는 키
+    pub identity: NodeIdentity,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            datagram_pings: false,
+            datagram_receive_buffer: Some(64 * 1024),
+            send_timeout: Duration::from_secs(5),
+            persistent_streams: true,
+            batch_window: Duration::ZERO,
+            max_batch_bytes: 64 * 1024,
+            recording_path: None,
+            network_id: DEFAULT_NETWORK_ID.to_string(),
+            handshake_timeout: Duration::from_secs(5),
+            identity: NodeIdentity::generate(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PeerInfo {
-    pub connection: Connection,
+    pub connection: Arc<dyn TransportConnection>,
     pub last_ping: Instant,
     pub last_pong: Instant,
     pub latency_ms: u64,
+    /// 핸드셰이크 서명으로 확인한 상대의 노드 id
+    pub node_id: Option<NodeId>,
+    /// 이 항목을 만든 연결 (같은 주소로 맺은 다른 연결과 구분)
+    conn_id: u64,
+    queues: PeerQueues,
+    /// 피어가 지원하지 않는다고 응답한 프로토콜
+    unsupported_protocols: HashSet<String>,
+}
+
+impl PeerInfo {
+    /// 확인한 노드 id (peers에는 서명을 확인한 피어만 등록되므로 대신 쓰는 주소 해시는 쓰이지 않음)
+    fn verified_id(&self, addr: SocketAddr) -> NodeId {
+        self.node_id.unwrap_or_else(|| NodeId::from_addr(&addr))
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -29,14 +290,71 @@ pub struct NetworkStats {
     pub pongs_received: u64,
     pub messages_sent: u64,
     pub messages_received: u64,
+    pub datagrams_sent: u64,
+    pub datagrams_received: u64,
     pub connections_established: u64,
     pub connections_lost: u64,
+    /// 스트림에 쓴 배치 수 (messages_sent / batches_sent = 평균 배치 크기)
+    pub batches_sent: u64,
+    /// network id나 서명이 맞지 않거나 Hello가 오지 않아 끊은 연결 수
+    pub handshakes_rejected: u64,
 }
 
-pub struct Network {
-    endpoint: Endpoint,
+/// 핸드셰이크 전에 한 스트림에 쌓아 두는 프레임의 최대 크기 (프레임 하나는 항상 담음)
+const MAX_EARLY_BYTES: usize = wire::HEADER_LEN + wire::MAX_PAYLOAD_LEN;
+
+/// Hello를 기다리는 연결 (핸드셰이크가 끝나야 peers에 등록됨)
+///
+/// 같은 주소로 동시에 여러 연결이 맺어질 수 있으므로 연결 id로 구분한다.
+struct PendingPeer {
+    addr: SocketAddr,
+    info: PeerInfo,
+    /// 핸드셰이크가 끝나면 true (실패해서 버려지면 수신 측은 에러를 받음)
+    verified: watch::Sender<bool>,
+    /// connect()가 핸드셰이크 결과를 기다리는 경우
+    waiter: Option<oneshot::Sender<Result<(), String>>>,
+    /// 상대에게 서명을 요구한 nonce
+    nonce: [u8; 32],
+    /// 상대가 Hello로 보낸 nonce (상대 서명에도 포함됨)
+    peer_nonce: Option<[u8; 32]>,
+    /// Hello보다 먼저 도착한 HelloAuth (Hello를 받으면 검증)
+    early_auth: Option<([u8; 32], Vec<u8>)>,
+    /// 고정된 노드 id (다른 키로 서명하면 거부)
+    expected_id: Option<NodeId>,
+    /// 같은 network의 Hello를 받음
+    hello_received: bool,
+}
+
+impl Shared {
+    fn record(&self, direction: Direction, peer: SocketAddr, path: DeliveryPath, frames: &[u8]) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(direction, peer, path, frames);
+        }
+    }
+}
+
+/// 연결 처리 태스크들이 공유하는 상태
+#[derive(Clone)]
+struct Shared {
     peers: Arc<RwLock<HashMap<SocketAddr, PeerInfo>>>,
+    /// 연결 id -> 핸드셰이크 중인 연결
+    handshakes: Arc<RwLock<HashMap<u64, PendingPeer>>>,
+    next_conn_id: Arc<AtomicU64>,
     stats: Arc<RwLock<NetworkStats>>,
+    datagrams: broadcast::Sender<(SocketAddr, Vec<u8>)>,
+    /// 피어가 보낸 일반 데이터 (Message::Data)
+    data: broadcast::Sender<(SocketAddr, Vec<u8>)>,
+    peer_events: broadcast::Sender<PeerEvent>,
+    /// 피어와 정상적으로 주고받은 시점 (핸드셰이크 완료, Pong 수신)
+    contacts: broadcast::Sender<SocketAddr>,
+    config: Arc<NetworkConfig>,
+    protocols: Arc<ProtocolRegistry>,
+    recorder: Option<Arc<TrafficRecorder>>,
+}
+
+pub struct Network {
+    transport: Arc<dyn Transport>,
+    shared: Shared,
 }
 
 impl Network {
@@ -45,87 +363,67 @@ impl Network {
     }
 
     pub async fn with_port(port: u16) -> Self {
-        // QUIC 서버 설정 (자체 서명 인증서)
-        let server_config = Self::make_server_config();
-        let client_config = Self::make_client_config();
-
-        let mut endpoint = None;
-        let mut current_port = port;
-        let max_attempts = 100; // 최대 100번 시도
-
-        // Address already in use 에러 시 포트를 1씩 증가시키며 재시도
-        for attempt in 0..max_attempts {
-            let addr = format!("0.0.0.0:{}", current_port);
-            match Endpoint::server(server_config.clone(), addr.parse().unwrap()) {
-                Ok(ep) => {
-                    endpoint = Some(ep);
-                    if attempt > 0 {
-                        log_network!(
-                            "✅ Found available port {} after {} attempts",
-                            current_port,
-                            attempt + 1
-                        );
-                    }
-                    break;
-                }
-                Err(e) => {
-                    let error_msg = e.to_string();
-                    if error_msg.contains("Address already in use")
-                        || error_msg.contains("already being used")
-                    {
-                        log_network!(
-                            "⚠️ Port {} already in use, trying port {}",
-                            current_port,
-                            current_port + 1
-                        );
-                        current_port += 1;
-                    } else {
-                        panic!("Failed to create endpoint: {:?}", e);
-                    }
-                }
-            }
-        }
+        Self::with_config(port, NetworkConfig::default()).await
+    }
 
-        let mut endpoint = endpoint.expect("Failed to find available port after maximum attempts");
-        endpoint.set_default_client_config(client_config);
+    pub async fn with_config(port: u16, config: NetworkConfig) -> Self {
+        let transport = QuicTransport::bind(port, &config);
+        Self::with_transport(Arc::new(transport), config).await
+    }
 
-        let addr = endpoint.local_addr().unwrap();
+    /// 임의의 Transport(QUIC, 인메모리 등) 위에 네트워크 구성
+    pub async fn with_transport(transport: Arc<dyn Transport>, config: NetworkConfig) -> Self {
+        let addr = transport.local_addr();
         log_network!("Listening on {}", addr);
 
+        let recorder = match &config.recording_path {
+            Some(path) => match TrafficRecorder::create(path).await {
+                Ok(recorder) => Some(Arc::new(recorder)),
+                Err(e) => {
+                    let error = e.to_string();
+                    log_network!("⚠️ Failed to start traffic recording: {}", error);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let (datagrams, _) = broadcast::channel(256);
+        let (contacts, _) = broadcast::channel(256);
+        // 컨센서스 메시지가 몰려도 구독자가 뒤처지지 않도록 넉넉하게
+        let (data, _) = broadcast::channel(1024);
+        let (peer_events, _) = broadcast::channel(256);
         let network = Self {
-            endpoint: endpoint.clone(),
-            peers: Arc::new(RwLock::new(HashMap::new())),
-            stats: Arc::new(RwLock::new(NetworkStats::default())),
+            transport: transport.clone(),
+            shared: Shared {
+                peers: Arc::new(RwLock::new(HashMap::new())),
+                handshakes: Arc::new(RwLock::new(HashMap::new())),
+                next_conn_id: Arc::new(AtomicU64::new(0)),
+                stats: Arc::new(RwLock::new(NetworkStats::default())),
+                datagrams,
+                data,
+                peer_events,
+                contacts,
+                config: Arc::new(config),
+                protocols: Arc::new(ProtocolRegistry::new()),
+                recorder,
+            },
         };
 
         // 연결 수락 루프
-        let peers = network.peers.clone();
-        let stats = network.stats.clone();
-        let endpoint_clone = endpoint.clone();
+        let shared = network.shared.clone();
         tokio::spawn(async move {
-            while let Some(conn) = endpoint_clone.accept().await {
-                let peers = peers.clone();
-                let stats = stats.clone();
+            while let Some(conn) = transport.accept().await {
+                let shared = shared.clone();
                 tokio::spawn(async move {
-                    if let Ok(conn) = conn.await {
-                        let addr = conn.remote_address();
-                        log_success!("New peer: {}", addr);
-
-                        // 연결 통계 업데이트
-                        stats.write().await.connections_established += 1;
-
-                        let peer_info = PeerInfo {
-                            connection: conn.clone(),
-                            last_ping: Instant::now(),
-                            last_pong: Instant::now(),
-                            latency_ms: 0,
-                        };
+                    let addr = conn.remote_address();
+                    log_success!("New peer: {}", addr);
 
-                        peers.write().await.insert(addr, peer_info);
+                    let (conn_id, verified) =
+                        Self::register_peer(conn.clone(), addr, &shared, None, None).await;
 
-                        // 이 피어로부터 메시지 수신 처리
-                        Self::handle_peer_messages(conn, addr, peers.clone(), stats.clone()).await;
-                    }
+                    // 이 피어로부터 메시지 수신 처리
+                    Self::handle_peer_messages(conn, addr, conn_id, verified, shared).await;
                 });
             }
         });
@@ -133,89 +431,343 @@ impl Network {
         network
     }
 
+    /// 피어에 연결하고 핸드셰이크가 끝날 때까지 대기
+    ///
+    /// 상대가 다른 network id를 보내거나, nonce 서명으로 노드 id를 증명하지 못하거나,
+    /// handshake_timeout 안에 핸드셰이크를 마치지 않으면 연결을 끊고 에러를 반환한다.
     pub async fn connect(
         &self,
         addr: SocketAddr,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let conn = self.endpoint.connect(addr, "localhost")?.await?;
-        log_connection!("Connected to {}", addr);
-
-        // 연결 통계 업데이트
-        self.stats.write().await.connections_established += 1;
+        self.connect_expecting(addr, None).await
+    }
 
-        let peer_info = PeerInfo {
-            connection: conn.clone(),
-            last_ping: Instant::now(),
-            last_pong: Instant::now(),
-            latency_ms: 0,
-        };
+    /// connect()와 같지만, expected_id가 있으면 상대가 그 노드 id의 키로 서명해야
+    /// peers에 등록한다 (다르면 PeerEvent 없이 연결을 끊고 에러를 반환)
+    pub async fn connect_expecting(
+        &self,
+        addr: SocketAddr,
+        expected_id: Option<NodeId>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.transport.connect(addr).await?;
 
-        self.peers.write().await.insert(addr, peer_info);
+        let (waiter, handshake) = oneshot::channel();
+        let (conn_id, verified) =
+            Self::register_peer(conn.clone(), addr, &self.shared, Some(waiter), expected_id)
+                .await;
 
         // 이 피어로부터 메시지 수신 처리
-        let peers = self.peers.clone();
-        let stats = self.stats.clone();
+        let shared = self.shared.clone();
         tokio::spawn(async move {
-            Self::handle_peer_messages(conn, addr, peers, stats).await;
+            Self::handle_peer_messages(conn, addr, conn_id, verified, shared).await;
         });
 
-        Ok(())
+        match handshake.await {
+            Ok(Ok(())) => {
+                log_connection!("Connected to {}", addr);
+                Ok(())
+            }
+            Ok(Err(reason)) => Err(reason.into()),
+            Err(_) => Err("connection closed during handshake".into()),
+        }
     }
 
-    pub async fn broadcast(&self, data: &[u8]) {
+    /// 모든 피어에게 데이터 전송 (Bulk 클래스)
+    pub async fn broadcast(&self, data: &[u8]) -> SendReport {
+        self.broadcast_with_class(data, TrafficClass::Bulk).await
+    }
+
+    /// 지정한 트래픽 클래스로 모든 피어에게 동시에 전송
+    ///
+    /// 페이로드가 wire::MAX_PAYLOAD_LEN을 넘으면 보내지 않고 모든 피어를 실패로 보고한다.
+    pub async fn broadcast_with_class(&self, data: &[u8], class: TrafficClass) -> SendReport {
         let msg = Message::Data(data.to_vec());
-        let serialized = bincode::serialize(&msg).unwrap();
+        // 락은 스냅샷을 뜨는 동안만 잡고, 실제 전송은 피어별로 병렬 처리
+        let targets = self.snapshot_queues().await;
+        let serialized = match wire::try_encode(&msg) {
+            Ok(serialized) => serialized,
+            Err(e) => return Self::fail_all(targets, e),
+        };
+        let timeout = self.shared.config.send_timeout;
 
-        let peers = self.peers.read().await;
-        for (_addr, peer_info) in peers.iter() {
-            if let Ok(mut send) = peer_info.connection.open_uni().await {
-                let _ = send.write_all(&serialized).await;
-                let _ = send.finish().await;
-            }
+        let mut tasks = JoinSet::new();
+        for (addr, queues) in targets {
+            let frame = serialized.clone();
+            tasks.spawn(async move { (addr, queues.send(class, frame, timeout).await) });
         }
+
+        Self::collect_report(tasks).await
     }
 
-    pub async fn send_ping(&self) {
-        let peers = self.peers.read().await;
-        let peer_count = peers.len();
-        log_network!("📍 Sending ping to {} peers", peer_count);
+    /// 특정 피어에게만 데이터 전송 (스트림에 쓰일 때까지 대기)
+    pub async fn send_with_class(
+        &self,
+        addr: SocketAddr,
+        data: &[u8],
+        class: TrafficClass,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let queues = self
+            .shared
+            .peers
+            .read()
+            .await
+            .get(&addr)
+            .map(|peer| peer.queues.clone())
+            .ok_or_else(|| format!("Unknown peer: {}", addr))?;
+        let serialized = wire::try_encode(&Message::Data(data.to_vec()))
+            .map_err(|e| format!("Failed to send to {}: {}", addr, e))?;
+        queues
+            .send(class, serialized, self.shared.config.send_timeout)
+            .await
+            .map_err(|e| format!("Failed to send to {}: {}", addr, e).into())
+    }
 
-        for (addr, peer_info) in peers.iter() {
-            let ping_id = uuid::Uuid::new_v4().to_string();
-            let ping_id_copy = ping_id.clone(); // 복사본 생성
-            let addr_copy = *addr; // 복사
+    /// 피어와 협상된 최대 데이터그램 크기 (피어가 지원하지 않으면 None)
+    pub async fn max_datagram_size(&self, addr: SocketAddr) -> Option<usize> {
+        let peers = self.shared.peers.read().await;
+        peers
+            .get(&addr)
+            .and_then(|peer| peer.connection.max_datagram_size())
+    }
 
-            let msg = Message::Ping {
-                id: ping_id.clone(),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64,
-            };
+    /// 비신뢰 데이터그램으로 전송 (미지원이거나 크기 초과 시 Control 스트림으로 폴백)
+    pub async fn send_datagram(
+        &self,
+        addr: SocketAddr,
+        data: &[u8],
+    ) -> Result<DeliveryPath, Box<dyn std::error::Error + Send + Sync>> {
+        let peer = self
+            .shared
+            .peers
+            .read()
+            .await
+            .get(&addr)
+            .cloned()
+            .ok_or_else(|| format!("Unknown peer: {}", addr))?;
 
-            let serialized = bincode::serialize(&msg).unwrap();
-            let serialized_len = serialized.len(); // 길이 미리 저장
+        let msg = Message::Datagram(data.to_vec());
+        // 스트림으로 폴백될 수 있으므로 프레임 한도를 넘으면 보내지 않음
+        wire::try_encode(&msg)?;
+        Self::send_unreliable(&peer, addr, &msg, &self.shared)
+            .await
+            .ok_or_else(|| format!("Failed to send datagram to {}", addr).into())
+    }
 
-            log_network!(
-                "📤 Sending Ping {} to {} ({} bytes)",
-                ping_id_copy,
-                addr_copy,
-                serialized_len
-            );
+    /// 데이터그램 채널 구독 (송신 피어 주소, 페이로드)
+    pub fn subscribe_datagrams(&self) -> broadcast::Receiver<(SocketAddr, Vec<u8>)> {
+        self.shared.datagrams.subscribe()
+    }
 
-            match peer_info.connection.open_uni().await {
-                Ok(mut send) => match send.write_all(&serialized).await {
-                    Ok(_) => match send.finish().await {
-                        Ok(_) => log_network!("✅ Ping {} sent to {}", ping_id, addr_copy),
-                        Err(e) => {
-                            log_network!("❌ Failed to finish send to {}: {:?}", addr_copy, e)
-                        }
-                    },
-                    Err(e) => log_network!("❌ Failed to write ping to {}: {:?}", addr_copy, e),
-                },
-                Err(e) => log_network!("❌ Failed to open stream to {}: {:?}", addr_copy, e),
-            }
+    /// 일반 데이터 구독 (송신 피어 주소, 페이로드) - broadcast()로 보낸 데이터
+    pub fn subscribe_data(&self) -> broadcast::Receiver<(SocketAddr, Vec<u8>)> {
+        self.shared.data.subscribe()
+    }
+
+    /// 피어 연결/종료 구독
+    pub fn subscribe_peer_events(&self) -> broadcast::Receiver<PeerEvent> {
+        self.shared.peer_events.subscribe()
+    }
+
+    /// 피어 접촉 구독 (핸드셰이크를 마치거나 Pong을 받을 때마다 피어 주소)
+    ///
+    /// 탐색 계층이 피어의 마지막 확인 시각을 갱신하는 데 사용
+    pub fn subscribe_contacts(&self) -> broadcast::Receiver<SocketAddr> {
+        self.shared.contacts.subscribe()
+    }
+
+    /// 메시지 기반 프로토콜 핸들러 등록
+    pub fn register_message_handler(
+        &self,
+        protocol: &str,
+        handler: impl MessageHandler + 'static,
+    ) -> Result<(), ProtocolError> {
+        self.shared
+            .protocols
+            .register(protocol, ProtocolHandler::Message(Arc::new(handler)))
+    }
+
+    /// 스트림 기반 프로토콜 핸들러 등록
+    pub fn register_stream_handler(
+        &self,
+        protocol: &str,
+        handler: impl StreamHandler + 'static,
+    ) -> Result<(), ProtocolError> {
+        self.shared
+            .protocols
+            .register(protocol, ProtocolHandler::Stream(Arc::new(handler)))
+    }
+
+    pub fn unregister_protocol(&self, protocol: &str) -> bool {
+        self.shared.protocols.unregister(protocol)
+    }
+
+    /// 로컬에 등록된 프로토콜 목록
+    pub fn protocols(&self) -> Vec<String> {
+        self.shared.protocols.protocols()
+    }
+
+    /// 피어가 프로토콜을 지원하지 않는다고 응답했으면 false (연결되지 않은 피어는 None)
+    pub async fn peer_supports(&self, addr: SocketAddr, protocol: &str) -> Option<bool> {
+        let peers = self.shared.peers.read().await;
+        peers
+            .get(&addr)
+            .map(|peer| !peer.unsupported_protocols.contains(protocol))
+    }
+
+    /// 특정 피어에게 프로토콜 메시지 전송 (스트림에 쓰일 때까지 대기)
+    pub async fn send_protocol(
+        &self,
+        addr: SocketAddr,
+        protocol: &str,
+        data: &[u8],
+        class: TrafficClass,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        crate::protocol::validate_id(protocol)?;
+        let queues = self.protocol_queues(addr, protocol).await?;
+
+        let msg = Message::Protocol {
+            protocol: protocol.to_string(),
+            data: data.to_vec(),
+        };
+        let serialized = wire::try_encode(&msg)
+            .map_err(|e| format!("Failed to send {} to {}: {}", protocol, addr, e))?;
+        queues
+            .send(class, serialized, self.shared.config.send_timeout)
+            .await
+            .map_err(|e| format!("Failed to send {} to {}: {}", protocol, addr, e).into())
+    }
+
+    /// 프로토콜을 지원하는 모든 피어에게 동시에 전송
+    pub async fn broadcast_protocol(
+        &self,
+        protocol: &str,
+        data: &[u8],
+        class: TrafficClass,
+    ) -> SendReport {
+        let msg = Message::Protocol {
+            protocol: protocol.to_string(),
+            data: data.to_vec(),
+        };
+        let timeout = self.shared.config.send_timeout;
+
+        let targets: Vec<(SocketAddr, PeerQueues)> = self
+            .shared
+            .peers
+            .read()
+            .await
+            .iter()
+            .filter(|(_, info)| !info.unsupported_protocols.contains(protocol))
+            .map(|(addr, info)| (*addr, info.queues.clone()))
+            .collect();
+        let serialized = match wire::try_encode(&msg) {
+            Ok(serialized) => serialized,
+            Err(e) => return Self::fail_all(targets, e),
+        };
+
+        let mut tasks = JoinSet::new();
+        for (addr, queues) in targets {
+            let frame = serialized.clone();
+            tasks.spawn(async move { (addr, queues.send(class, frame, timeout).await) });
+        }
+
+        Self::collect_report(tasks).await
+    }
+
+    /// 피어에게 프로토콜 전용 스트림 열기 (상대의 스트림 핸들러가 받음)
+    pub async fn open_protocol_stream(
+        &self,
+        addr: SocketAddr,
+        protocol: &str,
+        class: TrafficClass,
+    ) -> Result<Box<dyn SendStream>, Box<dyn std::error::Error + Send + Sync>> {
+        crate::protocol::validate_id(protocol)?;
+        self.protocol_queues(addr, protocol).await?;
+
+        let conn = self
+            .shared
+            .peers
+            .read()
+            .await
+            .get(&addr)
+            .map(|peer| peer.connection.clone())
+            .ok_or(ProtocolError::NotConnected(addr))?;
+
+        let mut stream = conn.open_uni(class.priority()).await?;
+        let header = wire::encode(&Message::OpenStream {
+            protocol: protocol.to_string(),
+        });
+        stream.write_all(&header).await?;
+        self.shared
+            .record(Direction::Outbound, addr, DeliveryPath::Stream, &header);
+        Ok(stream)
+    }
+
+    /// 트래픽 녹화기 (녹화 중이 아니면 None)
+    pub fn recorder(&self) -> Option<Arc<TrafficRecorder>> {
+        self.shared.recorder.clone()
+    }
+
+    /// 녹화된 수신 프레임을 실제로 받은 것처럼 처리 (재현용)
+    pub async fn replay_inbound(&self, from: SocketAddr, path: DeliveryPath, frames: &[u8]) {
+        Self::handle_frames(frames, from, path, &self.shared).await;
+    }
+
+    pub async fn send_ping(&self) -> SendReport {
+        let peers: Vec<(SocketAddr, PeerInfo)> = self
+            .shared
+            .peers
+            .read()
+            .await
+            .iter()
+            .map(|(addr, info)| (*addr, info.clone()))
+            .collect();
+        let peer_count = peers.len();
+        log_network!("📍 Sending ping to {} peers", peer_count);
+
+        let mut tasks = JoinSet::new();
+        for (addr, peer_info) in peers {
+            let shared = self.shared.clone();
+            tasks.spawn(async move {
+                let ping_id = uuid::Uuid::new_v4().to_string();
+                let msg = Message::Ping {
+                    id: ping_id.clone(),
+                    timestamp: std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_millis() as u64,
+                };
+
+                let result = if shared.config.datagram_pings {
+                    Self::send_unreliable(&peer_info, addr, &msg, &shared)
+                        .await
+                        .ok_or_else(|| "send failed".to_string())
+                } else {
+                    // Control 큐는 Bulk 전송과 분리되어 있어 대용량 브로드캐스트에 밀리지 않음
+                    let serialized = wire::encode(&msg);
+                    peer_info
+                        .queues
+                        .send(
+                            TrafficClass::Control,
+                            serialized,
+                            shared.config.send_timeout,
+                        )
+                        .await
+                        .map(|_| DeliveryPath::Stream)
+                };
+
+                match result {
+                    Ok(path) => {
+                        log_network!("📤 Sent Ping {} to {} via {:?}", ping_id, addr, path);
+                        (addr, Ok(()))
+                    }
+                    Err(e) => (addr, Err(e)),
+                }
+            });
         }
+
+        let report = Self::collect_report(tasks).await;
+        self.shared.stats.write().await.pings_sent += report.delivered.len() as u64;
+        report
     }
 
     pub async fn check_peer_health(&self) {
@@ -223,7 +775,7 @@ impl Network {
         let timeout = Duration::from_secs(10); // 10초 타임아웃
 
         {
-            let peers = self.peers.read().await;
+            let peers = self.shared.peers.read().await;
             for (addr, peer_info) in peers.iter() {
                 if peer_info.last_pong.elapsed() > timeout {
                     let elapsed_time = peer_info.last_pong.elapsed();
@@ -238,30 +790,92 @@ impl Network {
             }
         }
 
-        // 응답하지 않는 피어 제거
+        // 응답하지 않는 피어 제거 (연결도 닫아서 스트림 태스크가 끝나도록)
         if !dead_peers.is_empty() {
-            let mut peers = self.peers.write().await;
+            let mut peers = self.shared.peers.write().await;
             for addr in dead_peers {
-                peers.remove(&addr);
-                log_network!("❌ Removed dead peer: {}", addr);
+                if let Some(peer) = peers.remove(&addr) {
+                    peer.connection.close();
+                    log_network!("❌ Removed dead peer: {}", addr);
+                    let node_id = peer.verified_id(addr);
+                    let _ = self.shared.peer_events.send(PeerEvent::Disconnected(addr, node_id));
+                }
             }
         }
     }
 
+    /// 피어 연결 종료 (연결되어 있지 않으면 false)
+    pub async fn disconnect(&self, addr: SocketAddr) -> bool {
+        let peer = self.shared.peers.write().await.remove(&addr);
+        let pending: Vec<PendingPeer> = {
+            let mut handshakes = self.shared.handshakes.write().await;
+            let conn_ids: Vec<u64> = handshakes
+                .iter()
+                .filter(|(_, pending)| pending.addr == addr)
+                .map(|(conn_id, _)| *conn_id)
+                .collect();
+            conn_ids
+                .iter()
+                .filter_map(|conn_id| handshakes.remove(conn_id))
+                .collect()
+        };
+        if let Some(peer) = &peer {
+            let node_id = peer.verified_id(addr);
+            let _ = self.shared.peer_events.send(PeerEvent::Disconnected(addr, node_id));
+        }
+        let connections: Vec<_> = peer
+            .map(|peer| peer.connection)
+            .into_iter()
+            .chain(pending.into_iter().map(|pending| pending.info.connection))
+            .collect();
+        if connections.is_empty() {
+            return false;
+        }
+        for connection in connections {
+            connection.close();
+        }
+        log_network!("👋 Disconnected from {}", addr);
+        true
+    }
+
+    /// 이 주소와 연결되어 있거나 핸드셰이크 중인지 (다시 다이얼할 필요가 없는지)
+    pub async fn has_connection(&self, addr: SocketAddr) -> bool {
+        if self.shared.peers.read().await.contains_key(&addr) {
+            return true;
+        }
+        self.shared
+            .handshakes
+            .read()
+            .await
+            .values()
+            .any(|pending| pending.addr == addr)
+    }
+
     pub async fn peer_count(&self) -> usize {
-        self.peers.read().await.len()
+        self.shared.peers.read().await.len()
+    }
+
+    /// 이 노드의 id (핸드셰이크에서 상대에게 증명하는 id)
+    pub fn node_id(&self) -> NodeId {
+        self.shared.config.identity.node_id()
+    }
+
+    /// 핸드셰이크에서 확인한 피어의 노드 id (연결되어 있지 않으면 None)
+    pub async fn peer_node_id(&self, addr: SocketAddr) -> Option<NodeId> {
+        self.shared.peers.read().await.get(&addr)?.node_id
     }
 
     pub fn local_port(&self) -> u16 {
-        self.endpoint.local_addr().unwrap().port()
+        self.transport.local_addr().port()
     }
 
     pub async fn get_stats(&self) -> NetworkStats {
-        self.stats.read().await.clone()
+        self.shared.stats.read().await.clone()
     }
 
     pub async fn get_peers_info(&self) -> Vec<(SocketAddr, PeerInfo)> {
-        self.peers
+        self.shared
+            .peers
             .read()
             .await
             .iter()
@@ -269,160 +883,907 @@ impl Network {
             .collect()
     }
 
+    /// 프로토콜을 보낼 수 있는 피어의 전송 큐 (미지원으로 확인된 피어면 에러)
+    async fn protocol_queues(
+        &self,
+        addr: SocketAddr,
+        protocol: &str,
+    ) -> Result<PeerQueues, ProtocolError> {
+        let peers = self.shared.peers.read().await;
+        let peer = peers.get(&addr).ok_or(ProtocolError::NotConnected(addr))?;
+        if peer.unsupported_protocols.contains(protocol) {
+            return Err(ProtocolError::Unsupported {
+                peer: addr,
+                protocol: protocol.to_string(),
+            });
+        }
+        Ok(peer.queues.clone())
+    }
+
+    /// 현재 피어들의 전송 큐 스냅샷 (락을 오래 잡지 않기 위함)
+    async fn snapshot_queues(&self) -> Vec<(SocketAddr, PeerQueues)> {
+        self.shared
+            .peers
+            .read()
+            .await
+            .iter()
+            .map(|(addr, info)| (*addr, info.queues.clone()))
+            .collect()
+    }
+
+    /// 보내기 전에 실패한 메시지를 모든 대상 피어의 실패로 보고
+    fn fail_all(targets: Vec<(SocketAddr, PeerQueues)>, error: WireError) -> SendReport {
+        let error = error.to_string();
+        let error_copy = error.clone();
+        log_network!("❌ Not sending message: {}", error_copy);
+        SendReport {
+            delivered: Vec::new(),
+            failed: targets
+                .into_iter()
+                .map(|(addr, _)| (addr, error.clone()))
+                .collect(),
+        }
+    }
+
+    /// 피어별 전송 태스크 결과 수집
+    async fn collect_report(mut tasks: JoinSet<(SocketAddr, Result<(), String>)>) -> SendReport {
+        let mut report = SendReport::default();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((addr, Ok(()))) => report.delivered.push(addr),
+                Ok((addr, Err(e))) => {
+                    let error = e.clone();
+                    log_network!("❌ Failed to send to {}: {}", addr, error);
+                    report.failed.push((addr, e));
+                }
+                Err(e) => {
+                    let error = e.to_string();
+                    log_network!("❌ Send task failed: {}", error);
+                }
+            }
+        }
+        report
+    }
+
+    /// 데이터그램으로 전송하고, 불가능하면 Control 스트림으로 폴백
+    async fn send_unreliable(
+        peer: &PeerInfo,
+        addr: SocketAddr,
+        msg: &Message,
+        shared: &Shared,
+    ) -> Option<DeliveryPath> {
+        let serialized = wire::encode(msg);
+
+        // 협상된 최대 크기 이내일 때만 데이터그램 사용
+        let fits = peer
+            .connection
+            .max_datagram_size()
+            .is_some_and(|max| serialized.len() <= max);
+        if fits {
+            match peer.connection.send_datagram(serialized.clone()) {
+                Ok(()) => {
+                    shared.record(
+                        Direction::Outbound,
+                        addr,
+                        DeliveryPath::Datagram,
+                        &serialized,
+                    );
+                    shared.stats.write().await.datagrams_sent += 1;
+                    return Some(DeliveryPath::Datagram);
+                }
+                Err(e) => {
+                    log_network!(
+                        "⚠️ Datagram to {} failed, falling back to stream: {}",
+                        addr,
+                        e
+                    );
+                }
+            }
+        }
+
+        peer.queues
+            .enqueue(addr, TrafficClass::Control, serialized)
+            .then_some(DeliveryPath::Stream)
+    }
+
+    /// 새 연결의 전송 태스크를 시작하고 Hello 전송
+    ///
+    /// 상대의 Hello와 HelloAuth를 모두 받아야 peers에 등록된다. 연결 id와 핸드셰이크
+    /// 완료를 알려주는 수신기를 반환한다.
+    async fn register_peer(
+        conn: Arc<dyn TransportConnection>,
+        addr: SocketAddr,
+        shared: &Shared,
+        waiter: Option<oneshot::Sender<Result<(), String>>>,
+        expected_id: Option<NodeId>,
+    ) -> (u64, watch::Receiver<bool>) {
+        // 연결 통계 업데이트
+        shared.stats.write().await.connections_established += 1;
+        let conn_id = shared.next_conn_id.fetch_add(1, Ordering::Relaxed);
+
+        let (control, control_rx) = mpsc::channel(TrafficClass::Control.queue_capacity());
+        let (consensus, consensus_rx) = mpsc::channel(TrafficClass::Consensus.queue_capacity());
+        let (bulk, bulk_rx) = mpsc::channel(TrafficClass::Bulk.queue_capacity());
+
+        Self::spawn_class_writer(
+            conn.clone(),
+            addr,
+            TrafficClass::Control,
+            control_rx,
+            shared,
+        );
+        Self::spawn_class_writer(
+            conn.clone(),
+            addr,
+            TrafficClass::Consensus,
+            consensus_rx,
+            shared,
+        );
+        Self::spawn_class_writer(conn.clone(), addr, TrafficClass::Bulk, bulk_rx, shared);
+
+        let info = PeerInfo {
+            connection: conn,
+            last_ping: Instant::now(),
+            last_pong: Instant::now(),
+            latency_ms: 0,
+            node_id: None,
+            conn_id,
+            unsupported_protocols: HashSet::new(),
+            queues: PeerQueues {
+                control,
+                consensus,
+                bulk,
+            },
+        };
+
+        let mut nonce = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let hello = Message::Hello {
+            network_id: shared.config.network_id.clone(),
+            nonce,
+        };
+        info.queues
+            .enqueue(addr, TrafficClass::Control, wire::encode(&hello));
+        let (verified, verified_rx) = watch::channel(false);
+        shared.handshakes.write().await.insert(
+            conn_id,
+            PendingPeer {
+                addr,
+                info,
+                verified,
+                waiter,
+                nonce,
+                peer_nonce: None,
+                early_auth: None,
+                expected_id,
+                hello_received: false,
+            },
+        );
+
+        // 제한 시간 안에 핸드셰이크가 끝나지 않으면 연결 종료
+        let shared = shared.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(shared.config.handshake_timeout).await;
+            let pending = shared.handshakes.write().await.remove(&conn_id);
+            if let Some(pending) = pending {
+                log_network!("⏱️ Handshake with {} timed out", addr);
+                Self::reject_peer(pending, "handshake timed out".to_string(), &shared).await;
+            }
+        });
+
+        (conn_id, verified_rx)
+    }
+
+    /// 상대의 Hello 처리 - 같은 network면 이 연결과 양쪽 nonce에 서명해 보내고, 아니면 연결 종료
+    async fn receive_hello(
+        network_id: String,
+        nonce: [u8; 32],
+        addr: SocketAddr,
+        conn_id: u64,
+        shared: &Shared,
+    ) {
+        let mut handshakes = shared.handshakes.write().await;
+        let Some(pending) = handshakes.get_mut(&conn_id) else {
+            // 이미 핸드셰이크가 끝난 연결의 중복 Hello
+            return;
+        };
+
+        if network_id != shared.config.network_id {
+            let pending = handshakes.remove(&conn_id).unwrap();
+            drop(handshakes);
+            let reason = format!("peer belongs to network {}", network_id);
+            let expected = shared.config.network_id.clone();
+            log_network!(
+                "🚫 Rejected {}: network {} (expected {})",
+                addr,
+                network_id,
+                expected
+            );
+            Self::reject_peer(pending, reason, shared).await;
+            return;
+        }
+
+        if pending.hello_received {
+            return;
+        }
+        let binding = match pending.info.connection.channel_binding() {
+            Ok(binding) => binding,
+            Err(e) => {
+                let pending = handshakes.remove(&conn_id).unwrap();
+                drop(handshakes);
+                let reason = format!("no channel binding: {}", e);
+                let reason_copy = reason.clone();
+                log_network!("🚫 Rejected {}: {}", addr, reason_copy);
+                Self::reject_peer(pending, reason, shared).await;
+                return;
+            }
+        };
+        pending.hello_received = true;
+        pending.peer_nonce = Some(nonce);
+        let identity = &shared.config.identity;
+        let transcript = handshake_transcript(&network_id, &binding, &nonce, &pending.nonce);
+        let auth = Message::HelloAuth {
+            public_key: identity.public_key(),
+            signature: identity.sign(&transcript),
+        };
+        pending
+            .info
+            .queues
+            .enqueue(addr, TrafficClass::Control, wire::encode(&auth));
+        let early_auth = pending.early_auth.take();
+        drop(handshakes);
+
+        match early_auth {
+            Some((public_key, signature)) => {
+                Self::receive_hello_auth(public_key, signature, addr, conn_id, shared).await
+            }
+            None => Self::complete_handshake(addr, conn_id, shared).await,
+        }
+    }
+
+    /// 상대의 HelloAuth 처리 - 이 연결과 양쪽 nonce에 대한 서명을 확인해 노드 id를 기록
+    ///
+    /// 서명에 상대의 Hello nonce가 들어가므로 Hello보다 먼저 오면 보관해 두었다가 검증한다.
+    async fn receive_hello_auth(
+        public_key: [u8; 32],
+        signature: Vec<u8>,
+        addr: SocketAddr,
+        conn_id: u64,
+        shared: &Shared,
+    ) {
+        let mut handshakes = shared.handshakes.write().await;
+        let Some(pending) = handshakes.get_mut(&conn_id) else {
+            return;
+        };
+        if pending.info.node_id.is_some() {
+            return;
+        }
+        let Some(peer_nonce) = pending.peer_nonce else {
+            pending.early_auth = Some((public_key, signature));
+            return;
+        };
+
+        let node_id = node_id_for_key(&public_key);
+        let verified = pending
+            .info
+            .connection
+            .channel_binding()
+            .is_ok_and(|binding| {
+                let transcript = handshake_transcript(
+                    &shared.config.network_id,
+                    &binding,
+                    &pending.nonce,
+                    &peer_nonce,
+                );
+                verify(&public_key, &transcript, &signature)
+            });
+        let reason = if !verified {
+            Some("invalid handshake signature".to_string())
+        } else {
+            match pending.expected_id {
+                Some(expected) if expected != node_id => Some(format!(
+                    "node id {} does not match pinned {}",
+                    &node_id.to_hex()[..16],
+                    &expected.to_hex()[..16]
+                )),
+                _ => None,
+            }
+        };
+        if let Some(reason) = reason {
+            let pending = handshakes.remove(&conn_id).unwrap();
+            drop(handshakes);
+            let reason_copy = reason.clone();
+            log_network!("🚫 Rejected {}: {}", addr, reason_copy);
+            Self::reject_peer(pending, reason, shared).await;
+            return;
+        }
+
+        pending.info.node_id = Some(node_id);
+        drop(handshakes);
+
+        Self::complete_handshake(addr, conn_id, shared).await;
+    }
+
+    /// Hello와 HelloAuth를 모두 받았으면 peers에 등록
+    async fn complete_handshake(addr: SocketAddr, conn_id: u64, shared: &Shared) {
+        let mut pending = {
+            let mut handshakes = shared.handshakes.write().await;
+            match handshakes.get(&conn_id) {
+                Some(pending) if pending.hello_received && pending.info.node_id.is_some() => {
+                    handshakes.remove(&conn_id).unwrap()
+                }
+                _ => return,
+            }
+        };
+
+        let waiter = pending.waiter.take();
+        let node_id = pending.info.verified_id(addr);
+        let replaced = shared.peers.write().await.insert(addr, pending.info);
+        // peers에 등록한 뒤 알려야 먼저 도착해 기다리던 프레임이 피어를 찾을 수 있음
+        pending.verified.send_replace(true);
+        log_network!("🤝 Handshake with {} complete", addr);
+        let _ = shared.contacts.send(addr);
+        // 같은 주소로 다시 맺은 연결이면 이전 연결을 닫음 (같은 노드면 이벤트 없이 교체)
+        match replaced {
+            Some(old) => {
+                old.connection.close();
+                let old_id = old.verified_id(addr);
+                if old_id != node_id {
+                    let _ = shared.peer_events.send(PeerEvent::Disconnected(addr, old_id));
+                    let _ = shared.peer_events.send(PeerEvent::Connected(addr, node_id));
+                }
+            }
+            None => {
+                let _ = shared.peer_events.send(PeerEvent::Connected(addr, node_id));
+            }
+        }
+        if let Some(waiter) = waiter {
+            let _ = waiter.send(Ok(()));
+        }
+    }
+
+    async fn reject_peer(pending: PendingPeer, reason: String, shared: &Shared) {
+        pending.info.connection.close();
+        shared.stats.write().await.handshakes_rejected += 1;
+        if let Some(waiter) = pending.waiter {
+            let _ = waiter.send(Err(reason));
+        }
+    }
+
+    /// 트래픽 클래스 큐를 비우며 우선순위가 지정된 스트림으로 전송
+    ///
+    /// 기본적으로 클래스마다 하나의 스트림을 열어 계속 재사용하고, 짧은 시간 안에
+    /// 쌓인 작은 메시지들은 한 번의 쓰기로 묶어서 보낸다. 프레임 경계는 wire 헤더의
+    /// length로 구분되므로 수신 측은 스트림 하나에서 여러 프레임을 순서대로 읽는다.
+    fn spawn_class_writer(
+        conn: Arc<dyn TransportConnection>,
+        addr: SocketAddr,
+        class: TrafficClass,
+        mut rx: mpsc::Receiver<Outbound>,
+        shared: &Shared,
+    ) {
+        let shared = shared.clone();
+        let stats = shared.stats.clone();
+        let config = shared.config.clone();
+        tokio::spawn(async move {
+            let mut stream: Option<Box<dyn SendStream>> = None;
+
+            while let Some(first) = rx.recv().await {
+                let batch = Self::collect_batch(first, &mut rx, class, &config).await;
+                let frame_count = batch.acks.len();
+
+                if stream.is_none() {
+                    match conn.open_uni(class.priority()).await {
+                        Ok(send) => stream = Some(send),
+                        Err(e) => {
+                            let error = e.to_string();
+                            batch.complete(Err(error.clone()));
+                            log_network!(
+                                "❌ Failed to open {:?} stream to {}: {}",
+                                class,
+                                addr,
+                                error
+                            );
+                            break;
+                        }
+                    }
+                }
+
+                let mut send = stream.take().unwrap();
+                if let Err(e) = send.write_all(&batch.bytes).await {
+                    // 스트림만 버리고 다음 배치에서 새로 열어 재시도
+                    let error = e.to_string();
+                    batch.complete(Err(error.clone()));
+                    log_network!(
+                        "❌ Failed to write {:?} batch to {}: {}",
+                        class,
+                        addr,
+                        error
+                    );
+                    continue;
+                }
+
+                shared.record(
+                    Direction::Outbound,
+                    addr,
+                    DeliveryPath::Stream,
+                    &batch.bytes,
+                );
+                if config.persistent_streams {
+                    stream = Some(send);
+                } else {
+                    // finish는 피어의 ACK까지 기다리므로 큐 처리를 막지 않도록 분리
+                    tokio::spawn(async move {
+                        let _ = send.finish().await;
+                    });
+                }
+
+                batch.complete(Ok(()));
+                let mut stats = stats.write().await;
+                stats.messages_sent += frame_count as u64;
+                stats.batches_sent += 1;
+            }
+
+            if let Some(mut send) = stream {
+                let _ = send.finish().await;
+            }
+        });
+    }
+
+    /// 첫 메시지 이후 배치 창 안에 도착한 메시지를 모음
+    async fn collect_batch(
+        first: Outbound,
+        rx: &mut mpsc::Receiver<Outbound>,
+        class: TrafficClass,
+        config: &NetworkConfig,
+    ) -> Batch {
+        let mut batch = Batch::default();
+        batch.push(first);
+
+        // 스트림을 재사용하지 않으면 메시지마다 따로 보냄
+        if !config.persistent_streams {
+            return batch;
+        }
+
+        // 이미 큐에 쌓인 메시지는 바로 합침
+        while batch.bytes.len() < config.max_batch_bytes {
+            match rx.try_recv() {
+                Ok(outbound) => batch.push(outbound),
+                Err(_) => break,
+            }
+        }
+
+        // Control은 지연에 민감하므로 기다리지 않음
+        if class == TrafficClass::Control || config.batch_window.is_zero() {
+            return batch;
+        }
+
+        let deadline = Instant::now() + config.batch_window;
+        while batch.bytes.len() < config.max_batch_bytes {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(outbound)) => batch.push(outbound),
+                _ => break,
+            }
+        }
+        batch
+    }
+
     async fn handle_peer_messages(
-        conn: Connection,
+        conn: Arc<dyn TransportConnection>,
         addr: SocketAddr,
-        peers: Arc<RwLock<HashMap<SocketAddr, PeerInfo>>>,
-        _stats: Arc<RwLock<NetworkStats>>,
+        conn_id: u64,
+        verified: watch::Receiver<bool>,
+        shared: Shared,
     ) {
         log_network!("👂 Starting message handler for {}", addr);
+
+        // 데이터그램 수신 루프
+        let datagram_conn = conn.clone();
+        let datagram_shared = shared.clone();
+        let datagram_verified = verified.clone();
+        tokio::spawn(async move {
+            while let Ok(datagram) = datagram_conn.read_datagram().await {
+                // Hello는 스트림으로만 오므로 핸드셰이크 전의 데이터그램은 버림 (비신뢰 채널)
+                if !*datagram_verified.borrow() {
+                    continue;
+                }
+                datagram_shared.record(Direction::Inbound, addr, DeliveryPath::Datagram, &datagram);
+                datagram_shared.stats.write().await.datagrams_received += 1;
+                Self::handle_frames(&datagram, addr, DeliveryPath::Datagram, &datagram_shared)
+                    .await;
+            }
+        });
+
         loop {
             match conn.accept_uni().await {
-                Ok(mut recv) => {
-                    let buf = match recv.read_to_end(1024 * 1024).await {
-                        Ok(data) => data,
-                        Err(e) => {
-                            log_network!("⚠️ Failed to read from {}: {:?}", addr, e);
-                            continue;
+                Ok(recv) => {
+                    // 스트림마다 별도 태스크로 읽어 큰 메시지가 Ping/Pong을 막지 않도록 함
+                    let shared = shared.clone();
+                    let verified = verified.clone();
+                    tokio::spawn(async move {
+                        Self::handle_stream(recv, addr, conn_id, verified, shared).await;
+                    });
+                }
+                Err(e) => {
+                    // 연결 상태 확인
+                    let error_msg = e.to_string();
+                    log_network!("🔌 Connection closed: {} ({})", addr, error_msg);
+                    let removed = {
+                        let mut peers = shared.peers.write().await;
+                        // 같은 주소로 새로 맺은 연결의 항목은 건드리지 않음
+                        let current = peers.get(&addr).is_some_and(|peer| peer.conn_id == conn_id);
+                        if current {
+                            peers.remove(&addr)
+                        } else {
+                            None
                         }
                     };
+                    if let Some(peer) = removed {
+                        let node_id = peer.verified_id(addr);
+                        let _ = shared.peer_events.send(PeerEvent::Disconnected(addr, node_id));
+                    }
+                    // 핸드셰이크 중이었으면 대기 중인 connect()는 에러를 받음
+                    shared.handshakes.write().await.remove(&conn_id);
+                    break;
+                }
+            }
+        }
+    }
 
-                    let buf_len = buf.len();
-                    log_network!("📨 Received {} bytes from {}", buf_len, addr);
-
-                    if buf.is_empty() {
-                        log_network!("⚠️ Empty message from {}", addr);
+    /// 스트림에서 프레임이 완성되는 대로 처리 (오래 유지되는 스트림과 일회성 스트림 모두 지원)
+    ///
+    /// 헤더는 읽었지만 처리할 수 없는 프레임(크기 초과, 잘못된 페이로드, 모르는 critical
+    /// kind)은 length만큼 건너뛰고, 같은 스트림에 이어서 오는 프레임은 계속 처리한다.
+    ///
+    /// 클래스마다 스트림이 다르므로, 먼저 핸드셰이크를 마친 상대의 데이터가 우리 쪽
+    /// HelloAuth보다 먼저 도착할 수 있다. 그런 프레임은 스트림별로 모아 두었다가
+    /// 핸드셰이크가 끝나면 순서대로 처리한다.
+    async fn handle_stream(
+        mut recv: Box<dyn RecvStream>,
+        addr: SocketAddr,
+        conn_id: u64,
+        mut verified: watch::Receiver<bool>,
+        shared: Shared,
+    ) {
+        let mut buf = Vec::new();
+        let mut first_frame = true;
+        let mut is_verified = *verified.borrow();
+        let mut early = EarlyFrames::default();
+        // 버리는 중인 프레임의 남은 바이트 (크기 초과 프레임은 버퍼에 모으지 않음)
+        let mut skip = 0usize;
+        loop {
+            let read = if is_verified {
+                recv.read_chunk(64 * 1024).await
+            } else {
+                tokio::select! {
+                    read = recv.read_chunk(64 * 1024) => read,
+                    done = async { verified.wait_for(|verified| *verified).await.is_ok() } => {
+                        // 핸드셰이크가 실패하면 연결이 닫히므로 더 읽지 않음
+                        if !done {
+                            return;
+                        }
+                        is_verified = true;
+                        early.replay(addr, &shared).await;
                         continue;
                     }
+                }
+            };
+            let chunk = match read {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => {
+                    log_network!("⚠️ Failed to read from {}: {:?}", addr, e);
+                    return;
+                }
+            };
+            buf.extend_from_slice(&chunk);
 
-                    match bincode::deserialize::<Message>(&buf) {
-                        Ok(msg) => {
-                            match msg {
-                                Message::Ping { id, timestamp } => {
-                                    let id_copy = id.clone();
-                                    log_network!("🏓 Got Ping {} from {}", id_copy, addr);
-
-                                    // Ping 받으면 Pong 응답
-                                    let pong = Message::Pong {
-                                        id: id.clone(),
-                                        timestamp,
-                                    };
-                                    let serialized = bincode::serialize(&pong).unwrap();
-
-                                    if let Ok(mut send) = conn.open_uni().await {
-                                        let _ = send.write_all(&serialized).await;
-                                        let _ = send.finish().await;
-                                        log_network!("🏓 Sent Pong {} to {}", id, addr);
-                                    }
-
-                                    // last_ping 업데이트
-                                    if let Some(peer) = peers.write().await.get_mut(&addr) {
-                                        peer.last_ping = Instant::now();
-                                    }
-                                }
-                                Message::Pong { id, timestamp } => {
-                                    // Pong 받으면 latency 계산하고 last_pong 업데이트
-                                    let now = std::time::SystemTime::now()
-                                        .duration_since(std::time::UNIX_EPOCH)
-                                        .unwrap()
-                                        .as_millis()
-                                        as u64;
-
-                                    let latency = now.saturating_sub(timestamp);
-                                    let id_copy = id.clone();
-
-                                    if let Some(peer) = peers.write().await.get_mut(&addr) {
-                                        peer.last_pong = Instant::now();
-                                        peer.latency_ms = latency;
-                                        log_network!(
-                                            "🏓 Got Pong {} from {} ({}ms)",
-                                            id_copy,
-                                            addr,
-                                            latency
-                                        );
-                                    }
+            let mut offset = 0;
+            loop {
+                if skip > 0 {
+                    let skipped = skip.min(buf.len() - offset);
+                    offset += skipped;
+                    skip -= skipped;
+                    if skip > 0 {
+                        break;
+                    }
+                }
+                match wire::decode(&buf[offset..]) {
+                    Ok((Frame::Message(Message::OpenStream { protocol }), used)) if first_frame => {
+                        // 상대가 먼저 핸드셰이크를 마치고 연 스트림이면 우리 쪽이 끝날 때까지 대기
+                        if !is_verified && verified.wait_for(|verified| *verified).await.is_err() {
+                            log_network!("⚠️ {} stream from {} before handshake", protocol, addr);
+                            return;
+                        }
+                        shared.record(Direction::Inbound, addr, DeliveryPath::Stream, &buf[..used]);
+                        // 프로토콜 스트림 - 나머지 바이트는 프레임이 아니므로 핸들러에게 넘김
+                        let rest = buf.split_off(used);
+                        Self::dispatch_stream(
+                            protocol,
+                            addr,
+                            PrefixedRecvStream::new(rest, recv),
+                            &shared,
+                        )
+                        .await;
+                        return;
+                    }
+                    Ok((frame, used)) => {
+                        first_frame = false;
+                        shared.record(
+                            Direction::Inbound,
+                            addr,
+                            DeliveryPath::Stream,
+                            &buf[offset..offset + used],
+                        );
+                        offset += used;
+                        match frame {
+                            Frame::Message(Message::Hello { network_id, nonce }) => {
+                                Self::receive_hello(network_id, nonce, addr, conn_id, &shared)
+                                    .await;
+                            }
+                            Frame::Message(Message::HelloAuth {
+                                public_key,
+                                signature,
+                            }) => {
+                                Self::receive_hello_auth(
+                                    public_key, signature, addr, conn_id, &shared,
+                                )
+                                .await;
+                            }
+                            frame => {
+                                if !is_verified && *verified.borrow() {
+                                    is_verified = true;
+                                    early.replay(addr, &shared).await;
                                 }
-                                Message::Data(data) => {
-                                    // 일반 데이터 메시지 처리
-                                    let data_len = data.len();
-                                    log_network!("📦 Data from {}: {} bytes", addr, data_len);
+                                if is_verified {
+                                    Self::handle_frame(frame, addr, DeliveryPath::Stream, &shared)
+                                        .await;
+                                } else {
+                                    early.push(frame, used, addr);
                                 }
                             }
                         }
-                        Err(e) => {
-                            log_network!("⚠️ Failed to deserialize message from {}: {:?}", addr, e);
-                            let buf_sample = buf[..buf.len().min(100)].to_vec();
-                            log_network!("⚠️ Buffer content (first 100 bytes): {:?}", buf_sample);
-                        }
+                    }
+                    Err(WireError::Incomplete) => break,
+                    Err(
+                        e @ (WireError::TooLarge(_)
+                        | WireError::InvalidPayload(_)
+                        | WireError::UnknownCritical(_)),
+                    ) => {
+                        // 헤더의 length로 프레임 경계를 알 수 있으므로 이 프레임만 버림
+                        let length = match e {
+                            WireError::TooLarge(length) => length,
+                            _ => wire::decode_header(&buf[offset..]).map_or(0, |h| h.length),
+                        };
+                        first_frame = false;
+                        log_network!("⚠️ Skipping bad frame from {}: {}", addr, e);
+                        skip = wire::HEADER_LEN + length as usize;
+                    }
+                    Err(e) => {
+                        // 프레임 경계를 잃었으므로 스트림을 더 읽을 수 없음
+                        log_network!("⚠️ Failed to decode message from {}: {}", addr, e);
+                        return;
                     }
                 }
+            }
+            buf.drain(..offset);
+        }
+
+        if !buf.is_empty() {
+            let buf_len = buf.len();
+            log_network!(
+                "⚠️ Stream from {} ended with {} trailing bytes",
+                addr,
+                buf_len
+            );
+        }
+    }
+
+    /// 프로토콜 스트림을 등록된 스트림 핸들러에게 전달
+    async fn dispatch_stream(
+        protocol: String,
+        addr: SocketAddr,
+        stream: PrefixedRecvStream,
+        shared: &Shared,
+    ) {
+        match shared.protocols.get(&protocol) {
+            Some(ProtocolHandler::Stream(handler)) => {
+                let protocol_copy = protocol.clone();
+                log_network!("🧩 {} stream from {}", protocol_copy, addr);
+                handler.handle(addr, Box::new(stream)).await;
+            }
+            _ => Self::reject_protocol(protocol, addr, shared).await,
+        }
+    }
+
+    /// 핸들러가 없는 프로토콜에 대해 ProtocolUnsupported 응답
+    async fn reject_protocol(protocol: String, addr: SocketAddr, shared: &Shared) {
+        let protocol_copy = protocol.clone();
+        log_network!("🚫 Unsupported protocol {} from {}", protocol_copy, addr);
+
+        let queues = shared
+            .peers
+            .read()
+            .await
+            .get(&addr)
+            .map(|peer| peer.queues.clone());
+        if let Some(queues) = queues {
+            let reply = Message::ProtocolUnsupported { protocol };
+            queues.enqueue(addr, TrafficClass::Control, wire::encode(&reply));
+        }
+    }
+
+    /// 버퍼에 담긴 봉투 프레임들을 순서대로 처리
+    async fn handle_frames(buf: &[u8], addr: SocketAddr, path: DeliveryPath, shared: &Shared) {
+        let mut offset = 0;
+        while offset < buf.len() {
+            match wire::decode(&buf[offset..]) {
+                Ok((frame, used)) => {
+                    offset += used;
+                    Self::handle_frame(frame, addr, path, shared).await;
+                }
                 Err(e) => {
-                    // 연결 상태 확인
-                    let error_msg = e.to_string();
-                    log_network!("🔌 Connection closed: {} ({})", addr, error_msg);
-                    peers.write().await.remove(&addr);
+                    log_network!("⚠️ Failed to decode message from {}: {}", addr, e);
+                    let buf_sample = buf[offset..buf.len().min(offset + 100)].to_vec();
+                    log_network!("⚠️ Buffer content (first 100 bytes): {:?}", buf_sample);
                     break;
                 }
             }
         }
     }
 
-    fn make_server_config() -> ServerConfig {
-        // 자체 서명 인증서 생성
-        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
-        let cert_der = cert.serialize_der().unwrap();
-        let key_der = cert.serialize_private_key_der();
+    async fn handle_frame(frame: Frame, addr: SocketAddr, path: DeliveryPath, shared: &Shared) {
+        match frame {
+            Frame::Message(msg) => Self::handle_message(msg, addr, path, shared).await,
+            Frame::Skipped { kind, version } => {
+                // 새 버전 피어가 보낸 모르는 메시지는 건너뜀
+                log_network!(
+                    "⏭️ Skipped unknown message kind 0x{:02x} (v{}) from {}",
+                    kind,
+                    version,
+                    addr
+                );
+            }
+        }
+    }
 
-        let priv_key = rustls::PrivateKey(key_der);
-        let cert_chain = vec![rustls::Certificate(cert_der)];
+    async fn handle_message(msg: Message, addr: SocketAddr, path: DeliveryPath, shared: &Shared) {
+        match msg {
+            Message::Ping { id, timestamp } => {
+                let id_copy = id.clone();
+                log_network!("🏓 Got Ping {} from {} via {:?}", id_copy, addr, path);
 
-        // Keep-alive 설정으로 연결 유지
-        let mut transport_config = quinn::TransportConfig::default();
-        transport_config.keep_alive_interval(Some(Duration::from_secs(5))); // 5초마다 keep-alive
-        transport_config.max_idle_timeout(Some(Duration::from_secs(30).try_into().unwrap())); // 30초 타임아웃
+                // Ping이 온 경로로 Pong 응답 (데이터그램 Ping이면 데이터그램 우선)
+                let pong = Message::Pong {
+                    id: id.clone(),
+                    timestamp,
+                };
 
-        let mut config = ServerConfig::with_single_cert(cert_chain, priv_key).unwrap();
-        config.transport_config(Arc::new(transport_config));
-        config
-    }
+                // last_ping 업데이트
+                let peer = {
+                    let mut peers = shared.peers.write().await;
+                    peers.get_mut(&addr).map(|peer| {
+                        peer.last_ping = Instant::now();
+                        peer.clone()
+                    })
+                };
 
-    fn make_client_config() -> ClientConfig {
-        // 모든 인증서 허용 (개발용)
-        let crypto = rustls::ClientConfig::builder()
-            .with_safe_defaults()
-            .with_custom_certificate_verifier(SkipServerVerification::new())
-            .with_no_client_auth();
+                if let Some(peer) = peer {
+                    let sent = match path {
+                        DeliveryPath::Datagram => {
+                            Self::send_unreliable(&peer, addr, &pong, shared).await
+                        }
+                        DeliveryPath::Stream => {
+                            let serialized = wire::encode(&pong);
+                            peer.queues
+                                .enqueue(addr, TrafficClass::Control, serialized)
+                                .then_some(DeliveryPath::Stream)
+                        }
+                    };
+                    if let Some(sent) = sent {
+                        log_network!("🏓 Sent Pong {} to {} via {:?}", id, addr, sent);
+                    }
+                }
+            }
+            Message::Pong { id, timestamp } => {
+                // Pong 받으면 latency 계산하고 last_pong 업데이트
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64;
 
-        // Keep-alive 설정으로 연결 유지
-        let mut transport_config = quinn::TransportConfig::default();
-        transport_config.keep_alive_interval(Some(Duration::from_secs(5))); // 5초마다 keep-alive
-        transport_config.max_idle_timeout(Some(Duration::from_secs(30).try_into().unwrap())); // 30초 타임아웃
+                let latency = now.saturating_sub(timestamp);
+                let id_copy = id.clone();
 
-        let mut config = ClientConfig::new(Arc::new(crypto));
-        config.transport_config(Arc::new(transport_config));
-        config
+                if let Some(peer) = shared.peers.write().await.get_mut(&addr) {
+                    peer.last_pong = Instant::now();
+                    peer.latency_ms = latency;
+                    log_network!("🏓 Got Pong {} from {} ({}ms)", id_copy, addr, latency);
+                    let _ = shared.contacts.send(addr);
+                }
+                shared.stats.write().await.pongs_received += 1;
+            }
+            Message::Data(data) => {
+                // 일반 데이터 메시지 처리
+                let data_len = data.len();
+                log_network!("📦 Data from {}: {} bytes", addr, data_len);
+                shared.stats.write().await.messages_received += 1;
+                // 구독자가 없으면 send가 실패하지만 무시
+                let _ = shared.data.send((addr, data));
+            }
+            Message::Datagram(data) => {
+                // 구독자가 없으면 send가 실패하지만 무시
+                let _ = shared.datagrams.send((addr, data));
+            }
+            Message::Protocol { protocol, data } => match shared.protocols.get(&protocol) {
+                Some(ProtocolHandler::Message(handler)) => {
+                    shared.stats.write().await.messages_received += 1;
+                    handler.handle(addr, data).await;
+                }
+                _ => Self::reject_protocol(protocol, addr, shared).await,
+            },
+            Message::ProtocolUnsupported { protocol } => {
+                let protocol_copy = protocol.clone();
+                log_network!("🚫 Peer {} does not support {}", addr, protocol_copy);
+                if let Some(peer) = shared.peers.write().await.get_mut(&addr) {
+                    peer.unsupported_protocols.insert(protocol);
+                }
+            }
+            Message::OpenStream { protocol } => {
+                // 스트림의 첫 프레임이 아닌 OpenStream은 잘못된 메시지
+                log_network!("⚠️ Unexpected stream header for {} from {}", protocol, addr);
+            }
+            Message::Hello { .. } | Message::HelloAuth { .. } => {
+                // 핸드셰이크는 연결의 스트림에서만 처리 (데이터그램이나 재현으로 온 것은 무시)
+                log_network!("⚠️ Ignoring handshake frame from {} via {:?}", addr, path);
+            }
+        }
     }
 }
 
-// 인증서 검증 스킵 (개발용)
-struct SkipServerVerification;
+/// 핸드셰이크가 끝나기 전에 한 스트림으로 도착한 프레임
+#[derive(Default)]
+struct EarlyFrames {
+    frames: Vec<Frame>,
+    bytes: usize,
+}
 
-impl SkipServerVerification {
-    fn new() -> Arc<Self> {
-        Arc::new(Self)
+impl EarlyFrames {
+    /// 프레임 보관 (MAX_EARLY_BYTES를 넘으면 버림)
+    fn push(&mut self, frame: Frame, len: usize, addr: SocketAddr) {
+        if self.bytes + len > MAX_EARLY_BYTES {
+            log_network!("⚠️ Dropping frame from {} received before handshake", addr);
+            return;
+        }
+        self.bytes += len;
+        self.frames.push(frame);
     }
-}
 
-impl rustls::client::ServerCertVerifier for SkipServerVerification {
-    fn verify_server_cert(
-        &self,
-        _end_entity: &rustls::Certificate,
-        _intermediates: &[rustls::Certificate],
-        _server_name: &rustls::ServerName,
-        _scts: &mut dyn Iterator<Item = &[u8]>,
-        _ocsp_response: &[u8],
-        _now: std::time::SystemTime,
-    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
-        Ok(rustls::client::ServerCertVerified::assertion())
+    /// 핸드셰이크가 끝난 뒤 보관한 프레임을 도착 순서대로 처리
+    async fn replay(&mut self, addr: SocketAddr, shared: &Shared) {
+        self.bytes = 0;
+        for frame in std::mem::take(&mut self.frames) {
+            Network::handle_frame(frame, addr, DeliveryPath::Stream, shared).await;
+        }
     }
 }
+
+/// 핸드셰이크 서명 대상 - network id, 채널 바인딩, 상대의 챌린지와 서명자의 nonce
+///
+/// 도메인 문자열로 DHT 레코드 서명과 섞이지 않도록 하고, network id를 넣어
+/// 다른 network에서 받은 서명을 재사용하지 못하게 한다. 채널 바인딩은 연결마다
+/// 달라서, 중간자가 다른 연결에서 받은 서명을 그대로 중계하면 검증에 실패한다.
+pub(crate) fn handshake_transcript(
+    network_id: &str,
+    binding: &[u8; 32],
+    challenge: &[u8; 32],
+    signer_nonce: &[u8; 32],
+) -> Vec<u8> {
+    let mut transcript = b"guild-handshake-v2".to_vec();
+    transcript.extend_from_slice(&(network_id.len() as u32).to_be_bytes());
+    transcript.extend_from_slice(network_id.as_bytes());
+    transcript.extend_from_slice(binding);
+    transcript.extend_from_slice(challenge);
+    transcript.extend_from_slice(signer_nonce);
+    transcript
+}
@@ -0,0 +1,296 @@
+// 프로토콜 핸들러 레지스트리 - 하나의 연결 위에서 독립적인 프로토콜들을 다중화
+//
+// 서브시스템(discovery, chat, sync, bridge 등)은 프로토콜 id와 비동기 핸들러를 등록하고,
+// Network는 수신한 프로토콜 메시지/스트림을 id로 찾아 전달한다. 핸들러가 없는 id를 받으면
+// `ProtocolUnsupported`로 응답해서 송신 측이 해당 피어에게 더 보내지 않도록 한다.
+use crate::transport::{RecvStream, TransportError};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+
+/// 프로토콜 id 최대 길이
+pub const MAX_PROTOCOL_ID_LEN: usize = 128;
+
+/// 메시지 단위 핸들러 (프레임 하나 = 메시지 하나)
+///
+/// 같은 피어/트래픽 클래스의 메시지는 순서대로 호출되므로, 오래 걸리는 작업은
+/// 핸들러 안에서 별도 태스크로 분리해야 한다.
+#[async_trait]
+pub trait MessageHandler: Send + Sync {
+    async fn handle(&self, from: SocketAddr, data: Vec<u8>);
+}
+
+/// 스트림 단위 핸들러 (피어가 연 스트림 전체를 넘겨받음)
+#[async_trait]
+pub trait StreamHandler: Send + Sync {
+    async fn handle(&self, from: SocketAddr, stream: Box<dyn RecvStream>);
+}
+
+#[async_trait]
+impl<F, Fut> MessageHandler for F
+where
+    F: Fn(SocketAddr, Vec<u8>) -> Fut + Send + Sync,
+    Fut: Future<Output = ()> + Send,
+{
+    async fn handle(&self, from: SocketAddr, data: Vec<u8>) {
+        (self)(from, data).await
+    }
+}
+
+#[async_trait]
+impl<F, Fut> StreamHandler for F
+where
+    F: Fn(SocketAddr, Box<dyn RecvStream>) -> Fut + Send + Sync,
+    Fut: Future<Output = ()> + Send,
+{
+    async fn handle(&self, from: SocketAddr, stream: Box<dyn RecvStream>) {
+        (self)(from, stream).await
+    }
+}
+
+/// 등록된 핸들러
+#[derive(Clone)]
+pub enum ProtocolHandler {
+    Message(Arc<dyn MessageHandler>),
+    Stream(Arc<dyn StreamHandler>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    InvalidId(String),
+    AlreadyRegistered(String),
+    /// 피어가 이 프로토콜을 지원하지 않는다고 응답함
+    Unsupported {
+        peer: SocketAddr,
+        protocol: String,
+    },
+    NotConnected(SocketAddr),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::InvalidId(id) => write!(f, "invalid protocol id {:?}", id),
+            ProtocolError::AlreadyRegistered(id) => write!(f, "protocol {} already registered", id),
+            ProtocolError::Unsupported { peer, protocol } => {
+                write!(f, "peer {} does not support protocol {}", peer, protocol)
+            }
+            ProtocolError::NotConnected(peer) => write!(f, "peer {} is not connected", peer),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+/// 프로토콜 id -> 핸들러
+#[derive(Default)]
+pub struct ProtocolRegistry {
+    handlers: RwLock<HashMap<String, ProtocolHandler>>,
+}
+
+impl ProtocolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 핸들러 등록 (같은 id가 이미 있으면 에러)
+    pub fn register(&self, protocol: &str, handler: ProtocolHandler) -> Result<(), ProtocolError> {
+        validate_id(protocol)?;
+
+        let mut handlers = self.handlers.write().unwrap();
+        if handlers.contains_key(protocol) {
+            return Err(ProtocolError::AlreadyRegistered(protocol.to_string()));
+        }
+        handlers.insert(protocol.to_string(), handler);
+        Ok(())
+    }
+
+    /// 핸들러 제거 (등록되어 있었으면 true)
+    pub fn unregister(&self, protocol: &str) -> bool {
+        self.handlers.write().unwrap().remove(protocol).is_some()
+    }
+
+    pub fn get(&self, protocol: &str) -> Option<ProtocolHandler> {
+        self.handlers.read().unwrap().get(protocol).cloned()
+    }
+
+    /// 등록된 프로토콜 id 목록 (정렬됨)
+    pub fn protocols(&self) -> Vec<String> {
+        let mut protocols: Vec<String> = self.handlers.read().unwrap().keys().cloned().collect();
+        protocols.sort();
+        protocols
+    }
+}
+
+/// 프로토콜 id 검사 (비어 있지 않고, 길이 제한 이내, 출력 가능한 ASCII)
+pub fn validate_id(protocol: &str) -> Result<(), ProtocolError> {
+    let valid = !protocol.is_empty()
+        && protocol.len() <= MAX_PROTOCOL_ID_LEN
+        && protocol.bytes().all(|b| b.is_ascii_graphic());
+    if valid {
+        Ok(())
+    } else {
+        Err(ProtocolError::InvalidId(protocol.to_string()))
+    }
+}
+
+/// 이미 읽어 둔 바이트를 먼저 돌려주는 수신 스트림
+///
+/// 스트림 헤더 프레임을 읽을 때 함께 읽힌 데이터를 핸들러가 잃지 않도록 함
+pub(crate) struct PrefixedRecvStream {
+    prefix: Option<Vec<u8>>,
+    inner: Box<dyn RecvStream>,
+}
+
+impl PrefixedRecvStream {
+    pub(crate) fn new(prefix: Vec<u8>, inner: Box<dyn RecvStream>) -> Self {
+        Self {
+            prefix: (!prefix.is_empty()).then_some(prefix),
+            inner,
+        }
+    }
+}
+
+#[async_trait]
+impl RecvStream for PrefixedRecvStream {
+    async fn read_chunk(&mut self, max: usize) -> Result<Option<Vec<u8>>, TransportError> {
+        if let Some(mut prefix) = self.prefix.take() {
+            if prefix.len() > max {
+                self.prefix = Some(prefix.split_off(max));
+            }
+            return Ok(Some(prefix));
+        }
+        self.inner.read_chunk(max).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_transport::MemoryNetwork;
+    use crate::network::{Network, NetworkConfig, TrafficClass};
+    use std::collections::VecDeque;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// 정해진 청크를 차례로 돌려주는 수신 스트림
+    struct Chunks(VecDeque<Vec<u8>>);
+
+    #[async_trait]
+    impl RecvStream for Chunks {
+        async fn read_chunk(&mut self, _max: usize) -> Result<Option<Vec<u8>>, TransportError> {
+            Ok(self.0.pop_front())
+        }
+    }
+
+    async fn node(net: &MemoryNetwork, addr: &str) -> Network {
+        let transport = net.bind(addr.parse().unwrap());
+        Network::with_transport(Arc::new(transport), NetworkConfig::default()).await
+    }
+
+    async fn read_all(stream: &mut dyn RecvStream) -> Vec<u8> {
+        let mut data = Vec::new();
+        while let Some(chunk) = stream.read_chunk(4).await.unwrap() {
+            data.extend_from_slice(&chunk);
+        }
+        data
+    }
+
+    #[test]
+    fn duplicate_and_invalid_ids_are_rejected() {
+        let registry = ProtocolRegistry::new();
+        let handler = || ProtocolHandler::Message(Arc::new(|_: SocketAddr, _: Vec<u8>| async {}));
+        registry.register("guild/chat/1", handler()).unwrap();
+        assert_eq!(
+            registry.register("guild/chat/1", handler()).unwrap_err(),
+            ProtocolError::AlreadyRegistered("guild/chat/1".to_string())
+        );
+        assert!(matches!(
+            registry.register("has space", handler()),
+            Err(ProtocolError::InvalidId(_))
+        ));
+        assert!(registry.register("", handler()).is_err());
+        assert!(registry
+            .register(&"x".repeat(MAX_PROTOCOL_ID_LEN + 1), handler())
+            .is_err());
+
+        // 제거하면 다시 등록 가능
+        assert!(registry.unregister("guild/chat/1"));
+        registry.register("guild/chat/1", handler()).unwrap();
+        assert_eq!(registry.protocols(), vec!["guild/chat/1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn prefixed_stream_replays_its_prefix_first() {
+        let inner = Chunks(VecDeque::from(vec![b"world".to_vec()]));
+        let mut stream = PrefixedRecvStream::new(b"hello ".to_vec(), Box::new(inner));
+        // max보다 긴 prefix는 나눠서 돌려줌
+        assert_eq!(stream.read_chunk(4).await.unwrap(), Some(b"hell".to_vec()));
+        assert_eq!(read_all(&mut stream).await, b"o world".to_vec());
+
+        // 빈 prefix는 건너뜀
+        let inner = Chunks(VecDeque::from(vec![b"data".to_vec()]));
+        let mut stream = PrefixedRecvStream::new(Vec::new(), Box::new(inner));
+        assert_eq!(stream.read_chunk(16).await.unwrap(), Some(b"data".to_vec()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unknown_protocol_gets_an_unsupported_reply() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let _b = node(&net, "10.0.0.2:42000").await;
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        a.connect(b_addr).await.unwrap();
+        assert_eq!(a.peer_supports(b_addr, "guild/none/1").await, Some(true));
+
+        a.send_protocol(b_addr, "guild/none/1", b"hi", TrafficClass::Control)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(a.peer_supports(b_addr, "guild/none/1").await, Some(false));
+
+        // 이후에는 보내지 않고 바로 실패
+        let result = a
+            .send_protocol(b_addr, "guild/none/1", b"hi", TrafficClass::Control)
+            .await;
+        assert!(result.unwrap_err().to_string().contains("does not support"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stream_handler_receives_the_whole_stream() {
+        let net = MemoryNetwork::new(1);
+        let a = node(&net, "10.0.0.1:42000").await;
+        let b = node(&net, "10.0.0.2:42000").await;
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        b.register_stream_handler(
+            "guild/sync/1",
+            move |from: SocketAddr, mut stream: Box<dyn RecvStream>| {
+                let tx = tx.clone();
+                async move {
+                    let data = read_all(stream.as_mut()).await;
+                    let _ = tx.send((from, data));
+                }
+            },
+        )
+        .unwrap();
+        a.connect(b_addr).await.unwrap();
+
+        let mut stream = a
+            .open_protocol_stream(b_addr, "guild/sync/1", TrafficClass::Bulk)
+            .await
+            .unwrap();
+        stream.write_all(b"first ").await.unwrap();
+        stream.write_all(b"second").await.unwrap();
+        stream.finish().await.unwrap();
+
+        let (from, data) = rx.recv().await.unwrap();
+        assert_eq!(from, a_addr);
+        assert_eq!(data, b"first second".to_vec());
+    }
+}
@@ -0,0 +1,432 @@
+// 트래픽 녹화 - 송수신한 wire 프레임을 파일에 기록하고 다시 읽어 재현
+//
+// 파일 포맷 (모든 정수는 big-endian):
+//
+//   헤더:  magic b"GHREC" (5) | version (1)
+//   레코드: timestamp_us u64 | direction u8 | path u8 | family u8 (4/6)
+//          | ip (4 또는 16) | port u16 | length u32 | frames (length)
+//
+// frames에는 wire 봉투 프레임이 하나 이상 그대로 담긴다 (배치로 보낸 경우 여러 개).
+// 프로토콜 스트림은 OpenStream 헤더 프레임만 기록되고 이후 본문은 기록되지 않는다.
+use crate::log_network;
+use crate::network::DeliveryPath;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+pub const RECORDING_MAGIC: [u8; 5] = *b"GHREC";
+pub const RECORDING_VERSION: u8 = 1;
+
+/// 기록 대기 큐 크기 (가득 차면 레코드를 버리고 dropped 증가)
+const RECORD_QUEUE_SIZE: usize = 4096;
+
+/// 트래픽 방향
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound = 0,
+    Outbound = 1,
+}
+
+/// 녹화된 레코드 하나
+#[derive(Debug, Clone)]
+pub struct Record {
+    /// unix epoch 기준 마이크로초
+    pub timestamp_us: u64,
+    pub direction: Direction,
+    pub path: DeliveryPath,
+    pub peer: SocketAddr,
+    pub frames: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(std::io::Error),
+    BadHeader,
+    UnsupportedVersion(u8),
+    /// 레코드가 잘려 있음 (녹화 중 종료된 경우 마지막 레코드)
+    Truncated,
+    Invalid(&'static str),
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingError::Io(e) => write!(f, "io error: {}", e),
+            RecordingError::BadHeader => write!(f, "not a guild-home recording"),
+            RecordingError::UnsupportedVersion(v) => {
+                write!(f, "unsupported recording version {}", v)
+            }
+            RecordingError::Truncated => write!(f, "truncated record"),
+            RecordingError::Invalid(field) => write!(f, "invalid {}", field),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(e: std::io::Error) -> Self {
+        RecordingError::Io(e)
+    }
+}
+
+impl Record {
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.timestamp_us.to_be_bytes());
+        buf.push(self.direction as u8);
+        buf.push(match self.path {
+            DeliveryPath::Stream => 0,
+            DeliveryPath::Datagram => 1,
+        });
+        match self.peer.ip() {
+            IpAddr::V4(ip) => {
+                buf.push(4);
+                buf.extend_from_slice(&ip.octets());
+            }
+            IpAddr::V6(ip) => {
+                buf.push(6);
+                buf.extend_from_slice(&ip.octets());
+            }
+        }
+        buf.extend_from_slice(&self.peer.port().to_be_bytes());
+        buf.extend_from_slice(&(self.frames.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&self.frames);
+    }
+
+    /// 버퍼 앞쪽의 레코드 하나를 디코딩하고 사용한 바이트 수를 반환
+    pub fn decode(buf: &[u8]) -> Result<(Record, usize), RecordingError> {
+        let mut reader = ByteReader { buf, offset: 0 };
+
+        let timestamp_us = u64::from_be_bytes(reader.take()?);
+        let direction = match reader.take::<1>()?[0] {
+            0 => Direction::Inbound,
+            1 => Direction::Outbound,
+            _ => return Err(RecordingError::Invalid("direction")),
+        };
+        let path = match reader.take::<1>()?[0] {
+            0 => DeliveryPath::Stream,
+            1 => DeliveryPath::Datagram,
+            _ => return Err(RecordingError::Invalid("path")),
+        };
+        let ip = match reader.take::<1>()?[0] {
+            4 => IpAddr::V4(Ipv4Addr::from(reader.take::<4>()?)),
+            6 => IpAddr::V6(Ipv6Addr::from(reader.take::<16>()?)),
+            _ => return Err(RecordingError::Invalid("address family")),
+        };
+        let port = u16::from_be_bytes(reader.take()?);
+        let length = u32::from_be_bytes(reader.take()?) as usize;
+        let frames = reader.take_slice(length)?.to_vec();
+
+        let record = Record {
+            timestamp_us,
+            direction,
+            path,
+            peer: SocketAddr::new(ip, port),
+            frames,
+        };
+        Ok((record, reader.offset))
+    }
+}
+
+struct ByteReader<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl ByteReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], RecordingError> {
+        let slice = self.take_slice(N)?;
+        Ok(slice.try_into().unwrap())
+    }
+
+    fn take_slice(&mut self, len: usize) -> Result<&[u8], RecordingError> {
+        let end = self.offset + len;
+        if end > self.buf.len() {
+            return Err(RecordingError::Truncated);
+        }
+        let slice = &self.buf[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+}
+
+/// 트래픽 녹화기 - 기록은 큐에 넣기만 하고 파일 쓰기는 별도 태스크가 처리
+pub struct TrafficRecorder {
+    path: PathBuf,
+    tx: mpsc::Sender<Record>,
+    dropped: AtomicU64,
+}
+
+impl fmt::Debug for TrafficRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrafficRecorder")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl TrafficRecorder {
+    /// 녹화 파일 생성 (상위 디렉토리가 없으면 만듦)
+    pub async fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let file = tokio::fs::File::create(&path).await?;
+        let mut writer = tokio::io::BufWriter::new(file);
+        writer.write_all(&RECORDING_MAGIC).await?;
+        writer.write_all(&[RECORDING_VERSION]).await?;
+        writer.flush().await?;
+
+        let (tx, mut rx) = mpsc::channel::<Record>(RECORD_QUEUE_SIZE);
+        tokio::spawn(async move {
+            let mut buf = Vec::new();
+            while let Some(record) = rx.recv().await {
+                buf.clear();
+                record.encode(&mut buf);
+                // 이미 쌓여 있는 레코드는 한 번에 씀
+                while let Ok(record) = rx.try_recv() {
+                    record.encode(&mut buf);
+                }
+
+                let result = async {
+                    writer.write_all(&buf).await?;
+                    writer.flush().await
+                }
+                .await;
+                if let Err(e) = result {
+                    let error = e.to_string();
+                    log_network!("⚠️ Traffic recording stopped: {}", error);
+                    break;
+                }
+            }
+        });
+
+        let path_str = path.display().to_string();
+        log_network!("🎙️ Recording traffic to {}", path_str);
+
+        Ok(Self {
+            path,
+            tx,
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// 프레임 기록 (블로킹하지 않음 - 큐가 가득 차면 버림)
+    pub fn record(
+        &self,
+        direction: Direction,
+        peer: SocketAddr,
+        path: DeliveryPath,
+        frames: &[u8],
+    ) {
+        let record = Record {
+            timestamp_us: now_micros(),
+            direction,
+            path,
+            peer,
+            frames: frames.to_vec(),
+        };
+        if self.tx.try_send(record).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 큐가 가득 차거나 쓰기가 중단되어 버린 레코드 수
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// 녹화 파일 읽기
+pub struct RecordingReader {
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl RecordingReader {
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let data = tokio::fs::read(path).await?;
+        Self::from_bytes(data)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, RecordingError> {
+        let header_len = RECORDING_MAGIC.len() + 1;
+        if data.len() < header_len || data[..RECORDING_MAGIC.len()] != RECORDING_MAGIC {
+            return Err(RecordingError::BadHeader);
+        }
+        let version = data[RECORDING_MAGIC.len()];
+        if version != RECORDING_VERSION {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+
+        Ok(Self {
+            data,
+            offset: header_len,
+        })
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = Result<Record, RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.data.len() {
+            return None;
+        }
+        match Record::decode(&self.data[self.offset..]) {
+            Ok((record, used)) => {
+                self.offset += used;
+                Some(Ok(record))
+            }
+            Err(e) => {
+                // 손상된 지점 이후는 읽을 수 없음
+                self.offset = self.data.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn now_micros() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_transport::MemoryNetwork;
+    use crate::network::{Network, NetworkConfig};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("guild_home_{}_{}.rec", name, std::process::id()))
+    }
+
+    /// 기록 태스크가 파일에 쓸 때까지 기다렸다가 레코드를 모두 읽음
+    async fn read_records(path: &Path, expected: usize) -> Vec<Record> {
+        for _ in 0..200 {
+            let reader = RecordingReader::open(path).await.unwrap();
+            let records: Vec<Record> = reader.map(Result::unwrap).collect();
+            if records.len() >= expected {
+                return records;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("recording did not reach {} records", expected);
+    }
+
+    #[tokio::test]
+    async fn recorded_frames_read_back_in_order() {
+        let path = temp_path("recorder_round_trip");
+        let recorder = TrafficRecorder::create(&path).await.unwrap();
+        let v4: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let v6: SocketAddr = "[2001:db8::1]:42001".parse().unwrap();
+        recorder.record(Direction::Inbound, v4, DeliveryPath::Stream, b"first");
+        recorder.record(Direction::Outbound, v6, DeliveryPath::Datagram, b"second");
+        recorder.record(Direction::Inbound, v4, DeliveryPath::Stream, b"");
+
+        let records = read_records(&path, 3).await;
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].direction, Direction::Inbound);
+        assert_eq!(records[0].path, DeliveryPath::Stream);
+        assert_eq!(records[0].peer, v4);
+        assert_eq!(records[0].frames, b"first".to_vec());
+        assert_eq!(records[1].direction, Direction::Outbound);
+        assert_eq!(records[1].path, DeliveryPath::Datagram);
+        assert_eq!(records[1].peer, v6);
+        assert_eq!(records[1].frames, b"second".to_vec());
+        assert!(records[2].frames.is_empty());
+        assert!(records[0].timestamp_us <= records[1].timestamp_us);
+        assert_eq!(recorder.dropped(), 0);
+
+        // 잘린 마지막 레코드는 Truncated로 끝남
+        let mut data = tokio::fs::read(&path).await.unwrap();
+        data.truncate(data.len() - 3);
+        let results: Vec<_> = RecordingReader::from_bytes(data).unwrap().collect();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[2], Err(RecordingError::Truncated)));
+
+        assert!(matches!(
+            RecordingReader::from_bytes(b"GHXXX\x01".to_vec()),
+            Err(RecordingError::BadHeader)
+        ));
+        assert!(matches!(
+            RecordingReader::from_bytes(b"GHREC\x09".to_vec()),
+            Err(RecordingError::UnsupportedVersion(9))
+        ));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn replayed_inbound_frames_are_delivered() {
+        let path = temp_path("recorder_replay");
+        let net = MemoryNetwork::new(1);
+        let a_addr: SocketAddr = "10.0.0.1:42000".parse().unwrap();
+        let b_addr: SocketAddr = "10.0.0.2:42000".parse().unwrap();
+        let a = Network::with_transport(Arc::new(net.bind(a_addr)), NetworkConfig::default()).await;
+        let b_config = NetworkConfig {
+            recording_path: Some(path.clone()),
+            ..NetworkConfig::default()
+        };
+        let b = Network::with_transport(Arc::new(net.bind(b_addr)), b_config).await;
+        a.connect(b_addr).await.unwrap();
+
+        let mut b_data = b.subscribe_data();
+        a.broadcast(b"block 1").await;
+        a.broadcast(b"block 2").await;
+        b_data.recv().await.unwrap();
+        b_data.recv().await.unwrap();
+
+        // b가 녹화한 수신 프레임을 연결 없는 새 노드에 재현
+        let mut inbound = Vec::new();
+        for _ in 0..200 {
+            inbound = RecordingReader::open(&path)
+                .await
+                .unwrap()
+                .map(Result::unwrap)
+                .filter(|record| record.direction == Direction::Inbound)
+                .collect();
+            if inbound
+                .iter()
+                .any(|r| r.frames.windows(7).any(|w| w == b"block 2"))
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let replay_addr: SocketAddr = "10.0.0.9:42000".parse().unwrap();
+        let replay =
+            Network::with_transport(Arc::new(net.bind(replay_addr)), NetworkConfig::default())
+                .await;
+        let mut replay_data = replay.subscribe_data();
+        for record in &inbound {
+            replay
+                .replay_inbound(record.peer, record.path, &record.frames)
+                .await;
+        }
+        assert_eq!(
+            replay_data.try_recv().unwrap(),
+            (a_addr, b"block 1".to_vec())
+        );
+        assert_eq!(
+            replay_data.try_recv().unwrap(),
+            (a_addr, b"block 2".to_vec())
+        );
+        assert!(replay_data.try_recv().is_err());
+        std::fs::remove_file(&path).ok();
+    }
+}
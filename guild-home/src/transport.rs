@@ -0,0 +1,310 @@
+// Transport 추상화 - Network가 사용하는 연결/스트림/데이터그램 계층
+use crate::log_network;
+use crate::network::NetworkConfig;
+use async_trait::async_trait;
+use quinn::{ClientConfig, Connection, Endpoint, ServerConfig};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 연결을 맺고 수락하는 전송 계층 (QUIC, 인메모리 등)
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// 원격 주소로 연결
+    async fn connect(
+        &self,
+        addr: SocketAddr,
+    ) -> Result<Arc<dyn TransportConnection>, TransportError>;
+
+    /// 다음 수신 연결 대기 (전송 계층이 닫히면 None)
+    async fn accept(&self) -> Option<Arc<dyn TransportConnection>>;
+
+    /// 바인딩된 로컬 주소
+    fn local_addr(&self) -> SocketAddr;
+}
+
+/// 하나의 피어 연결
+#[async_trait]
+pub trait TransportConnection: Send + Sync + fmt::Debug {
+    fn remote_address(&self) -> SocketAddr;
+
+    /// 우선순위가 지정된 단방향 스트림 열기 (값이 클수록 먼저 전송)
+    async fn open_uni(&self, priority: i32) -> Result<Box<dyn SendStream>, TransportError>;
+
+    /// 피어가 연 단방향 스트림 수락 (연결이 끊기면 에러)
+    async fn accept_uni(&self) -> Result<Box<dyn RecvStream>, TransportError>;
+
+    /// 피어와 협상된 최대 데이터그램 크기 (미지원이면 None)
+    fn max_datagram_size(&self) -> Option<usize>;
+
+    /// 비신뢰 데이터그램 전송
+    fn send_datagram(&self, data: Vec<u8>) -> Result<(), TransportError>;
+
+    /// 다음 데이터그램 수신 (연결이 끊기면 에러)
+    async fn read_datagram(&self) -> Result<Vec<u8>, TransportError>;
+
+    /// 연결 종료
+    fn close(&self);
+
+    /// 이 연결에서만 양쪽이 같은 값을 얻는 채널 바인딩
+    ///
+    /// 핸드셰이크 서명에 포함되므로, 다른 연결로 중계된 서명은 검증에 실패한다.
+    fn channel_binding(&self) -> Result<[u8; 32], TransportError>;
+}
+
+/// 단방향 송신 스트림
+#[async_trait]
+pub trait SendStream: Send {
+    async fn write_all(&mut self, data: &[u8]) -> Result<(), TransportError>;
+
+    /// 스트림 종료 (피어가 모든 데이터를 받을 때까지 대기할 수 있음)
+    async fn finish(&mut self) -> Result<(), TransportError>;
+}
+
+/// 단방향 수신 스트림
+#[async_trait]
+pub trait RecvStream: Send {
+    /// 최대 `max` 바이트 읽기 (스트림이 끝나면 None)
+    async fn read_chunk(&mut self, max: usize) -> Result<Option<Vec<u8>>, TransportError>;
+
+    /// 스트림 끝까지 읽기 (`size_limit` 초과 시 에러)
+    async fn read_to_end(&mut self, size_limit: usize) -> Result<Vec<u8>, TransportError> {
+        let mut buf = Vec::new();
+        while let Some(chunk) = self.read_chunk(64 * 1024).await? {
+            buf.extend_from_slice(&chunk);
+            if buf.len() > size_limit {
+                return Err(format!("Stream exceeded {} bytes", size_limit).into());
+            }
+        }
+        Ok(buf)
+    }
+}
+
+/// QUIC (quinn) 기반 전송 계층
+pub struct QuicTransport {
+    endpoint: Endpoint,
+    incoming: Mutex<mpsc::Receiver<Arc<dyn TransportConnection>>>,
+}
+
+impl QuicTransport {
+    /// 포트에 바인딩 (사용 중이면 포트를 1씩 증가시키며 재시도)
+    pub fn bind(port: u16, network_config: &NetworkConfig) -> Self {
+        // QUIC 서버 설정 (자체 서명 인증서)
+        let server_config = Self::make_server_config(network_config);
+        let client_config = Self::make_client_config(network_config);
+
+        let mut endpoint = None;
+        let mut current_port = port;
+        let max_attempts = 100; // 최대 100번 시도
+
+        // Address already in use 에러 시 포트를 1씩 증가시키며 재시도
+        for attempt in 0..max_attempts {
+            let addr = format!("0.0.0.0:{}", current_port);
+            match Endpoint::server(server_config.clone(), addr.parse().unwrap()) {
+                Ok(ep) => {
+                    endpoint = Some(ep);
+                    if attempt > 0 {
+                        log_network!(
+                            "✅ Found available port {} after {} attempts",
+                            current_port,
+                            attempt + 1
+                        );
+                    }
+                    break;
+                }
+                Err(e) => {
+                    let error_msg = e.to_string();
+                    if error_msg.contains("Address already in use")
+                        || error_msg.contains("already being used")
+                    {
+                        log_network!(
+                            "⚠️ Port {} already in use, trying port {}",
+                            current_port,
+                            current_port + 1
+                        );
+                        current_port += 1;
+                    } else {
+                        panic!("Failed to create endpoint: {:?}", e);
+                    }
+                }
+            }
+        }
+
+        let mut endpoint = endpoint.expect("Failed to find available port after maximum attempts");
+        endpoint.set_default_client_config(client_config);
+
+        // 핸드셰이크가 서로를 막지 않도록 연결마다 별도 태스크에서 완료
+        let (tx, rx) = mpsc::channel(64);
+        let endpoint_clone = endpoint.clone();
+        tokio::spawn(async move {
+            while let Some(connecting) = endpoint_clone.accept().await {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    if let Ok(conn) = connecting.await {
+                        let conn: Arc<dyn TransportConnection> = Arc::new(QuicConnection(conn));
+                        let _ = tx.send(conn).await;
+                    }
+                });
+            }
+        });
+
+        Self {
+            endpoint,
+            incoming: Mutex::new(rx),
+        }
+    }
+
+    fn make_server_config(network_config: &NetworkConfig) -> ServerConfig {
+        // 자체 서명 인증서 생성
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_der = cert.serialize_der().unwrap();
+        let key_der = cert.serialize_private_key_der();
+
+        let priv_key = rustls::PrivateKey(key_der);
+        let cert_chain = vec![rustls::Certificate(cert_der)];
+
+        let mut config = ServerConfig::with_single_cert(cert_chain, priv_key).unwrap();
+        config.transport_config(Arc::new(Self::make_transport_config(network_config)));
+        config
+    }
+
+    fn make_client_config(network_config: &NetworkConfig) -> ClientConfig {
+        // 모든 인증서 허용 (개발용)
+        let crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(SkipServerVerification::new())
+            .with_no_client_auth();
+
+        let mut config = ClientConfig::new(Arc::new(crypto));
+        config.transport_config(Arc::new(Self::make_transport_config(network_config)));
+        config
+    }
+
+    fn make_transport_config(network_config: &NetworkConfig) -> quinn::TransportConfig {
+        // Keep-alive 설정으로 연결 유지
+        let mut transport_config = quinn::TransportConfig::default();
+        transport_config.keep_alive_interval(Some(Duration::from_secs(5))); // 5초마다 keep-alive
+        transport_config.max_idle_timeout(Some(Duration::from_secs(30).try_into().unwrap())); // 30초 타임아웃
+        transport_config.datagram_receive_buffer_size(network_config.datagram_receive_buffer);
+        transport_config
+    }
+}
+
+#[async_trait]
+impl Transport for QuicTransport {
+    async fn connect(
+        &self,
+        addr: SocketAddr,
+    ) -> Result<Arc<dyn TransportConnection>, TransportError> {
+        let conn = self.endpoint.connect(addr, "localhost")?.await?;
+        Ok(Arc::new(QuicConnection(conn)))
+    }
+
+    async fn accept(&self) -> Option<Arc<dyn TransportConnection>> {
+        self.incoming.lock().await.recv().await
+    }
+
+    fn local_addr(&self) -> SocketAddr {
+        self.endpoint.local_addr().unwrap()
+    }
+}
+
+#[derive(Debug)]
+struct QuicConnection(Connection);
+
+#[async_trait]
+impl TransportConnection for QuicConnection {
+    fn remote_address(&self) -> SocketAddr {
+        self.0.remote_address()
+    }
+
+    async fn open_uni(&self, priority: i32) -> Result<Box<dyn SendStream>, TransportError> {
+        let send = self.0.open_uni().await?;
+        let _ = send.set_priority(priority);
+        Ok(Box::new(QuicSendStream(send)))
+    }
+
+    async fn accept_uni(&self) -> Result<Box<dyn RecvStream>, TransportError> {
+        let recv = self.0.accept_uni().await?;
+        Ok(Box::new(QuicRecvStream(recv)))
+    }
+
+    fn max_datagram_size(&self) -> Option<usize> {
+        self.0.max_datagram_size()
+    }
+
+    fn send_datagram(&self, data: Vec<u8>) -> Result<(), TransportError> {
+        self.0.send_datagram(data.into())?;
+        Ok(())
+    }
+
+    async fn read_datagram(&self) -> Result<Vec<u8>, TransportError> {
+        let datagram = self.0.read_datagram().await?;
+        Ok(datagram.to_vec())
+    }
+
+    fn close(&self) {
+        self.0.close(0u32.into(), b"closed");
+    }
+
+    fn channel_binding(&self) -> Result<[u8; 32], TransportError> {
+        // TLS 세션마다 달라서 중간자가 양쪽 세션을 따로 맺으면 값이 어긋남
+        let mut binding = [0u8; 32];
+        self.0
+            .export_keying_material(&mut binding, b"EXPORTER-guild-handshake", b"")
+            .map_err(|_| "TLS keying material export failed")?;
+        Ok(binding)
+    }
+}
+
+struct QuicSendStream(quinn::SendStream);
+
+#[async_trait]
+impl SendStream for QuicSendStream {
+    async fn write_all(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        self.0.write_all(data).await?;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<(), TransportError> {
+        self.0.finish().await?;
+        Ok(())
+    }
+}
+
+struct QuicRecvStream(quinn::RecvStream);
+
+#[async_trait]
+impl RecvStream for QuicRecvStream {
+    async fn read_chunk(&mut self, max: usize) -> Result<Option<Vec<u8>>, TransportError> {
+        let chunk = self.0.read_chunk(max, true).await?;
+        Ok(chunk.map(|chunk| chunk.bytes.to_vec()))
+    }
+}
+
+// 인증서 검증 스킵 (개발용)
+struct SkipServerVerification;
+
+impl SkipServerVerification {
+    fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+}
+
+impl rustls::client::ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
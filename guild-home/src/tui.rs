@@ -217,11 +217,10 @@ impl TuiApp {
             .split(area.inner(&Margin::new(1, 1)));
 
         // 실제 Ping/Pong 성공률 계산
-        let ping_success_rate = if self.network_stats.pings_sent > 0 {
-            ((self.network_stats.pongs_received * 100) / self.network_stats.pings_sent).min(100)
-        } else {
-            0
-        };
+        let ping_success_rate = (self.network_stats.pongs_received * 100)
+            .checked_div(self.network_stats.pings_sent)
+            .unwrap_or(0)
+            .min(100);
         
         let gauge_color = if ping_success_rate >= 90 {
             Color::Green
@@ -0,0 +1,465 @@
+// Wire 포맷 - 버전이 있는 메시지 봉투 (envelope)
+//
+// 모든 스트림/데이터그램 메시지는 아래 9바이트 헤더 뒤에 페이로드가 붙는다.
+//
+// | 오프셋 | 크기 | 필드     | 설명                                   |
+// |--------|------|----------|----------------------------------------|
+// | 0      | 2    | magic    | `b"GH"` (0x47 0x48)                    |
+// | 2      | 1    | version  | 프로토콜 버전 (현재 1)                 |
+// | 3      | 1    | kind     | 메시지 종류 (`MessageKind`)            |
+// | 4      | 1    | flags    | 비트 플래그 (`FLAG_*`)                 |
+// | 5      | 4    | length   | 페이로드 길이 (u32 big-endian)         |
+// | 9      | N    | payload  | 종류별 페이로드                        |
+//
+// 헤더 레이아웃은 모든 버전에서 고정이므로, 수신 측은 모르는 kind를 length만큼
+// 건너뛸 수 있다. 단 FLAG_CRITICAL이 설정된 모르는 kind는 에러로 처리한다.
+// 구조체 페이로드(Ping/Pong)는 bincode로 인코딩하며, 새 필드는 뒤에만 추가한다
+// (이전 버전은 뒤쪽의 모르는 바이트를 무시함). 전체 명세는 WIRE_FORMAT.md 참고.
+use crate::network::Message;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const MAGIC: [u8; 2] = *b"GH";
+pub const PROTOCOL_VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 9;
+pub const MAX_PAYLOAD_LEN: usize = 1024 * 1024;
+
+/// 수신 측이 이 kind를 모르면 건너뛰지 말고 에러로 처리
+pub const FLAG_CRITICAL: u8 = 0x01;
+
+/// 메시지 종류 코드
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MessageKind {
+    Ping = 0x01,
+    Pong = 0x02,
+    Data = 0x03,
+    Datagram = 0x04,
+    Protocol = 0x05,
+    ProtocolUnsupported = 0x06,
+    OpenStream = 0x07,
+    Hello = 0x08,
+    HelloAuth = 0x09,
+}
+
+impl MessageKind {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x01 => Some(MessageKind::Ping),
+            0x02 => Some(MessageKind::Pong),
+            0x03 => Some(MessageKind::Data),
+            0x04 => Some(MessageKind::Datagram),
+            0x05 => Some(MessageKind::Protocol),
+            0x06 => Some(MessageKind::ProtocolUnsupported),
+            0x07 => Some(MessageKind::OpenStream),
+            0x08 => Some(MessageKind::Hello),
+            0x09 => Some(MessageKind::HelloAuth),
+            _ => None,
+        }
+    }
+}
+
+/// 디코딩된 헤더
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub version: u8,
+    pub kind: u8,
+    pub flags: u8,
+    pub length: u32,
+}
+
+/// 디코딩 결과
+#[derive(Debug, Clone)]
+pub enum Frame {
+    Message(Message),
+    /// 알 수 없는 kind - 페이로드를 건너뜀
+    Skipped {
+        kind: u8,
+        version: u8,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireError {
+    /// 헤더나 페이로드가 아직 다 도착하지 않음
+    Incomplete,
+    BadMagic([u8; 2]),
+    UnsupportedVersion(u8),
+    TooLarge(u32),
+    UnknownCritical(u8),
+    InvalidPayload(String),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Incomplete => write!(f, "incomplete frame"),
+            WireError::BadMagic(magic) => write!(f, "bad magic {:02x?}", magic),
+            WireError::UnsupportedVersion(version) => write!(f, "unsupported version {}", version),
+            WireError::TooLarge(length) => write!(f, "payload too large ({} bytes)", length),
+            WireError::UnknownCritical(kind) => write!(f, "unknown critical kind 0x{:02x}", kind),
+            WireError::InvalidPayload(e) => write!(f, "invalid payload: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+// Ping/Pong 페이로드 (필드는 뒤에만 추가)
+#[derive(Serialize, Deserialize)]
+struct PingPayload {
+    id: String,
+    timestamp: u64,
+}
+
+// 프로토콜 메시지 페이로드
+#[derive(Serialize, Deserialize)]
+struct ProtocolPayload {
+    protocol: String,
+    data: Vec<u8>,
+}
+
+// 핸드셰이크 페이로드 (필드는 뒤에만 추가)
+#[derive(Serialize, Deserialize)]
+struct HelloPayload {
+    network_id: String,
+    nonce: [u8; 32],
+}
+
+// 핸드셰이크 서명 페이로드 (필드는 뒤에만 추가)
+#[derive(Serialize, Deserialize)]
+struct HelloAuthPayload {
+    public_key: [u8; 32],
+    signature: Vec<u8>,
+}
+
+// ProtocolUnsupported/OpenStream 페이로드
+#[derive(Serialize, Deserialize)]
+struct ProtocolIdPayload {
+    protocol: String,
+}
+
+/// 메시지를 봉투로 인코딩 (페이로드 크기는 확인하지 않음 - 사용자 데이터는 `try_encode`)
+///
+/// 골든 벡터 (WIRE_FORMAT.md와 동일):
+///
+/// ```
+/// use guild_home::network::Message;
+/// use guild_home::wire;
+///
+/// let ping = Message::Ping { id: "a".to_string(), timestamp: 1 };
+/// assert_eq!(
+///     wire::encode(&ping),
+///     [
+///         0x47, 0x48, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x11, // 헤더
+///         0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x61, // id = "a"
+///         0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // timestamp = 1
+///     ]
+/// );
+///
+/// let data = Message::Data(vec![0xde, 0xad]);
+/// assert_eq!(
+///     wire::encode(&data),
+///     [0x47, 0x48, 0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x02, 0xde, 0xad]
+/// );
+///
+/// // 모르는 kind(0x7f)는 건너뛰고, 뒤따르는 프레임은 정상 디코딩
+/// let mut buf = vec![0x47, 0x48, 0x01, 0x7f, 0x00, 0x00, 0x00, 0x00, 0x01, 0xff];
+/// buf.extend(wire::encode(&data));
+/// let (first, used) = wire::decode(&buf).unwrap();
+/// assert!(matches!(first, wire::Frame::Skipped { kind: 0x7f, .. }));
+/// let (second, _) = wire::decode(&buf[used..]).unwrap();
+/// assert!(matches!(second, wire::Frame::Message(Message::Data(d)) if d == [0xde, 0xad]));
+///
+/// // 뒤에 필드가 추가된 Ping도 기존 필드만 읽어서 디코딩
+/// let mut newer = wire::encode(&ping);
+/// newer[8] = 0x15;
+/// newer.extend([0xaa, 0xbb, 0xcc, 0xdd]);
+/// let (frame, _) = wire::decode(&newer).unwrap();
+/// assert!(matches!(frame, wire::Frame::Message(Message::Ping { timestamp: 1, .. })));
+/// ```
+pub fn encode(msg: &Message) -> Vec<u8> {
+    let (kind, payload) = encode_payload(msg);
+    encode_raw(kind as u8, 0, &payload)
+}
+
+/// 메시지를 봉투로 인코딩하되, 페이로드가 MAX_PAYLOAD_LEN을 넘으면 TooLarge
+///
+/// 수신 측은 한도를 넘는 프레임을 받지 않으므로 보내기 전에 실패로 처리한다.
+pub fn try_encode(msg: &Message) -> Result<Vec<u8>, WireError> {
+    let (kind, payload) = encode_payload(msg);
+    if payload.len() > MAX_PAYLOAD_LEN {
+        let length = u32::try_from(payload.len()).unwrap_or(u32::MAX);
+        return Err(WireError::TooLarge(length));
+    }
+    Ok(encode_raw(kind as u8, 0, &payload))
+}
+
+fn encode_payload(msg: &Message) -> (MessageKind, Vec<u8>) {
+    match msg {
+        Message::Ping { id, timestamp } => (
+            MessageKind::Ping,
+            bincode::serialize(&PingPayload {
+                id: id.clone(),
+                timestamp: *timestamp,
+            })
+            .unwrap(),
+        ),
+        Message::Pong { id, timestamp } => (
+            MessageKind::Pong,
+            bincode::serialize(&PingPayload {
+                id: id.clone(),
+                timestamp: *timestamp,
+            })
+            .unwrap(),
+        ),
+        Message::Data(data) => (MessageKind::Data, data.clone()),
+        Message::Datagram(data) => (MessageKind::Datagram, data.clone()),
+        Message::Protocol { protocol, data } => (
+            MessageKind::Protocol,
+            bincode::serialize(&ProtocolPayload {
+                protocol: protocol.clone(),
+                data: data.clone(),
+            })
+            .unwrap(),
+        ),
+        Message::ProtocolUnsupported { protocol } => (
+            MessageKind::ProtocolUnsupported,
+            bincode::serialize(&ProtocolIdPayload {
+                protocol: protocol.clone(),
+            })
+            .unwrap(),
+        ),
+        Message::OpenStream { protocol } => (
+            MessageKind::OpenStream,
+            bincode::serialize(&ProtocolIdPayload {
+                protocol: protocol.clone(),
+            })
+            .unwrap(),
+        ),
+        Message::Hello { network_id, nonce } => (
+            MessageKind::Hello,
+            bincode::serialize(&HelloPayload {
+                network_id: network_id.clone(),
+                nonce: *nonce,
+            })
+            .unwrap(),
+        ),
+        Message::HelloAuth {
+            public_key,
+            signature,
+        } => (
+            MessageKind::HelloAuth,
+            bincode::serialize(&HelloAuthPayload {
+                public_key: *public_key,
+                signature: signature.clone(),
+            })
+            .unwrap(),
+        ),
+    }
+}
+
+/// 임의의 kind/flags로 프레임 인코딩 (길이 한도는 호출하는 쪽에서 확인)
+pub fn encode_raw(kind: u8, flags: u8, payload: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(HEADER_LEN + payload.len());
+    buf.extend_from_slice(&MAGIC);
+    buf.push(PROTOCOL_VERSION);
+    buf.push(kind);
+    buf.push(flags);
+    buf.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    buf.extend_from_slice(payload);
+    buf
+}
+
+/// 헤더만 디코딩
+pub fn decode_header(buf: &[u8]) -> Result<FrameHeader, WireError> {
+    if buf.len() < HEADER_LEN {
+        return Err(WireError::Incomplete);
+    }
+    if buf[0..2] != MAGIC {
+        return Err(WireError::BadMagic([buf[0], buf[1]]));
+    }
+
+    let header = FrameHeader {
+        version: buf[2],
+        kind: buf[3],
+        flags: buf[4],
+        length: u32::from_be_bytes([buf[5], buf[6], buf[7], buf[8]]),
+    };
+
+    // 헤더 레이아웃이 고정이므로 더 높은 버전도 읽을 수 있음 (0은 예약)
+    if header.version == 0 {
+        return Err(WireError::UnsupportedVersion(header.version));
+    }
+    if header.length as usize > MAX_PAYLOAD_LEN {
+        return Err(WireError::TooLarge(header.length));
+    }
+    Ok(header)
+}
+
+/// 버퍼 앞쪽의 프레임 하나를 디코딩하고 사용한 바이트 수를 반환
+pub fn decode(buf: &[u8]) -> Result<(Frame, usize), WireError> {
+    let header = decode_header(buf)?;
+    let total = HEADER_LEN + header.length as usize;
+    if buf.len() < total {
+        return Err(WireError::Incomplete);
+    }
+    let payload = &buf[HEADER_LEN..total];
+
+    let kind = match MessageKind::from_u8(header.kind) {
+        Some(kind) => kind,
+        None if header.flags & FLAG_CRITICAL != 0 => {
+            return Err(WireError::UnknownCritical(header.kind));
+        }
+        None => {
+            return Ok((
+                Frame::Skipped {
+                    kind: header.kind,
+                    version: header.version,
+                },
+                total,
+            ));
+        }
+    };
+
+    let msg = match kind {
+        MessageKind::Ping | MessageKind::Pong => {
+            let ping: PingPayload = bincode::deserialize(payload)
+                .map_err(|e| WireError::InvalidPayload(e.to_string()))?;
+            if kind == MessageKind::Ping {
+                Message::Ping {
+                    id: ping.id,
+                    timestamp: ping.timestamp,
+                }
+            } else {
+                Message::Pong {
+                    id: ping.id,
+                    timestamp: ping.timestamp,
+                }
+            }
+        }
+        MessageKind::Data => Message::Data(payload.to_vec()),
+        MessageKind::Datagram => Message::Datagram(payload.to_vec()),
+        MessageKind::Protocol => {
+            let message: ProtocolPayload = bincode::deserialize(payload)
+                .map_err(|e| WireError::InvalidPayload(e.to_string()))?;
+            Message::Protocol {
+                protocol: message.protocol,
+                data: message.data,
+            }
+        }
+        MessageKind::ProtocolUnsupported | MessageKind::OpenStream => {
+            let id: ProtocolIdPayload = bincode::deserialize(payload)
+                .map_err(|e| WireError::InvalidPayload(e.to_string()))?;
+            if kind == MessageKind::OpenStream {
+                Message::OpenStream {
+                    protocol: id.protocol,
+                }
+            } else {
+                Message::ProtocolUnsupported {
+                    protocol: id.protocol,
+                }
+            }
+        }
+        MessageKind::Hello => {
+            let hello: HelloPayload = bincode::deserialize(payload)
+                .map_err(|e| WireError::InvalidPayload(e.to_string()))?;
+            Message::Hello {
+                network_id: hello.network_id,
+                nonce: hello.nonce,
+            }
+        }
+        MessageKind::HelloAuth => {
+            let auth: HelloAuthPayload = bincode::deserialize(payload)
+                .map_err(|e| WireError::InvalidPayload(e.to_string()))?;
+            Message::HelloAuth {
+                public_key: auth.public_key,
+                signature: auth.signature,
+            }
+        }
+    };
+    Ok((Frame::Message(msg), total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_frame() -> Vec<u8> {
+        encode(&Message::Data(vec![0xde, 0xad]))
+    }
+
+    #[test]
+    fn bad_magic_is_rejected() {
+        let mut buf = data_frame();
+        buf[0] = b'X';
+        assert_eq!(decode(&buf).unwrap_err(), WireError::BadMagic([b'X', b'H']));
+    }
+
+    #[test]
+    fn reserved_version_is_rejected_and_newer_versions_decode() {
+        let mut buf = data_frame();
+        buf[2] = 0;
+        assert_eq!(decode(&buf).unwrap_err(), WireError::UnsupportedVersion(0));
+
+        // 헤더 레이아웃이 같으므로 더 높은 버전도 읽음
+        buf[2] = PROTOCOL_VERSION + 1;
+        let (frame, used) = decode(&buf).unwrap();
+        assert!(matches!(frame, Frame::Message(Message::Data(d)) if d == [0xde, 0xad]));
+        assert_eq!(used, buf.len());
+    }
+
+    #[test]
+    fn length_above_max_payload_is_rejected_from_header_alone() {
+        let mut header = encode_raw(MessageKind::Data as u8, 0, &[]);
+        let length = MAX_PAYLOAD_LEN as u32 + 1;
+        header[5..9].copy_from_slice(&length.to_be_bytes());
+        // 페이로드를 기다리지 않고 헤더만으로 거부
+        assert_eq!(decode(&header).unwrap_err(), WireError::TooLarge(length));
+
+        let max = encode_raw(MessageKind::Data as u8, 0, &vec![0; MAX_PAYLOAD_LEN]);
+        assert!(decode(&max).is_ok());
+    }
+
+    #[test]
+    fn truncated_header_and_payload_are_incomplete() {
+        let buf = data_frame();
+        for len in 0..buf.len() {
+            assert_eq!(decode(&buf[..len]).unwrap_err(), WireError::Incomplete, "len {}", len);
+        }
+        assert_eq!(decode(&buf).unwrap().1, buf.len());
+    }
+
+    #[test]
+    fn unknown_kind_is_skipped_unless_critical() {
+        let skipped = encode_raw(0x7f, 0, &[1, 2, 3]);
+        let (frame, used) = decode(&skipped).unwrap();
+        assert!(matches!(frame, Frame::Skipped { kind: 0x7f, version: PROTOCOL_VERSION }));
+        assert_eq!(used, skipped.len());
+
+        let critical = encode_raw(0x7f, FLAG_CRITICAL, &[1, 2, 3]);
+        assert_eq!(decode(&critical).unwrap_err(), WireError::UnknownCritical(0x7f));
+
+        // 알고 있는 kind는 FLAG_CRITICAL이 있어도 그대로 디코딩
+        let known = encode_raw(MessageKind::Data as u8, FLAG_CRITICAL, &[7]);
+        assert!(matches!(decode(&known).unwrap().0, Frame::Message(Message::Data(d)) if d == [7]));
+    }
+
+    #[test]
+    fn try_encode_rejects_oversize_payload() {
+        let max = Message::Data(vec![0; MAX_PAYLOAD_LEN]);
+        assert_eq!(try_encode(&max).unwrap(), encode(&max));
+
+        let over = Message::Data(vec![0; MAX_PAYLOAD_LEN + 1]);
+        assert_eq!(
+            try_encode(&over).unwrap_err(),
+            WireError::TooLarge(MAX_PAYLOAD_LEN as u32 + 1)
+        );
+    }
+
+    #[test]
+    fn malformed_payload_is_invalid() {
+        let buf = encode_raw(MessageKind::Ping as u8, 0, &[0xff]);
+        assert!(matches!(decode(&buf).unwrap_err(), WireError::InvalidPayload(_)));
+    }
+}
@@ -0,0 +1,375 @@
+// IPC 클라이언트 SDK - 애플리케이션이 guild-home에 연결해서 메시지를 주고받음
+//
+// 연결하면 버전 확인과 애플리케이션 등록을 마친 뒤, 백그라운드 태스크가 수신 메시지를 계속 읽어 채널에
+// 넣는다. 그래서 recv()는 tokio::select!에서 취소되어도 메시지를 잃지 않는다. 요청에 대한
+// 응답(PeerList 등)은 채널로 가지 않고 기다리는 요청에 바로 전달된다.
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use crate::codec::{read_message, write_message, IpcError};
+use crate::message::{validate_app_id, IPCMessage, PeerId, IPC_VERSION};
+
+/// 요청 응답 대기 시간
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// FindPeers 응답 대기 시간 (DHT 조회와 연결 시도를 기다림)
+pub const FIND_PEERS_TIMEOUT: Duration = Duration::from_secs(30);
+/// 핸드셰이크 응답 대기 시간 (서버 쪽 accept_handshake도 같은 시간만 기다림)
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 아직 읽지 않은 수신 메시지 최대 수 (가득 차면 읽기를 멈춤)
+const INCOMING_CAPACITY: usize = 1024;
+
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+type PendingPeers = Arc<std::sync::Mutex<VecDeque<oneshot::Sender<Vec<PeerId>>>>>;
+
+/// guild-home IPC 연결
+pub struct IPCClient {
+    writer: Mutex<Writer>,
+    incoming: Mutex<mpsc::Receiver<IPCMessage>>,
+    // GetPeers/FindPeers 요청 순서대로 응답을 기다리는 쪽
+    pending_peers: PendingPeers,
+    server_version: u32,
+    app: Option<String>,
+}
+
+impl IPCClient {
+    /// 로컬 guild-home IPC 포트에 연결 (애플리케이션 id 없이 태그 없는 데이터를 주고받음)
+    pub async fn connect(port: u16) -> Result<Self, IpcError> {
+        Self::connect_addr(SocketAddr::from(([127, 0, 0, 1], port)), None).await
+    }
+
+    /// 애플리케이션 id로 등록하며 연결 (같은 id의 메시지만 받음)
+    pub async fn connect_app(port: u16, app: &str) -> Result<Self, IpcError> {
+        Self::connect_addr(SocketAddr::from(([127, 0, 0, 1], port)), Some(app)).await
+    }
+
+    pub async fn connect_addr(addr: SocketAddr, app: Option<&str>) -> Result<Self, IpcError> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        Self::from_stream(stream, app).await
+    }
+
+    /// guild-home의 Unix 소켓(data_dir/ipc.sock 등)에 연결
+    #[cfg(unix)]
+    pub async fn connect_unix(
+        path: impl AsRef<std::path::Path>,
+        app: Option<&str>,
+    ) -> Result<Self, IpcError> {
+        let stream = tokio::net::UnixStream::connect(path).await?;
+        Self::from_stream(stream, app).await
+    }
+
+    /// 이미 연결된 스트림으로 핸드셰이크 후 클라이언트 생성
+    pub async fn from_stream<S>(mut stream: S, app: Option<&str>) -> Result<Self, IpcError>
+    where
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        if let Some(app) = app {
+            validate_app_id(app).map_err(IpcError::Handshake)?;
+        }
+        let app = app.map(str::to_string);
+        let server_version =
+            tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake(&mut stream, app.clone()))
+                .await
+                .map_err(|_| IpcError::Timeout)??;
+
+        let (mut reader, writer) = tokio::io::split(stream);
+        let (tx, rx) = mpsc::channel(INCOMING_CAPACITY);
+        let pending_peers: PendingPeers = Arc::default();
+
+        let pending = pending_peers.clone();
+        tokio::spawn(async move {
+            while let Ok(msg) = read_message(&mut reader).await {
+                match msg {
+                    IPCMessage::PeerList(peers) => {
+                        if let Some(waiter) = pending.lock().unwrap().pop_front() {
+                            let _ = waiter.send(peers);
+                        }
+                    }
+                    msg => {
+                        if tx.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            // 연결이 끊기면 기다리던 요청도 실패 처리 (보내는 쪽을 버려서)
+            pending.lock().unwrap().clear();
+        });
+
+        Ok(Self {
+            writer: Mutex::new(Box::new(writer)),
+            incoming: Mutex::new(rx),
+            pending_peers,
+            server_version,
+            app,
+        })
+    }
+
+    /// 핸드셰이크에서 받은 guild-home의 프로토콜 버전
+    pub fn server_version(&self) -> u32 {
+        self.server_version
+    }
+
+    /// 등록한 애플리케이션 id
+    pub fn app(&self) -> Option<&str> {
+        self.app.as_deref()
+    }
+
+    /// 메시지 전송
+    pub async fn send(&self, msg: IPCMessage) -> Result<(), IpcError> {
+        write_message(&mut *self.writer.lock().await, &msg).await
+    }
+
+    /// 다음 수신 메시지 (연결이 끊기면 Closed, select!에서 써도 안전)
+    pub async fn recv(&self) -> Result<IPCMessage, IpcError> {
+        self.incoming
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(IpcError::Closed)
+    }
+
+    /// 모든 피어에게 브로드캐스트 요청
+    pub async fn broadcast(&self, data: Vec<u8>) -> Result<(), IpcError> {
+        self.send(IPCMessage::Broadcast(data)).await
+    }
+
+    /// 특정 피어에게 전송
+    pub async fn send_to(&self, peer: PeerId, data: Vec<u8>) -> Result<(), IpcError> {
+        self.send(IPCMessage::SendTo { peer, data }).await
+    }
+
+    /// 현재 연결된 피어 목록
+    pub async fn peers(&self) -> Result<Vec<PeerId>, IpcError> {
+        self.request_peers(IPCMessage::GetPeers, DEFAULT_REQUEST_TIMEOUT)
+            .await
+    }
+
+    /// capability 태그를 가진 피어를 최대 count개 찾기
+    ///
+    /// guild-home이 mDNS와 DHT 광고에서 찾고, 아직 연결되지 않은 피어에는 연결을 시도한다.
+    /// 연결된 피어만 반환하므로 결과의 id로 바로 send_to를 쓸 수 있다.
+    pub async fn find_peers(
+        &self,
+        capability: &str,
+        count: usize,
+    ) -> Result<Vec<PeerId>, IpcError> {
+        self.request_peers(
+            IPCMessage::FindPeers {
+                capability: capability.to_string(),
+                count: count.min(u32::MAX as usize) as u32,
+            },
+            FIND_PEERS_TIMEOUT,
+        )
+        .await
+    }
+
+    // PeerList로 응답하는 요청 (guild-home은 요청 순서대로 응답함)
+    async fn request_peers(
+        &self,
+        request: IPCMessage,
+        timeout: Duration,
+    ) -> Result<Vec<PeerId>, IpcError> {
+        let (tx, rx) = oneshot::channel();
+        // 쓰기 잠금을 잡은 채로 넣어야 대기 순서가 요청 순서와 같고, 실패하면 맨 뒤가 우리 것
+        let mut writer = self.writer.lock().await;
+        self.pending_peers.lock().unwrap().push_back(tx);
+        if let Err(e) = write_message(&mut *writer, &request).await {
+            self.pending_peers.lock().unwrap().pop_back();
+            return Err(e);
+        }
+        drop(writer);
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(peers)) => Ok(peers),
+            Ok(Err(_)) => Err(IpcError::Closed),
+            Err(_) => Err(IpcError::Timeout),
+        }
+    }
+}
+
+/// Hello와 Register를 보내고 guild-home의 Hello를 기다림
+async fn handshake<S>(stream: &mut S, app: Option<String>) -> Result<u32, IpcError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_message(
+        stream,
+        &IPCMessage::Hello {
+            version: IPC_VERSION,
+        },
+    )
+    .await?;
+    write_message(stream, &IPCMessage::Register { app }).await?;
+    match read_message(stream).await? {
+        IPCMessage::Hello { version } if version == IPC_VERSION => Ok(version),
+        IPCMessage::Hello { version } => Err(IpcError::VersionMismatch {
+            ours: IPC_VERSION,
+            theirs: version,
+        }),
+        IPCMessage::Error(reason) => Err(IpcError::Handshake(reason)),
+        other => Err(IpcError::Handshake(format!("unexpected {:?}", other))),
+    }
+}
+
+/// 서버(guild-home) 쪽 핸드셰이크 - 클라이언트의 Hello와 Register를 받고 Hello로 응답
+///
+/// 등록한 애플리케이션 id를 반환한다. 버전이 다르거나, id가 잘못되었거나, 메시지 순서가
+/// 맞지 않으면 Error를 보내고 Err를 반환한다 (버전이 다르면 Register를 기다리지 않음).
+/// 클라이언트가 HANDSHAKE_TIMEOUT 안에 끝내지 않으면 Timeout을 반환한다.
+pub async fn accept_handshake<S>(stream: &mut S) -> Result<Option<String>, IpcError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    tokio::time::timeout(HANDSHAKE_TIMEOUT, accept(stream))
+        .await
+        .map_err(|_| IpcError::Timeout)?
+}
+
+async fn accept<S>(stream: &mut S) -> Result<Option<String>, IpcError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let result = match read_message(stream).await? {
+        IPCMessage::Hello { version } if version == IPC_VERSION => {
+            match read_message(stream).await? {
+                IPCMessage::Register { app: Some(app) } => match validate_app_id(&app) {
+                    Ok(()) => Ok(Some(app)),
+                    Err(reason) => Err(IpcError::Handshake(reason)),
+                },
+                IPCMessage::Register { app: None } => Ok(None),
+                other => Err(IpcError::Handshake(format!(
+                    "expected Register, got {:?}",
+                    other
+                ))),
+            }
+        }
+        IPCMessage::Hello { version } => Err(IpcError::VersionMismatch {
+            ours: IPC_VERSION,
+            theirs: version,
+        }),
+        other => Err(IpcError::Handshake(format!(
+            "expected Hello, got {:?}",
+            other
+        ))),
+    };
+    let reply = match &result {
+        Ok(_) => IPCMessage::Hello {
+            version: IPC_VERSION,
+        },
+        Err(e) => IPCMessage::Error(e.to_string()),
+    };
+    write_message(stream, &reply).await?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn client_and_server_handshake() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let server = tokio::spawn(async move { accept_handshake(&mut server).await });
+        let client = IPCClient::from_stream(client, Some("chat")).await.unwrap();
+        assert_eq!(client.server_version(), IPC_VERSION);
+        assert_eq!(server.await.unwrap().unwrap(), Some("chat".to_string()));
+    }
+
+    #[tokio::test]
+    async fn server_rejects_other_version() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let server = tokio::spawn(async move { accept_handshake(&mut server).await });
+        let old = IPC_VERSION - 1;
+        write_message(&mut client, &IPCMessage::Hello { version: old })
+            .await
+            .unwrap();
+
+        let result = server.await.unwrap();
+        assert!(matches!(
+            result,
+            Err(IpcError::VersionMismatch { ours: IPC_VERSION, theirs }) if theirs == old
+        ));
+        // Register를 기다리지 않고 Error로 응답
+        assert!(matches!(
+            read_message(&mut client).await.unwrap(),
+            IPCMessage::Error(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn client_rejects_other_version() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            read_message(&mut server).await.unwrap();
+            read_message(&mut server).await.unwrap();
+            let newer = IPCMessage::Hello {
+                version: IPC_VERSION + 1,
+            };
+            write_message(&mut server, &newer).await.unwrap();
+            // 클라이언트가 응답을 읽을 때까지 연결 유지
+            let _ = read_message(&mut server).await;
+        });
+
+        let result = IPCClient::from_stream(client, None).await;
+        assert!(matches!(
+            result,
+            Err(IpcError::VersionMismatch { ours: IPC_VERSION, theirs }) if theirs == IPC_VERSION + 1
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_client_times_out() {
+        let (_client, mut server) = tokio::io::duplex(1024);
+        let result = accept_handshake(&mut server).await;
+        assert!(matches!(result, Err(IpcError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn new_client_works_after_server_closes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            // 첫 연결은 핸드셰이크 후 바로 닫고, 두 번째 연결은 메시지를 되돌려 보냄
+            let (mut stream, _) = listener.accept().await.unwrap();
+            accept_handshake(&mut stream).await.unwrap();
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            accept_handshake(&mut stream).await.unwrap();
+            while let Ok(msg) = read_message(&mut stream).await {
+                write_message(&mut stream, &msg).await.unwrap();
+            }
+        });
+
+        let client = IPCClient::connect_addr(addr, Some("chat")).await.unwrap();
+        assert!(matches!(client.recv().await, Err(IpcError::Closed)));
+
+        let client = IPCClient::connect_addr(addr, Some("chat")).await.unwrap();
+        client.broadcast(vec![1, 2, 3]).await.unwrap();
+        assert_eq!(
+            client.recv().await.unwrap(),
+            IPCMessage::Broadcast(vec![1, 2, 3])
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_request_leaves_no_waiter() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            accept_handshake(&mut server).await.unwrap();
+        });
+        let client = IPCClient::from_stream(client, None).await.unwrap();
+        assert!(matches!(client.recv().await, Err(IpcError::Closed)));
+
+        // 보내지 못한 요청은 나중에 오는 PeerList를 가로채지 않도록 대기열에서 빠짐
+        assert!(client.peers().await.is_err());
+        assert!(client.pending_peers.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+// IPC 프레이밍 - 4바이트 빅엔디언 길이 + bincode로 직렬화한 IPCMessage
+use std::fmt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::message::IPCMessage;
+
+/// 프레임 하나의 최대 크기 (잘못된 길이로 큰 버퍼를 잡지 않도록)
+pub const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum IpcError {
+    Io(std::io::Error),
+    /// 직렬화/역직렬화 실패
+    Codec(bincode::Error),
+    FrameTooLarge(usize),
+    /// 상대 프로토콜 버전이 다름
+    VersionMismatch {
+        ours: u32,
+        theirs: u32,
+    },
+    /// 핸드셰이크에서 예상하지 못한 메시지나 상대가 보낸 Error
+    Handshake(String),
+    /// 연결이 닫힘
+    Closed,
+    Timeout,
+}
+
+impl fmt::Display for IpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IpcError::Io(e) => write!(f, "IPC I/O error: {}", e),
+            IpcError::Codec(e) => write!(f, "IPC codec error: {}", e),
+            IpcError::FrameTooLarge(size) => write!(
+                f,
+                "IPC frame too large ({} > {} bytes)",
+                size, MAX_FRAME_SIZE
+            ),
+            IpcError::VersionMismatch { ours, theirs } => {
+                write!(f, "IPC version mismatch (ours {}, theirs {})", ours, theirs)
+            }
+            IpcError::Handshake(reason) => write!(f, "IPC handshake failed: {}", reason),
+            IpcError::Closed => write!(f, "IPC connection closed"),
+            IpcError::Timeout => write!(f, "IPC request timed out"),
+        }
+    }
+}
+
+impl std::error::Error for IpcError {}
+
+impl From<std::io::Error> for IpcError {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::UnexpectedEof {
+            IpcError::Closed
+        } else {
+            IpcError::Io(e)
+        }
+    }
+}
+
+impl From<bincode::Error> for IpcError {
+    fn from(e: bincode::Error) -> Self {
+        IpcError::Codec(e)
+    }
+}
+
+/// 길이 프리픽스를 포함한 프레임으로 인코딩
+///
+/// ```
+/// use guild_ipc::{decode, encode, IPCMessage};
+///
+/// let msg = IPCMessage::Broadcast(vec![1, 2, 3]);
+/// let frame = encode(&msg).unwrap();
+/// let len = u32::from_be_bytes(frame[..4].try_into().unwrap()) as usize;
+/// assert_eq!(len, frame.len() - 4);
+/// assert_eq!(decode(&frame[4..]).unwrap(), msg);
+/// ```
+pub fn encode(msg: &IPCMessage) -> Result<Vec<u8>, IpcError> {
+    let payload = bincode::serialize(msg)?;
+    if payload.len() > MAX_FRAME_SIZE {
+        return Err(IpcError::FrameTooLarge(payload.len()));
+    }
+    let mut frame = Vec::with_capacity(4 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// 프레임 본문(길이 프리픽스 제외) 디코딩
+pub fn decode(payload: &[u8]) -> Result<IPCMessage, IpcError> {
+    Ok(bincode::deserialize(payload)?)
+}
+
+/// 스트림에서 메시지 하나 읽기 (취소되면 스트림 위치가 어긋나므로 select!에 직접 쓰지 말 것)
+pub async fn read_message<R: AsyncRead + Unpin>(reader: &mut R) -> Result<IPCMessage, IpcError> {
+    let mut len_bytes = [0u8; 4];
+    reader.read_exact(&mut len_bytes).await?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(IpcError::FrameTooLarge(len));
+    }
+
+    let mut buffer = vec![0u8; len];
+    reader.read_exact(&mut buffer).await?;
+    decode(&buffer)
+}
+
+/// 스트림에 메시지 하나 쓰기
+pub async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    msg: &IPCMessage,
+) -> Result<(), IpcError> {
+    let frame = encode(msg)?;
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn messages_round_trip_over_a_stream() {
+        let messages = vec![
+            IPCMessage::Hello { version: 2 },
+            IPCMessage::Register {
+                app: Some("chat".to_string()),
+            },
+            IPCMessage::Broadcast(vec![7; 1000]),
+            IPCMessage::SendTo {
+                peer: [9; 32],
+                data: Vec::new(),
+            },
+            IPCMessage::GetPeers,
+            IPCMessage::FindPeers {
+                capability: "validator".to_string(),
+                count: 3,
+            },
+        ];
+        let (mut writer, mut reader) = tokio::io::duplex(64 * 1024);
+        for msg in &messages {
+            write_message(&mut writer, msg).await.unwrap();
+        }
+        for msg in &messages {
+            assert_eq!(&read_message(&mut reader).await.unwrap(), msg);
+        }
+    }
+
+    #[tokio::test]
+    async fn oversize_frames_are_rejected() {
+        let msg = IPCMessage::Broadcast(vec![0; MAX_FRAME_SIZE + 1]);
+        assert!(matches!(encode(&msg), Err(IpcError::FrameTooLarge(_))));
+
+        // 길이만 보고 거부 (본문을 기다리거나 버퍼를 잡지 않음)
+        let (mut writer, mut reader) = tokio::io::duplex(64);
+        let len = (MAX_FRAME_SIZE + 1) as u32;
+        writer.write_all(&len.to_be_bytes()).await.unwrap();
+        assert!(matches!(
+            read_message(&mut reader).await,
+            Err(IpcError::FrameTooLarge(size)) if size == MAX_FRAME_SIZE + 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn truncated_frame_is_closed() {
+        let frame = encode(&IPCMessage::Broadcast(vec![1, 2, 3, 4])).unwrap();
+        let (mut writer, mut reader) = tokio::io::duplex(64);
+        writer.write_all(&frame[..frame.len() - 2]).await.unwrap();
+        drop(writer);
+        assert!(matches!(
+            read_message(&mut reader).await,
+            Err(IpcError::Closed)
+        ));
+
+        // 길이가 맞아도 본문이 메시지가 아니면 Codec 오류
+        assert!(matches!(decode(&[0xff; 3]), Err(IpcError::Codec(_))));
+    }
+}
@@ -0,0 +1,8 @@
+// Guild IPC - guild-home과 로컬 애플리케이션 사이의 IPC 프로토콜과 클라이언트 SDK
+pub mod client;
+pub mod codec;
+pub mod message;
+
+pub use client::{accept_handshake, IPCClient, DEFAULT_REQUEST_TIMEOUT, FIND_PEERS_TIMEOUT};
+pub use codec::{decode, encode, read_message, write_message, IpcError, MAX_FRAME_SIZE};
+pub use message::{validate_app_id, IPCMessage, PeerId, IPC_VERSION, MAX_APP_ID_LEN};
@@ -0,0 +1,87 @@
+// IPC 메시지 - guild-home과 로컬 애플리케이션(블록체인 등)이 주고받는 메시지
+//
+// 연결하면 클라이언트가 Hello와 Register(애플리케이션 id)를 연달아 보내고, guild-home은
+// 둘 다 받아들이면 Hello로, 버전이 다르거나 id가 잘못되었으면 Error로 응답한 뒤 연결을
+// 끊는다. 이후에는 양쪽이 자유롭게 메시지를 보낸다.
+//
+// 애플리케이션 id를 등록한 클라이언트의 브로드캐스트는 그 id로 태그되어 전송되고, 피어에게서
+// 같은 id로 태그된 메시지만 그 클라이언트들에게 전달된다. id 없이 등록하면 태그 없는 데이터를
+// 주고받는다.
+use serde::{Deserialize, Serialize};
+
+/// 프로토콜 버전 (메시지 구조가 바뀌면 증가)
+pub const IPC_VERSION: u32 = 3;
+
+/// 애플리케이션 id 최대 길이
+pub const MAX_APP_ID_LEN: usize = 64;
+
+/// 피어 식별자
+pub type PeerId = [u8; 32];
+
+/// 애플리케이션 id 검사 (영문자, 숫자, `-`, `_`, `.`만 허용)
+///
+/// ```
+/// use guild_ipc::validate_app_id;
+///
+/// assert!(validate_app_id("minimal-blockchain").is_ok());
+/// assert!(validate_app_id("chat/v1").is_err());
+/// assert!(validate_app_id("").is_err());
+/// ```
+pub fn validate_app_id(app: &str) -> Result<(), String> {
+    let valid = !app.is_empty()
+        && app.len() <= MAX_APP_ID_LEN
+        && app
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid app id {:?}", app))
+    }
+}
+
+/// IPC 메시지
+///
+/// bincode는 variant 순서로 인코딩하므로 새 variant는 끝에 추가한다.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IPCMessage {
+    // Guild-Home -> 애플리케이션
+    PeerMessage {
+        from: PeerId,
+        data: Vec<u8>,
+    },
+    PeerJoined(PeerId),
+    PeerLeft(PeerId),
+
+    // 애플리케이션 -> Guild-Home
+    Broadcast(Vec<u8>),
+    SendTo {
+        peer: PeerId,
+        data: Vec<u8>,
+    },
+
+    // 양방향
+    /// 연결 직후 버전 확인
+    Hello {
+        version: u32,
+    },
+    /// 요청을 처리할 수 없음 (버전 불일치 등, 보낸 쪽은 곧 연결을 끊을 수 있음)
+    Error(String),
+
+    // 요청/응답
+    /// 현재 연결된 피어 목록 요청
+    GetPeers,
+    /// GetPeers 응답
+    PeerList(Vec<PeerId>),
+
+    /// 핸드셰이크에서 Hello 다음에 보내는 애플리케이션 등록 (None이면 태그 없는 데이터)
+    Register {
+        app: Option<String>,
+    },
+
+    /// capability 태그(validator 등)를 가진 피어 검색 (응답은 PeerList, 최대 count개)
+    FindPeers {
+        capability: String,
+        count: u32,
+    },
+}
@@ -7,7 +7,6 @@ pub struct SimpleConsensus {
     pub node_id: [u8; 32],
     validators: Vec<[u8; 32]>,
     current_height: u64,
-    current_round: u32,
     votes: HashMap<u64, Vec<BlockVote>>,
     last_block: Option<MinimalBlock>,
     committed_blocks: Vec<MinimalBlock>,
@@ -20,7 +19,6 @@ impl SimpleConsensus {
             node_id,
             validators: vec![node_id], // 처음엔 자신만
             current_height: 0,
-            current_round: 0,
             votes: HashMap::new(),
             last_block: None,
             committed_blocks: Vec::new(),
@@ -83,7 +81,7 @@ impl SimpleConsensus {
     pub fn add_vote(&mut self, vote: BlockVote) {
         let quorum_size = self.get_quorum_size();
         let vote_height = vote.height;
-        let votes = self.votes.entry(vote.height).or_insert_with(Vec::new);
+        let votes = self.votes.entry(vote.height).or_default();
         
         // 중복 투표 방지
         if !votes.iter().any(|v| v.voter == vote.voter) {
@@ -1,15 +1,17 @@
 // 최소 블록체인 - Guild-Home 네트워크 사용
 mod types;
 mod consensus;
-mod ipc;
 
 use consensus::SimpleConsensus;
-use ipc::{IPCClient, IPCMessage};
+use guild_ipc::{IPCClient, IPCMessage};
 use std::env;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::time::{interval, Duration};
 use types::{ConsensusMessage, MinimalBlock};
 
+/// Guild-Home에 등록하는 애플리케이션 id (같은 id의 노드끼리만 메시지를 주고받음)
+const APP_ID: &str = "minimal-blockchain";
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     guild_logger::init_logger(false);
@@ -19,6 +21,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 명령줄 인자 파싱
     let args: Vec<String> = env::args().collect();
     let mut ipc_port: u16 = 9000; // 기본 포트
+    let mut ipc_socket: Option<String> = None; // 지정하면 TCP 대신 Unix 소켓 사용
     
     // --port 플래그 처리
     for i in 0..args.len() {
@@ -35,10 +38,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 std::process::exit(1);
             }
         }
+        if args[i] == "--socket" || args[i] == "-s" {
+            if i + 1 < args.len() {
+                ipc_socket = Some(args[i + 1].clone());
+            } else {
+                eprintln!("❌ --socket 플래그 뒤에 소켓 경로가 필요합니다");
+                eprintln!("사용법: {} [--socket <PATH>]", args[0]);
+                std::process::exit(1);
+            }
+        }
         if args[i] == "--help" || args[i] == "-h" {
             println!("사용법: {} [옵션]", args[0]);
             println!("옵션:");
             println!("  -p, --port <PORT>    IPC 포트 설정 (기본값: 9000)");
+            println!("  -s, --socket <PATH>  Guild-Home IPC Unix 소켓 경로 (예: ./data/ipc.sock)");
             println!("  -h, --help           도움말 표시");
             std::process::exit(0);
         }
@@ -50,15 +63,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             ipc_port = port;
         }
     }
+    if let Ok(env_socket) = env::var("IPC_SOCKET") {
+        ipc_socket = Some(env_socket);
+    }
     
     // 노드 ID 생성
     let node_id = generate_node_id();
-    println!("📍 노드 ID: {}", hex::encode(&node_id));
+    println!("📍 노드 ID: {}", hex::encode(node_id));
     
     // IPC 연결 (Guild-Home에 연결)
-    println!("🔌 Guild-Home 연결 중... (포트: {})", ipc_port);
-    let mut ipc = IPCClient::connect(ipc_port).await?;
-    println!("✅ Guild-Home 연결 성공!");
+    let ipc = match &ipc_socket {
+        Some(path) => {
+            println!("🔌 Guild-Home 연결 중... (소켓: {})", path);
+            IPCClient::connect_unix(path, Some(APP_ID)).await?
+        }
+        None => {
+            println!("🔌 Guild-Home 연결 중... (포트: {})", ipc_port);
+            IPCClient::connect_app(ipc_port, APP_ID).await?
+        }
+    };
+    println!("✅ Guild-Home 연결 성공! (IPC 프로토콜 v{})", ipc.server_version());
     
     // 컨센서스 엔진 초기화
     let mut consensus = SimpleConsensus::new(node_id);
@@ -85,9 +109,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
             
-            // Guild-Home으로부터 메시지 수신
-            Ok(msg) = ipc.recv() => {
-                handle_ipc_message(&mut consensus, &mut ipc, msg).await?;
+            // Guild-Home으로부터 메시지 수신 (연결이 끊기면 종료)
+            msg = ipc.recv() => {
+                handle_ipc_message(&mut consensus, &ipc, msg?).await?;
             }
         }
     }
@@ -96,7 +120,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 // IPC 메시지 처리
 async fn handle_ipc_message(
     consensus: &mut SimpleConsensus,
-    ipc: &mut IPCClient,
+    ipc: &IPCClient,
     msg: IPCMessage,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match msg {
@@ -128,6 +152,7 @@ async fn handle_ipc_message(
                         if consensus.check_quorum(vote.height) {
                             println!("🎉 블록 #{} 확정! (2/3 투표 달성)", vote.height);
                             consensus.finalize_block(vote.height);
+                            consensus.print_stats();
                         }
                     }
                     
@@ -68,10 +68,3 @@ impl BlockVote {
         }
     }
 }
-
-/// 피어 정보
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PeerInfo {
-    pub id: [u8; 32],
-    pub address: String,
-}
\ No newline at end of file